@@ -0,0 +1,208 @@
+//! Rough throughput comparisons, run with `cargo bench`. Each case reports
+//! the best of several timed runs.
+
+use json_parser::{Interner, JsonParser, JsonValue, ParserOptions, from_str_with, validate_with};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+fn best_of<F: FnMut()>(runs: usize, mut f: F) -> Duration {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(name: &str, bytes: usize, elapsed: Duration) {
+    let mb_per_s = bytes as f64 / elapsed.as_secs_f64() / 1e6;
+    println!("{name:<40} {elapsed:>12.3?} {mb_per_s:>8.1} MB/s");
+}
+
+fn numbers_heavy() -> String {
+    let numbers: Vec<String> = (0..200_000)
+        .map(|i| format!("{}.{}e-{}", i * 7919 % 100_003, i % 1000, i % 30))
+        .collect();
+    format!("[{}]", numbers.join(","))
+}
+
+/// Mostly strings and keys, so the scanner's per-character work dominates.
+fn ascii_heavy() -> String {
+    let records: Vec<String> = (0..50_000)
+        .map(|i| {
+            format!(
+                r#"{{"id": "user-{i}", "name": "Some Name {i}", "email": "user{i}@example.com", "active": true}}"#
+            )
+        })
+        .collect();
+    format!("[{}]", records.join(","))
+}
+
+/// Many small request-like bodies.
+fn small_documents() -> Vec<String> {
+    (0..20_000)
+        .map(|i| {
+            format!(
+                r#"{{"id": {i}, "tags": ["a", "b", "c", "d", "e"], "point": [{i}, 2, 3],
+                    "meta": {{"k1": 1, "k2": 2, "k3": 3, "k4": 4, "k5": [true, false, null]}}}}"#
+            )
+        })
+        .collect()
+}
+
+/// A few multi-megabyte strings that are mostly `\n` escapes.
+fn escape_heavy() -> String {
+    let line = format!("{}\\n", "x".repeat(8));
+    let strings: Vec<String> = (0..4)
+        .map(|_| format!("\"{}\"", line.repeat(200_000)))
+        .collect();
+    format!("[{}]", strings.join(","))
+}
+
+/// Records whose string values mostly come from small sets, like enums.
+fn enum_heavy() -> String {
+    let statuses = ["active", "suspended", "pending_verification"];
+    let regions = ["eu-west-1", "us-east-1", "ap-southeast-2", "sa-east-1"];
+    let records: Vec<String> = (0..50_000)
+        .map(|i| {
+            let status = statuses[i % statuses.len()];
+            let region = regions[i % regions.len()];
+            format!(
+                r#"{{"id": {i}, "status": "{status}", "region": "{region}", "plan": "standard"}}"#
+            )
+        })
+        .collect();
+    format!("[{}]", records.join(","))
+}
+
+/// Bytes of string data in `value`, keys included, with no sharing.
+fn string_bytes(value: &JsonValue) -> usize {
+    match value {
+        JsonValue::String(s) => s.len(),
+        JsonValue::Array(elements) => elements.iter().map(string_bytes).sum(),
+        JsonValue::Object(members) => members.iter().map(|(k, v)| k.len() + string_bytes(v)).sum(),
+        _ => 0,
+    }
+}
+
+fn main() {
+    let documents = small_documents();
+    let bytes = documents.iter().map(String::len).sum();
+    let options = ParserOptions::default();
+    let elapsed = best_of(5, || {
+        for document in &documents {
+            black_box(from_str_with(black_box(document), &options).unwrap());
+        }
+    });
+    report("small_documents/fresh", bytes, elapsed);
+    let mut parser = JsonParser::new();
+    let elapsed = best_of(5, || {
+        for document in &documents {
+            black_box(parser.parse(black_box(document)).unwrap());
+        }
+    });
+    report("small_documents/reused", bytes, elapsed);
+    // Run with `--features arena`.
+    #[cfg(feature = "arena")]
+    {
+        let mut arena = json_parser::Arena::new();
+        let elapsed = best_of(5, || {
+            for document in &documents {
+                black_box(json_parser::parse_in(black_box(document), &arena).unwrap());
+                arena.reset();
+            }
+        });
+        report("small_documents/arena", bytes, elapsed);
+    }
+
+    // A single non-ASCII character turns off the ASCII fast path for the
+    // whole document, giving the UTF-8-aware scanner as a baseline.
+    let ascii = ascii_heavy();
+    let mixed = ascii.replacen("Some Name 0", "Some Name \u{e9}", 1);
+    for (name, input) in [("ascii", &ascii), ("non_ascii", &mixed)] {
+        let elapsed = best_of(5, || {
+            validate_with(black_box(input), &ParserOptions::default()).unwrap();
+        });
+        report(&format!("strings/validate/{name}"), input.len(), elapsed);
+    }
+
+    // The byte path checks UTF-8 up front unless the caller vouches for
+    // it; the `&str` path never does.
+    let bytes = mixed.as_bytes();
+    let elapsed = best_of(5, || {
+        black_box(from_str_with(black_box(&mixed), &ParserOptions::default()).unwrap());
+    });
+    report("utf8/from_str", bytes.len(), elapsed);
+    let elapsed = best_of(5, || {
+        black_box(json_parser::from_slice(black_box(bytes)).unwrap());
+    });
+    report("utf8/from_slice", bytes.len(), elapsed);
+    let elapsed = best_of(5, || {
+        // SAFETY: `bytes` came from a `String`.
+        black_box(unsafe { json_parser::from_utf8_unchecked_slice(black_box(bytes)) }.unwrap());
+    });
+    report("utf8/from_utf8_unchecked_slice", bytes.len(), elapsed);
+
+    let input = escape_heavy();
+    let elapsed = best_of(5, || {
+        black_box(from_str_with(black_box(&input), &ParserOptions::default()).unwrap());
+    });
+    report("escapes/parse", input.len(), elapsed);
+    let elapsed = best_of(5, || {
+        validate_with(black_box(&input), &ParserOptions::default()).unwrap();
+    });
+    report("escapes/validate", input.len(), elapsed);
+
+    let input = enum_heavy();
+    let elapsed = best_of(5, || {
+        black_box(from_str_with(black_box(&input), &ParserOptions::default()).unwrap());
+    });
+    report("enums/owned", input.len(), elapsed);
+    // Keys and values without escapes borrow from the input.
+    #[cfg(feature = "arena")]
+    {
+        let mut arena = json_parser::Arena::new();
+        let elapsed = best_of(5, || {
+            black_box(json_parser::parse_in(black_box(&input), &arena).unwrap());
+            arena.reset();
+        });
+        report("enums/arena", input.len(), elapsed);
+    }
+    for (name, values) in [("keys", false), ("keys_and_values", true)] {
+        let elapsed = best_of(5, || {
+            let mut interner = Interner::new().intern_values(values);
+            black_box(interner.parse(black_box(&input)).unwrap());
+        });
+        report(&format!("enums/interned/{name}"), input.len(), elapsed);
+    }
+    // Pooled bytes are held once each; with values not interned, each value
+    // string still has its own allocation on top.
+    let owned = string_bytes(&from_str_with(&input, &ParserOptions::default()).unwrap());
+    let mut interner = Interner::new().intern_values(true);
+    interner.parse(&input).unwrap();
+    println!(
+        "enums: {owned} bytes of strings owned, {} bytes in {} pooled strings",
+        interner.pool_bytes(),
+        interner.len()
+    );
+
+    let input = numbers_heavy();
+    let strict = ParserOptions::default();
+    let fast = ParserOptions {
+        fast_numbers: true,
+        ..Default::default()
+    };
+
+    for (name, options) in [("strict", &strict), ("fast_numbers", &fast)] {
+        let elapsed = best_of(5, || {
+            black_box(from_str_with(black_box(&input), options).unwrap());
+        });
+        report(&format!("numbers/parse/{name}"), input.len(), elapsed);
+        let elapsed = best_of(5, || {
+            validate_with(black_box(&input), options).unwrap();
+        });
+        report(&format!("numbers/validate/{name}"), input.len(), elapsed);
+    }
+}
@@ -0,0 +1,368 @@
+use crate::error::{Error, LexError, ParseError, Position};
+use crate::number::Number;
+use crate::parser::{DecodeError, decode_string};
+use crate::tokenizer::{Span, Token, Token::*, Tokenizer};
+use crate::value::JsonValue;
+use std::cell::RefCell;
+
+/// Chunks of `T` that never reallocate, so slices handed out stay put
+/// while more are allocated.
+struct Pool<T> {
+    chunks: RefCell<Vec<Vec<T>>>,
+}
+
+impl<T> Pool<T> {
+    fn new() -> Self {
+        Pool {
+            chunks: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn alloc_extend<I: ExactSizeIterator<Item = T>>(&self, items: I) -> &[T] {
+        let n = items.len();
+        if n == 0 {
+            return &[];
+        }
+        let mut chunks = self.chunks.borrow_mut();
+        let last_capacity = chunks.last().map_or(0, Vec::capacity);
+        if chunks.last().is_none_or(|c| c.capacity() - c.len() < n) {
+            chunks.push(Vec::with_capacity(n.max(last_capacity * 2).max(64)));
+        }
+        let chunk = chunks.last_mut().unwrap();
+        let start = chunk.len();
+        chunk.extend(items);
+        assert_eq!(chunk.len(), start + n, "iterator lied about its length");
+        // SAFETY: the chunk had room for all `n` items, so extending it did
+        // not move its buffer, and nothing does until `&mut self` is taken
+        // to drop or clear the chunks. The slice is disjoint from every
+        // other one handed out.
+        unsafe { std::slice::from_raw_parts(chunk.as_ptr().add(start), n) }
+    }
+
+    /// Drops everything but the newest (largest) chunk, which is kept empty
+    /// for reuse.
+    fn clear(&mut self) {
+        let chunks = self.chunks.get_mut();
+        if let Some(mut last) = chunks.pop() {
+            last.clear();
+            chunks.clear();
+            chunks.push(last);
+        }
+    }
+}
+
+/// Memory that [`parse_in`] allocates a document's nodes from, all freed at
+/// once when the arena is dropped or [`reset`](Arena::reset).
+pub struct Arena {
+    bytes: Pool<u8>,
+    // The pools really hold values borrowed for as long as the arena is;
+    // see `alloc_values`.
+    values: Pool<ArenaValue<'static>>,
+    members: Pool<(&'static str, ArenaValue<'static>)>,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Arena {
+            bytes: Pool::new(),
+            values: Pool::new(),
+            members: Pool::new(),
+        }
+    }
+
+    /// Frees every value parsed into the arena, keeping some of its memory
+    /// for the next document.
+    pub fn reset(&mut self) {
+        self.bytes.clear();
+        self.values.clear();
+        self.members.clear();
+    }
+
+    fn alloc_str(&self, s: &str) -> &str {
+        let bytes = self.bytes.alloc_extend(s.bytes());
+        // SAFETY: a copy of the bytes of a `str`.
+        unsafe { std::str::from_utf8_unchecked(bytes) }
+    }
+
+    // SAFETY (for both of these): only lifetimes are transmuted. Values
+    // borrow from the input and the arena, both of which outlive `'a`, and
+    // dropping them never reads what they borrow (their only drop glue is
+    // `Number::Text`'s owned text, which the arena parser never produces),
+    // so the pools never look at them once `'a` is over.
+    // Handing them out for `'a` only keeps `reset` from running while they
+    // are in use.
+
+    fn alloc_values<'a, I>(&'a self, values: I) -> &'a [ArenaValue<'a>]
+    where
+        I: ExactSizeIterator<Item = ArenaValue<'a>>,
+    {
+        let values = values.map(|v| unsafe { std::mem::transmute::<_, ArenaValue<'static>>(v) });
+        let slice = self.values.alloc_extend(values);
+        unsafe { std::mem::transmute::<&[ArenaValue<'static>], &'a [ArenaValue<'a>]>(slice) }
+    }
+
+    fn alloc_members<'a, I>(&'a self, members: I) -> &'a [(&'a str, ArenaValue<'a>)]
+    where
+        I: ExactSizeIterator<Item = (&'a str, ArenaValue<'a>)>,
+    {
+        let members = members
+            .map(|m| unsafe { std::mem::transmute::<_, (&'static str, ArenaValue<'static>)>(m) });
+        let slice = self.members.alloc_extend(members);
+        unsafe {
+            std::mem::transmute::<
+                &[(&'static str, ArenaValue<'static>)],
+                &'a [(&'a str, ArenaValue<'a>)],
+            >(slice)
+        }
+    }
+}
+
+/// A JSON value whose strings and containers live in an [`Arena`] (or, for
+/// strings without escapes, in the input itself).
+///
+/// Objects keep every member in source order, duplicates included; `get`
+/// finds the last one, matching what [`from_str`](crate::from_str) keeps.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArenaValue<'a> {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(&'a str),
+    Array(&'a [ArenaValue<'a>]),
+    Object(&'a [(&'a str, ArenaValue<'a>)]),
+}
+
+impl<'a> ArenaValue<'a> {
+    pub fn get(&self, key: &str) -> Option<&'a ArenaValue<'a>> {
+        match *self {
+            ArenaValue::Object(members) => members
+                .iter()
+                .rev()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match *self {
+            ArenaValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Copies the value out of the arena.
+    pub fn to_value(&self) -> JsonValue {
+        match *self {
+            ArenaValue::Null => JsonValue::Null,
+            ArenaValue::Bool(b) => JsonValue::Bool(b),
+            ArenaValue::Number(ref n) => JsonValue::Number(n.clone()),
+            ArenaValue::String(s) => JsonValue::String(s.to_string()),
+            ArenaValue::Array(elements) => {
+                JsonValue::Array(elements.iter().map(ArenaValue::to_value).collect())
+            }
+            ArenaValue::Object(members) => {
+                let mut value = JsonValue::empty_object();
+                for (key, member) in members {
+                    *value.entry(key).unwrap().or_default() = member.to_value();
+                }
+                value
+            }
+        }
+    }
+}
+
+/// Parses a complete JSON document into `arena`, which then owns every node
+/// of it, so the whole tree is freed in one go instead of node by node.
+/// Strings without escapes are not copied but borrowed from `input`.
+///
+/// Only standard JSON with default options is accepted, and there is no
+/// depth limit.
+pub fn parse_in<'a>(input: &'a str, arena: &'a Arena) -> Result<&'a ArenaValue<'a>, Error> {
+    let mut parser = ArenaParser {
+        input,
+        arena,
+        tokenizer: Tokenizer::borrowed(input),
+        peeked: None,
+        elements: Vec::new(),
+        members: Vec::new(),
+    };
+    let value = parser.parse_value()?;
+    if let Some((t, span)) = parser.next()? {
+        return Err(parser.error(span.start, format!("Expecting end of input. Got {:?}", t)));
+    }
+    Ok(&arena.alloc_values(std::iter::once(value))[0])
+}
+
+struct ArenaParser<'a> {
+    input: &'a str,
+    arena: &'a Arena,
+    tokenizer: Tokenizer<'a>,
+    peeked: Option<(Token, Span)>,
+    /// Children of the open containers, moved into the arena as each one
+    /// closes.
+    elements: Vec<ArenaValue<'a>>,
+    members: Vec<(&'a str, ArenaValue<'a>)>,
+}
+
+impl<'a> ArenaParser<'a> {
+    fn next(&mut self) -> Result<Option<(Token, Span)>, Error> {
+        match self.peeked.take() {
+            Some(t) => Ok(Some(t)),
+            None => Ok(self.tokenizer.next_spanned().transpose()?),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<Token>, Error> {
+        if self.peeked.is_none() {
+            self.peeked = self.tokenizer.next_spanned().transpose()?;
+        }
+        Ok(self.peeked.map(|(t, _)| t))
+    }
+
+    fn expect(&mut self, token: Token) -> Result<Span, Error> {
+        match self.next()? {
+            Some((t, span)) if t == token => Ok(span),
+            Some((t, span)) => Err(self.error(
+                span.start,
+                format!("Expecting token {:?}. Got {:?}", token, t),
+            )),
+            None => Err(self.error(
+                self.input.len(),
+                format!("Expecting token {:?}. Got end of input", token),
+            )),
+        }
+    }
+
+    fn error(&self, offset: usize, message: String) -> Error {
+        Error::Parse(ParseError::new(
+            message,
+            Position::locate(self.input, offset),
+        ))
+    }
+
+    fn parse_value(&mut self) -> Result<ArenaValue<'a>, Error> {
+        let Some((token, span)) = self.next()? else {
+            return Err(self.error(
+                self.input.len(),
+                "Expecting a value. Got end of input".to_string(),
+            ));
+        };
+        let text = &self.input[span.start..span.end];
+        Ok(match token {
+            Null => ArenaValue::Null,
+            Boolean => ArenaValue::Bool(text == "true"),
+            Number => ArenaValue::Number(Number::from_literal(text).unwrap()),
+            Stringy => ArenaValue::String(self.string(span)?),
+            BeginArray => {
+                let mark = self.elements.len();
+                if self.peek()? == Some(EndArray) {
+                    self.next()?;
+                } else {
+                    loop {
+                        let element = self.parse_value()?;
+                        self.elements.push(element);
+                        if self.peek()? != Some(ValueSeparator) {
+                            break;
+                        }
+                        self.next()?;
+                    }
+                    self.expect(EndArray)?;
+                }
+                ArenaValue::Array(self.arena.alloc_values(self.elements.drain(mark..)))
+            }
+            BeginObject => {
+                let mark = self.members.len();
+                if self.peek()? == Some(EndObject) {
+                    self.next()?;
+                } else {
+                    loop {
+                        let key = self.expect(Stringy)?;
+                        let key = self.string(key)?;
+                        self.expect(NameSeparator)?;
+                        let value = self.parse_value()?;
+                        self.members.push((key, value));
+                        if self.peek()? != Some(ValueSeparator) {
+                            break;
+                        }
+                        self.next()?;
+                    }
+                    self.expect(EndObject)?;
+                }
+                ArenaValue::Object(self.arena.alloc_members(self.members.drain(mark..)))
+            }
+            t => {
+                return Err(
+                    self.error(span.start, format!("Expecting an expression. Got {:?}.", t))
+                );
+            }
+        })
+    }
+
+    fn string(&self, span: Span) -> Result<&'a str, Error> {
+        let literal = &self.input[span.start..span.end];
+        if !literal.contains('\\') {
+            return Ok(&literal[1..literal.len() - 1]);
+        }
+        match decode_string(literal, None) {
+            Ok(decoded) => Ok(self.arena.alloc_str(&decoded)),
+            Err(DecodeError::Malformed(message)) => Err(Error::Lex(LexError::new(
+                message,
+                Position::locate(self.input, span.start),
+            ))),
+            Err(DecodeError::TooLong) => unreachable!("no limit was set"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_in() -> anyhow::Result<()> {
+        let input =
+            r#"{"a": [1, -2.5, "x\ny", {"b": null, "c": [[], {}]}], "\u00e9": true, "a": 2}"#;
+        let arena = Arena::new();
+        let value = parse_in(input, &arena)?;
+        assert_eq!(value.to_value(), crate::from_str(input)?);
+        assert_eq!(value.get("é"), Some(&ArenaValue::Bool(true)));
+        assert_eq!(value.get("a"), Some(&ArenaValue::Number(Number::Int(2))));
+        let ArenaValue::Object(members) = value else {
+            panic!("expected an object");
+        };
+        // Unescaped strings, keys included, point into the input; escaped
+        // ones are decoded into the arena.
+        let borrowed = |s: &str| input.as_bytes().as_ptr_range().contains(&s.as_ptr());
+        assert!(borrowed(members[0].0));
+        assert!(!borrowed(members[1].0));
+        assert_eq!(members[1].0, "é");
+
+        for input in ["", "[1,", "[1 2]", r#"{"a" 1}"#, "[1]]", r#"["\x"]"#] {
+            assert!(parse_in(input, &arena).is_err(), "{input}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset() -> anyhow::Result<()> {
+        let mut arena = Arena::new();
+        for i in 0..3 {
+            let input = format!(r#"[{i}, "\t{i}", [{{"k": "v"}}]]"#);
+            let value = parse_in(&input, &arena)?.to_value();
+            assert_eq!(value, crate::from_str(&input)?);
+            arena.reset();
+        }
+        // Many values force new chunks without moving earlier ones.
+        let input = format!("[{}]", vec!["[1, \"\\u0041\"]"; 1000].join(","));
+        let value = parse_in(&input, &arena)?;
+        assert_eq!(value.to_value(), crate::from_str(&input)?);
+        Ok(())
+    }
+}
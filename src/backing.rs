@@ -0,0 +1,389 @@
+use crate::error::Error;
+use crate::options::ParserOptions;
+use crate::parser::{Owned, Parser};
+use crate::tokenizer::Tokenizer;
+use crate::value::JsonValue;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// How a [`JsonValue`] stores object members, chosen by its type parameter.
+///
+/// The parser builds any backing directly (see [`from_str_backed`]), and
+/// reading values — [`get`](JsonValue::get), [`pointer`](JsonValue::pointer),
+/// the `as_*` accessors — and serializing them work with any backing. Edits
+/// and the other tree operations need the default, [`Ordered`]; convert
+/// with [`JsonValue::to_backing`]. So the other backings mostly suit
+/// documents that are parsed once and then looked up many times.
+pub trait ObjectBacking: Sized {
+    type Map<V>;
+
+    /// Builds a map from members in source order. Backings that can't
+    /// repeat a key keep its last value.
+    fn collect<V>(members: impl Iterator<Item = (String, V)>) -> Self::Map<V>;
+
+    /// The members, in the backing's own order.
+    fn iter<'m, V: 'm>(map: &'m Self::Map<V>) -> impl Iterator<Item = (&'m String, &'m V)>;
+
+    fn len<V>(map: &Self::Map<V>) -> usize;
+
+    fn lookup<'m, V>(map: &'m Self::Map<V>, key: &str) -> Option<&'m V>;
+
+    /// Pushes the members of `a` and `b` to compare pairwise onto `out`,
+    /// or returns `false` if their keys already differ.
+    fn pair<'m, V>(
+        a: &'m Self::Map<V>,
+        b: &'m Self::Map<V>,
+        out: &mut Vec<(&'m V, &'m V)>,
+    ) -> bool {
+        if Self::len(a) != Self::len(b) {
+            return false;
+        }
+        for (k, v) in Self::iter(a) {
+            let Some(w) = Self::lookup(b, k) else {
+                return false;
+            };
+            out.push((v, w));
+        }
+        true
+    }
+}
+
+/// The default backing, `Vec<(String, JsonValue)>`: members keep their
+/// source order, repeats included, and lookups scan from the start.
+/// Objects compare equal only with their members in the same order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ordered;
+
+/// `HashMap<String, JsonValue>`: constant-time lookups, no order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hashed;
+
+/// `BTreeMap<String, JsonValue>`: members sorted by key.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sorted;
+
+impl ObjectBacking for Ordered {
+    type Map<V> = Vec<(String, V)>;
+
+    fn collect<V>(members: impl Iterator<Item = (String, V)>) -> Self::Map<V> {
+        members.collect()
+    }
+
+    fn iter<'m, V: 'm>(map: &'m Self::Map<V>) -> impl Iterator<Item = (&'m String, &'m V)> {
+        map.iter().map(|(k, v)| (k, v))
+    }
+
+    fn len<V>(map: &Self::Map<V>) -> usize {
+        map.len()
+    }
+
+    fn lookup<'m, V>(map: &'m Self::Map<V>, key: &str) -> Option<&'m V> {
+        map.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    fn pair<'m, V>(
+        a: &'m Self::Map<V>,
+        b: &'m Self::Map<V>,
+        out: &mut Vec<(&'m V, &'m V)>,
+    ) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+        for ((key_a, a), (key_b, b)) in a.iter().zip(b) {
+            if key_a != key_b {
+                return false;
+            }
+            out.push((a, b));
+        }
+        true
+    }
+}
+
+impl ObjectBacking for Hashed {
+    type Map<V> = HashMap<String, V>;
+
+    fn collect<V>(members: impl Iterator<Item = (String, V)>) -> Self::Map<V> {
+        members.collect()
+    }
+
+    fn iter<'m, V: 'm>(map: &'m Self::Map<V>) -> impl Iterator<Item = (&'m String, &'m V)> {
+        map.iter()
+    }
+
+    fn len<V>(map: &Self::Map<V>) -> usize {
+        map.len()
+    }
+
+    fn lookup<'m, V>(map: &'m Self::Map<V>, key: &str) -> Option<&'m V> {
+        map.get(key)
+    }
+}
+
+impl ObjectBacking for Sorted {
+    type Map<V> = BTreeMap<String, V>;
+
+    fn collect<V>(members: impl Iterator<Item = (String, V)>) -> Self::Map<V> {
+        members.collect()
+    }
+
+    fn iter<'m, V: 'm>(map: &'m Self::Map<V>) -> impl Iterator<Item = (&'m String, &'m V)> {
+        map.iter()
+    }
+
+    fn len<V>(map: &Self::Map<V>) -> usize {
+        map.len()
+    }
+
+    fn lookup<'m, V>(map: &'m Self::Map<V>, key: &str) -> Option<&'m V> {
+        map.get(key)
+    }
+}
+
+/// Parses a complete JSON document into a value with the given object
+/// backing, e.g. `from_str_backed::<Hashed>(input, &options)`. Each object's
+/// members are gathered in source order and then moved into its map, so
+/// `options.duplicate_keys` decides which of a repeated key's values are
+/// kept whatever the backing.
+pub fn from_str_backed<B: ObjectBacking>(
+    input: &str,
+    options: &ParserOptions,
+) -> Result<JsonValue<B>, Error> {
+    let mut tokenizer = Tokenizer::borrowed(input).configured(options);
+    let tokens = std::iter::from_fn(|| tokenizer.next_spanned());
+    Parser::<_, Owned<B>>::new(input, tokens, options).parse()
+}
+
+impl<B: ObjectBacking> JsonValue<B> {
+    /// A copy of the value with objects stored in backing `C`, e.g. to edit
+    /// a [`Hashed`] value as an [`Ordered`] one. Uses an explicit stack, like `PartialEq`, so deeply
+    /// nested values cannot overflow it.
+    pub fn to_backing<C: ObjectBacking>(&self) -> JsonValue<C> {
+        enum Frame<'s, B: ObjectBacking, C: ObjectBacking> {
+            Array(std::slice::Iter<'s, JsonValue<B>>, Vec<JsonValue<C>>),
+            Object(
+                std::vec::IntoIter<(&'s String, &'s JsonValue<B>)>,
+                Vec<(String, JsonValue<C>)>,
+                Option<&'s String>,
+            ),
+        }
+
+        let mut open: Vec<Frame<B, C>> = Vec::new();
+        let mut next = self;
+        loop {
+            let mut done = match next {
+                JsonValue::Null => Some(JsonValue::Null),
+                JsonValue::Bool(b) => Some(JsonValue::Bool(*b)),
+                JsonValue::Number(n) => Some(JsonValue::Number(n.clone())),
+                JsonValue::String(s) => Some(JsonValue::String(s.clone())),
+                JsonValue::Array(elements) => {
+                    open.push(Frame::Array(
+                        elements.iter(),
+                        Vec::with_capacity(elements.len()),
+                    ));
+                    None
+                }
+                JsonValue::Object(members) => {
+                    let pending: Vec<_> = B::iter(members).collect();
+                    open.push(Frame::Object(
+                        pending.into_iter(),
+                        Vec::with_capacity(B::len(members)),
+                        None,
+                    ));
+                    None
+                }
+            };
+            // Hand the finished value to its container, closing containers
+            // until one has a child left to copy.
+            next = loop {
+                let Some(frame) = open.last_mut() else {
+                    return done.expect("a value without containers is finished at once");
+                };
+                let child = match frame {
+                    Frame::Array(rest, built) => {
+                        built.extend(done.take());
+                        rest.next()
+                    }
+                    Frame::Object(rest, built, key) => {
+                        if let (Some(value), Some(key)) = (done.take(), key.take()) {
+                            built.push((key.clone(), value));
+                        }
+                        rest.next().map(|(k, v)| {
+                            *key = Some(k);
+                            v
+                        })
+                    }
+                };
+                if let Some(child) = child {
+                    break child;
+                }
+                done = Some(match open.pop() {
+                    Some(Frame::Array(_, built)) => JsonValue::Array(built),
+                    Some(Frame::Object(_, built, _)) => {
+                        JsonValue::Object(C::collect(built.into_iter()))
+                    }
+                    None => unreachable!("the frame was just inspected"),
+                });
+            };
+        }
+    }
+}
+
+/// Objects print as a list of `(key, value)` pairs whatever the backing.
+impl<B: ObjectBacking> fmt::Debug for JsonValue<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => f.write_str("Null"),
+            JsonValue::Bool(b) => f.debug_tuple("Bool").field(b).finish(),
+            JsonValue::Number(n) => f.debug_tuple("Number").field(n).finish(),
+            JsonValue::String(s) => f.debug_tuple("String").field(s).finish(),
+            JsonValue::Array(elements) => f.debug_tuple("Array").field(elements).finish(),
+            JsonValue::Object(members) => f
+                .debug_tuple("Object")
+                .field(&DebugMembers::<B>(members))
+                .finish(),
+        }
+    }
+}
+
+struct DebugMembers<'m, B: ObjectBacking>(&'m B::Map<JsonValue<B>>);
+
+impl<B: ObjectBacking> fmt::Debug for DebugMembers<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(B::iter(self.0)).finish()
+    }
+}
+
+/// Copies through [`JsonValue::to_backing`], so cloning deeply nested values
+/// cannot overflow the stack either.
+impl<B: ObjectBacking> Clone for JsonValue<B> {
+    fn clone(&self) -> Self {
+        self.to_backing()
+    }
+}
+
+/// Uses an explicit worklist instead of recursion, so comparing deeply
+/// nested values cannot overflow the stack. Objects compare as the backing
+/// decides: in order for [`Ordered`], by key for the others.
+impl<B: ObjectBacking> PartialEq for JsonValue<B> {
+    fn eq(&self, other: &Self) -> bool {
+        let mut pending = vec![(self, other)];
+        while let Some(pair) = pending.pop() {
+            match pair {
+                (JsonValue::Null, JsonValue::Null) => {}
+                (JsonValue::Bool(a), JsonValue::Bool(b)) if a == b => {}
+                (JsonValue::Number(a), JsonValue::Number(b)) if a == b => {}
+                (JsonValue::String(a), JsonValue::String(b)) if a == b => {}
+                (JsonValue::Array(a), JsonValue::Array(b)) if a.len() == b.len() => {
+                    pending.extend(a.iter().zip(b));
+                }
+                (JsonValue::Object(a), JsonValue::Object(b)) if B::pair(a, b, &mut pending) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::DuplicateKeys;
+
+    #[test]
+    fn test_backings() -> anyhow::Result<()> {
+        let input = r#"{"b": [1, {"y": null, "x": "s"}], "a": true, "c": {}}"#;
+        let options = ParserOptions::default();
+        let ordered = from_str_backed::<Ordered>(input, &options)?;
+        assert_eq!(ordered, crate::from_str(input)?);
+        assert_eq!(
+            ordered.to_string(),
+            r#"{"b":[1,{"y":null,"x":"s"}],"a":true,"c":{}}"#
+        );
+
+        let sorted = from_str_backed::<Sorted>(input, &options)?;
+        let text = sorted.to_backing::<Ordered>().to_string();
+        assert_eq!(text, r#"{"a":true,"b":[1,{"x":"s","y":null}],"c":{}}"#);
+        assert_eq!(from_str_backed::<Sorted>(&text, &options)?, sorted);
+
+        let hashed = from_str_backed::<Hashed>(input, &options)?;
+        let JsonValue::Object(members) = &hashed else {
+            panic!("expected an object");
+        };
+        assert_eq!(members["a"], JsonValue::Bool(true));
+        let text = hashed.to_backing::<Ordered>().to_string();
+        assert_eq!(from_str_backed::<Hashed>(&text, &options)?, hashed);
+        // Key order only matters to the ordered backing.
+        assert_eq!(hashed.to_backing::<Sorted>(), sorted);
+        assert_ne!(
+            crate::from_str(r#"{"a": 1, "b": 2}"#)?,
+            crate::from_str(r#"{"b": 2, "a": 1}"#)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_backed_access() -> anyhow::Result<()> {
+        let input = r#"{"b": {"x": [1, "s"]}, "a": -0, "c": {}}"#;
+        let options = ParserOptions::default();
+        let sorted = from_str_backed::<Sorted>(input, &options)?;
+        assert_eq!(sorted.to_string(), r#"{"a":-0,"b":{"x":[1,"s"]},"c":{}}"#);
+        let pretty = crate::SerializeOptions::pretty();
+        assert_eq!(
+            sorted.to_string_with(&pretty)?,
+            sorted.to_backing::<Ordered>().to_string_with(&pretty)?
+        );
+
+        let hashed = from_str_backed::<Hashed>(input, &options)?;
+        assert!(hashed.contains_key("c") && !hashed.contains_key("d"));
+        assert_eq!(hashed.pointer_str("/b/x/1"), Some("s"));
+        assert_eq!(
+            hashed
+                .get("b")
+                .and_then(|b| b.get("x"))
+                .map(JsonValue::depth),
+            Some(1)
+        );
+        assert_eq!(
+            hashed.pointer("/a").and_then(JsonValue::as_number_raw),
+            Some("-0")
+        );
+        // Members come out in arbitrary order, unless sorted.
+        assert_eq!(
+            from_str_backed::<Hashed>(&hashed.to_string(), &options)?,
+            hashed
+        );
+        let sort_keys = crate::SerializeOptions {
+            sort_keys: true,
+            ..Default::default()
+        };
+        assert_eq!(hashed.to_string_with(&sort_keys)?, sorted.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_backed_duplicate_keys() -> anyhow::Result<()> {
+        let input = r#"{"a": 1, "b": 2, "a": 3}"#;
+        for (duplicate_keys, expected) in [
+            (DuplicateKeys::First, "1"),
+            (DuplicateKeys::Last, "3"),
+            (DuplicateKeys::Combine, "[1,3]"),
+        ] {
+            let options = ParserOptions {
+                duplicate_keys,
+                ..Default::default()
+            };
+            let hashed = from_str_backed::<Hashed>(input, &options)?;
+            let JsonValue::Object(members) = &hashed else {
+                panic!("expected an object");
+            };
+            assert_eq!(members.len(), 2);
+            assert_eq!(members["a"].to_backing::<Ordered>().to_string(), expected);
+        }
+        let options = ParserOptions {
+            duplicate_keys: DuplicateKeys::Error,
+            ..Default::default()
+        };
+        assert!(from_str_backed::<Sorted>(input, &options).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::value::JsonValue;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn sextet(c: u8) -> Option<u32> {
+    let v = match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'+' => 62,
+        b'/' => 63,
+        _ => return None,
+    };
+    Some(v as u32)
+}
+
+/// Decodes standard (RFC 4648 §4) base64. Padding is optional, but if
+/// present it must complete the last group. Whitespace is not allowed.
+fn decode(s: &str) -> Option<Vec<u8>> {
+    let padded = s.ends_with('=');
+    let body = s.trim_end_matches('=');
+    if (padded && (!s.len().is_multiple_of(4) || s.len() - body.len() > 2)) || body.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(body.len() * 3 / 4);
+    let (mut bits, mut n) = (0u32, 0);
+    for c in body.bytes() {
+        bits = bits << 6 | sextet(c)?;
+        n += 6;
+        if n >= 8 {
+            n -= 8;
+            out.push((bits >> n) as u8);
+            bits &= (1 << n) - 1;
+        }
+    }
+    Some(out)
+}
+
+fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for group in bytes.chunks(3) {
+        let b = [
+            group[0],
+            *group.get(1).unwrap_or(&0),
+            *group.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= group.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+impl JsonValue {
+    /// Decodes a string value as standard base64, the usual way to embed
+    /// binary data in JSON. `None` for non-strings and for invalid base64.
+    pub fn as_base64_bytes(&self) -> Option<Vec<u8>> {
+        decode(self.as_str()?)
+    }
+
+    /// A string holding `bytes` as padded standard base64, the inverse of
+    /// [`as_base64_bytes`](Self::as_base64_bytes).
+    pub fn from_bytes_base64(bytes: &[u8]) -> JsonValue {
+        JsonValue::String(encode(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        for (bytes, text) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"\xff\xfe\x00", "//4A"),
+        ] {
+            let value = JsonValue::from_bytes_base64(bytes);
+            assert_eq!(value, JsonValue::from(text), "{bytes:?}");
+            assert_eq!(value.as_base64_bytes().as_deref(), Some(bytes), "{text}");
+        }
+        assert_eq!(
+            JsonValue::from("Zm8").as_base64_bytes(),
+            Some(b"fo".to_vec())
+        );
+
+        for invalid in ["Zm9v!", "Zg=", "Z===", "Zm9vY", "Zm 9v", "-_8="] {
+            assert_eq!(
+                JsonValue::from(invalid).as_base64_bytes(),
+                None,
+                "{invalid}"
+            );
+        }
+        assert_eq!(JsonValue::from(1i64).as_base64_bytes(), None);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::number::Number;
+use crate::value::JsonValue;
+use anyhow::{Context, bail};
+
+// Layout: the magic bytes `JPB` and a version byte, then one value. A value
+// is a tag byte followed by its payload:
+//
+// | tag | value        | payload                                    |
+// |-----|--------------|--------------------------------------------|
+// | 0   | `null`       | none                                       |
+// | 1   | `false`      | none                                       |
+// | 2   | `true`       | none                                       |
+// | 3   | `Int`        | 8 bytes, little-endian                     |
+// | 4   | `UInt`       | 8 bytes, little-endian                     |
+// | 5   | `Float`      | 8 bytes, little-endian IEEE 754 bits       |
+// | 6   | string       | varint byte length, UTF-8 bytes            |
+// | 7   | array        | varint element count, elements             |
+// | 8   | object       | varint member count, (key string, value)s  |
+// | 9   | `Text`       | 8 bytes as for `Float`, literal as string  |
+//
+// Varints are unsigned LEB128. Object members keep their order.
+const MAGIC: &[u8] = b"JPB\x01";
+
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const INT: u8 = 3;
+const UINT: u8 = 4;
+const FLOAT: u8 = 5;
+const STRING: u8 = 6;
+const ARRAY: u8 = 7;
+const OBJECT: u8 = 8;
+const TEXT: u8 = 9;
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_varint(out, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_value(out: &mut Vec<u8>, value: &JsonValue) {
+    match value {
+        JsonValue::Null => out.push(NULL),
+        JsonValue::Bool(false) => out.push(FALSE),
+        JsonValue::Bool(true) => out.push(TRUE),
+        JsonValue::Number(Number::Int(i)) => {
+            out.push(INT);
+            out.extend_from_slice(&i.to_le_bytes());
+        }
+        JsonValue::Number(Number::UInt(u)) => {
+            out.push(UINT);
+            out.extend_from_slice(&u.to_le_bytes());
+        }
+        JsonValue::Number(Number::Float(f)) => {
+            out.push(FLOAT);
+            out.extend_from_slice(&f.to_bits().to_le_bytes());
+        }
+        JsonValue::Number(Number::Text(f, text)) => {
+            out.push(TEXT);
+            out.extend_from_slice(&f.to_bits().to_le_bytes());
+            write_str(out, text);
+        }
+        JsonValue::String(s) => {
+            out.push(STRING);
+            write_str(out, s);
+        }
+        JsonValue::Array(elements) => {
+            out.push(ARRAY);
+            write_varint(out, elements.len() as u64);
+            for element in elements {
+                write_value(out, element);
+            }
+        }
+        JsonValue::Object(members) => {
+            out.push(OBJECT);
+            write_varint(out, members.len() as u64);
+            for (key, value) in members {
+                write_str(out, key);
+                write_value(out, value);
+            }
+        }
+    }
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+    position: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, n: usize) -> anyhow::Result<&'b [u8]> {
+        let end = self
+            .position
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .with_context(|| format!("Truncated input at byte {}", self.position))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn eight(&mut self) -> anyhow::Result<[u8; 8]> {
+        Ok(self.take(8)?.try_into().unwrap())
+    }
+
+    fn varint(&mut self) -> anyhow::Result<u64> {
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            n |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        bail!("Overlong varint ending at byte {}", self.position)
+    }
+
+    /// A count of items that each take at least one byte, so a corrupt count
+    /// can't make us allocate more than the input could hold.
+    fn count(&mut self) -> anyhow::Result<usize> {
+        let n = self.varint()?;
+        let remaining = self.bytes.len() - self.position;
+        match usize::try_from(n) {
+            Ok(n) if n <= remaining => Ok(n),
+            _ => bail!("Count {n} at byte {} exceeds the input", self.position),
+        }
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        let len = self.count()?;
+        let start = self.position;
+        let bytes = self.take(len)?;
+        let s = std::str::from_utf8(bytes)
+            .with_context(|| format!("Invalid UTF-8 in string at byte {start}"))?;
+        Ok(s.to_string())
+    }
+
+    fn value(&mut self) -> anyhow::Result<JsonValue> {
+        let tag_at = self.position;
+        Ok(match self.byte()? {
+            NULL => JsonValue::Null,
+            FALSE => JsonValue::Bool(false),
+            TRUE => JsonValue::Bool(true),
+            INT => JsonValue::Number(Number::Int(i64::from_le_bytes(self.eight()?))),
+            UINT => JsonValue::Number(Number::UInt(u64::from_le_bytes(self.eight()?))),
+            FLOAT => JsonValue::Number(Number::Float(f64::from_bits(u64::from_le_bytes(
+                self.eight()?,
+            )))),
+            TEXT => {
+                let f = f64::from_bits(u64::from_le_bytes(self.eight()?));
+                JsonValue::Number(Number::Text(f, self.string()?.into()))
+            }
+            STRING => JsonValue::String(self.string()?),
+            ARRAY => {
+                let len = self.count()?;
+                let mut elements = Vec::with_capacity(len);
+                for _ in 0..len {
+                    elements.push(self.value()?);
+                }
+                JsonValue::Array(elements)
+            }
+            OBJECT => {
+                let len = self.count()?;
+                let mut members = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let key = self.string()?;
+                    members.push((key, self.value()?));
+                }
+                JsonValue::Object(members)
+            }
+            tag => bail!("Unknown tag {tag} at byte {tag_at}"),
+        })
+    }
+}
+
+impl JsonValue {
+    /// Encodes the value in a compact binary format, for caching parsed
+    /// documents without re-tokenizing their text. JSON text stays the
+    /// authoritative format: this one is only meant to be read back by
+    /// [`from_bytes`](Self::from_bytes) of the same crate version.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        write_value(&mut out, self);
+        out
+    }
+
+    /// Decodes a value written by [`to_bytes`](Self::to_bytes). Numbers,
+    /// including the literals of [`Number::Text`], and the order of object
+    /// members come back exactly as they were.
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<JsonValue> {
+        let Some(body) = bytes.strip_prefix(MAGIC) else {
+            bail!("Not in the binary value format");
+        };
+        let mut reader = Reader {
+            bytes: body,
+            position: 0,
+        };
+        let value = reader.value()?;
+        if reader.position != body.len() {
+            bail!("Trailing bytes after value at byte {}", reader.position);
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParserOptions, SerializeOptions, from_str, from_str_with};
+
+    #[test]
+    fn test_round_trip() -> anyhow::Result<()> {
+        let value = from_str(
+            r#"{"z": null, "a": [true, false, 0, -1, 2.0, -0.0, 1e300,
+                9223372036854775807, -9223372036854775808, 18446744073709551615],
+                "nested": {"é😀": ["", "x\ny", [], {}], "b": {"c": [[[1.5]]]}},
+                "long": "0123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789"}"#,
+        )?;
+        let bytes = value.to_bytes();
+        let decoded = JsonValue::from_bytes(&bytes)?;
+        assert_eq!(decoded, value);
+        // Equality alone would accept `2.0` decoding as `2`.
+        assert_eq!(decoded.to_string(), value.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip_number_text() -> anyhow::Result<()> {
+        let keep_text = ParserOptions {
+            keep_number_text: true,
+            ..Default::default()
+        };
+        for (input, options) in [
+            ("[-0]", ParserOptions::default()),
+            ("[1e400]", ParserOptions::default()),
+            ("[1.10]", keep_text),
+        ] {
+            let value = from_str_with(input, &options)?;
+            let decoded = JsonValue::from_bytes(&value.to_bytes())?;
+            assert_eq!(decoded, value, "{input}");
+            let text = decoded.to_string_with(&SerializeOptions::default())?;
+            assert_eq!(text, input);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed() {
+        let bytes = from_str(r#"{"a": [1, "b"]}"#).unwrap().to_bytes();
+        assert!(JsonValue::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(JsonValue::from_bytes(b"{}").is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(NULL);
+        assert!(JsonValue::from_bytes(&trailing).is_err());
+        // A huge array count is rejected rather than allocated.
+        let mut huge = MAGIC.to_vec();
+        huge.push(ARRAY);
+        write_varint(&mut huge, u64::MAX);
+        assert!(JsonValue::from_bytes(&huge).is_err());
+    }
+}
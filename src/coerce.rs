@@ -0,0 +1,132 @@
+use crate::value::{JsonValue, ValueKind};
+
+impl JsonValue {
+    /// Converts the value to the `target` type the way loosely typed
+    /// sources mean it, e.g. for configuration read from environment
+    /// variables, or `None` if there is no sensible conversion. Unlike the
+    /// `as_*` accessors, which never convert, this accepts:
+    ///
+    /// - a value already of type `target`, unchanged;
+    /// - a string holding a JSON number, surrounding whitespace allowed,
+    ///   as that number: `" 42 "` is `42`, but `"0x2A"` and `"NaN"` are
+    ///   nothing;
+    /// - a string `true` or `false`, in any case, as that bool;
+    /// - a number or a bool as its JSON text: `1.5` is `"1.5"`;
+    /// - a bool as the number `1` or `0`, and the numbers `1` and `0`,
+    ///   however written, as `true` and `false`.
+    ///
+    /// Nothing converts to or from `null`, arrays or objects.
+    ///
+    /// ```
+    /// use json_parser::{JsonValue, ValueKind};
+    ///
+    /// let port = JsonValue::from("8080").coerce_to(ValueKind::Number).unwrap();
+    /// assert_eq!(port.as_u64(), Some(8080));
+    /// assert_eq!(JsonValue::from("yes").coerce_to(ValueKind::Bool), None);
+    /// ```
+    pub fn coerce_to(&self, target: ValueKind) -> Option<JsonValue> {
+        if self.kind() == target {
+            return Some(self.clone());
+        }
+        match (self, target) {
+            (JsonValue::String(s), ValueKind::Number) => match crate::from_str(s.trim()).ok()? {
+                number @ JsonValue::Number(_) => Some(number),
+                _ => None,
+            },
+            (JsonValue::String(s), ValueKind::Bool) => {
+                let s = s.trim();
+                if s.eq_ignore_ascii_case("true") {
+                    Some(JsonValue::Bool(true))
+                } else if s.eq_ignore_ascii_case("false") {
+                    Some(JsonValue::Bool(false))
+                } else {
+                    None
+                }
+            }
+            (JsonValue::Number(_) | JsonValue::Bool(_), ValueKind::String) => {
+                Some(JsonValue::String(self.to_string()))
+            }
+            (&JsonValue::Bool(b), ValueKind::Number) => Some(JsonValue::from(i64::from(b))),
+            (JsonValue::Number(n), ValueKind::Bool) => match n.as_f64() {
+                0.0 => Some(JsonValue::Bool(false)),
+                1.0 => Some(JsonValue::Bool(true)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::value::{JsonValue, ValueKind};
+
+    fn coerce(input: &str, target: ValueKind) -> Option<String> {
+        let value = crate::from_str(input).unwrap();
+        value.coerce_to(target).map(|v| v.to_string())
+    }
+
+    #[test]
+    fn test_string_conversions() {
+        assert_eq!(coerce(r#""42""#, ValueKind::Number).as_deref(), Some("42"));
+        assert_eq!(
+            coerce(r#"" -1.5e2 ""#, ValueKind::Number).as_deref(),
+            Some("-150.0")
+        );
+        assert_eq!(
+            coerce(r#""1e999""#, ValueKind::Number).as_deref(),
+            Some("1e999")
+        );
+        assert_eq!(
+            coerce(r#""TRUE""#, ValueKind::Bool).as_deref(),
+            Some("true")
+        );
+        assert_eq!(
+            coerce(r#""false""#, ValueKind::Bool).as_deref(),
+            Some("false")
+        );
+        assert_eq!(coerce("42", ValueKind::String).as_deref(), Some(r#""42""#));
+        assert_eq!(
+            coerce("2.50", ValueKind::String).as_deref(),
+            Some(r#""2.5""#)
+        );
+        assert_eq!(
+            coerce("true", ValueKind::String).as_deref(),
+            Some(r#""true""#)
+        );
+    }
+
+    #[test]
+    fn test_number_bool_conversions() {
+        assert_eq!(coerce("true", ValueKind::Number).as_deref(), Some("1"));
+        assert_eq!(coerce("false", ValueKind::Number).as_deref(), Some("0"));
+        assert_eq!(coerce("1.0", ValueKind::Bool).as_deref(), Some("true"));
+        assert_eq!(coerce("-0", ValueKind::Bool).as_deref(), Some("false"));
+        assert_eq!(coerce("2", ValueKind::Bool), None);
+    }
+
+    #[test]
+    fn test_no_conversion() {
+        for (input, target) in [
+            (r#""0x2A""#, ValueKind::Number),
+            (r#""NaN""#, ValueKind::Number),
+            (r#""[1]""#, ValueKind::Number),
+            (r#""""#, ValueKind::Number),
+            (r#""yes""#, ValueKind::Bool),
+            ("null", ValueKind::String),
+            ("null", ValueKind::Bool),
+            ("1", ValueKind::Null),
+            ("[1]", ValueKind::String),
+            (r#"{"a": 1}"#, ValueKind::Array),
+            (r#""a""#, ValueKind::Array),
+        ] {
+            assert_eq!(coerce(input, target), None, "{input} to {target:?}");
+        }
+        assert_eq!(coerce("[1]", ValueKind::Array).as_deref(), Some("[1]"));
+        assert_eq!(coerce("null", ValueKind::Null).as_deref(), Some("null"));
+        assert_eq!(
+            JsonValue::from("s").coerce_to(ValueKind::String),
+            Some(JsonValue::from("s"))
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::error::{Error, ParseError, Position};
+use crate::tokenizer::{Token, Token::*, Tokenizer};
+
+/// Counts the elements of a top-level array without building it.
+///
+/// Only the token stream is inspected: strings and numbers are not decoded,
+/// and nested containers are skipped by tracking bracket depth, so the
+/// document is not fully validated.
+pub fn count_array_elements(input: &str) -> Result<usize, Error> {
+    count_entries(input, BeginArray, EndArray)
+}
+
+/// Counts the members of a top-level object without building it. See
+/// [`count_array_elements`] for what is (not) checked.
+pub fn count_object_members(input: &str) -> Result<usize, Error> {
+    count_entries(input, BeginObject, EndObject)
+}
+
+fn count_entries(input: &str, open: Token, close: Token) -> Result<usize, Error> {
+    let error = |offset, message: String| {
+        Error::Parse(ParseError::new(message, Position::locate(input, offset)))
+    };
+
+    let mut tokenizer = Tokenizer::borrowed(input);
+    match tokenizer.next_spanned().transpose()? {
+        Some((t, _)) if t == open => {}
+        Some((t, span)) => {
+            return Err(error(
+                span.start,
+                format!("Expecting {:?} at root. Got {:?}", open, t),
+            ));
+        }
+        None => return Err(error(input.len(), "Expecting a value".to_string())),
+    }
+
+    let mut depth = 1;
+    let mut count = 0;
+    let mut empty = true;
+    while let Some((t, span)) = tokenizer.next_spanned().transpose()? {
+        match t {
+            BeginObject | BeginArray => depth += 1,
+            EndObject | EndArray => depth -= 1,
+            ValueSeparator if depth == 1 => count += 1,
+            _ => {}
+        }
+        if depth == 0 {
+            if t != close {
+                return Err(error(
+                    span.start,
+                    format!("Expecting {:?}. Got {:?}", close, t),
+                ));
+            }
+            return Ok(if empty { 0 } else { count + 1 });
+        }
+        empty = false;
+    }
+    Err(error(
+        input.len(),
+        format!("Expecting {:?}. Got end of input", close),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count() -> anyhow::Result<()> {
+        assert_eq!(count_array_elements("[]")?, 0);
+        assert_eq!(
+            count_array_elements(r#"[1, [2, 3], {"a": [4, 5]}, "x,y"]"#)?,
+            4
+        );
+        assert_eq!(count_object_members(r#"{"a": [1, 2], "b": {"c": 3}}"#)?, 2);
+        assert!(count_array_elements(r#"{"a": 1}"#).is_err());
+        assert!(count_object_members("[1, 2").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use crate::value::JsonValue;
+
+/// An RFC 3339 timestamp such as `2024-02-29T13:45:00.25+01:00`, as
+/// written: the fields are not converted to UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    /// Up to 60, for a leap second.
+    pub second: u8,
+    pub nanosecond: u32,
+    /// Offset from UTC in minutes; `Z` is 0.
+    pub offset_minutes: i16,
+}
+
+impl DateTime {
+    /// Parses the `date-time` production of RFC 3339. Lowercase `t` and `z`
+    /// are accepted, as is a space between date and time; fractions beyond
+    /// nanoseconds are truncated.
+    pub fn parse(s: &str) -> Option<DateTime> {
+        let b = s.as_bytes();
+        if b.len() < 20
+            || b[4] != b'-'
+            || b[7] != b'-'
+            || !matches!(b[10], b'T' | b't' | b' ')
+            || b[13] != b':'
+            || b[16] != b':'
+        {
+            return None;
+        }
+        let year = digits(&b[0..4])? as u16;
+        let month = digits(&b[5..7])? as u8;
+        let day = digits(&b[8..10])? as u8;
+        let hour = digits(&b[11..13])? as u8;
+        let minute = digits(&b[14..16])? as u8;
+        let second = digits(&b[17..19])? as u8;
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            return None;
+        }
+
+        let mut rest = &b[19..];
+        let mut nanosecond = 0;
+        if let Some(fraction) = rest.strip_prefix(b".") {
+            let len = fraction.iter().take_while(|c| c.is_ascii_digit()).count();
+            if len == 0 {
+                return None;
+            }
+            for (i, &c) in fraction[..len.min(9)].iter().enumerate() {
+                nanosecond += u32::from(c - b'0') * 10u32.pow(8 - i as u32);
+            }
+            rest = &fraction[len..];
+        }
+        let offset_minutes = match rest {
+            [b'Z' | b'z'] => 0,
+            [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] => {
+                let hours = digits(&[*h1, *h2])?;
+                let minutes = digits(&[*m1, *m2])?;
+                if hours > 23 || minutes > 59 {
+                    return None;
+                }
+                let offset = (hours * 60 + minutes) as i16;
+                if *sign == b'-' { -offset } else { offset }
+            }
+            _ => return None,
+        };
+        Some(DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanosecond,
+            offset_minutes,
+        })
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z, ignoring the fraction. A leap
+    /// second counts as the first second of the next minute.
+    pub fn unix_timestamp(&self) -> i64 {
+        // Days from the civil date, after Howard Hinnant's algorithm.
+        let (year, month) = (i64::from(self.year), i64::from(self.month));
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146_097 + day_of_era - 719_468;
+        days * 86_400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+            - i64::from(self.offset_minutes) * 60
+    }
+}
+
+fn digits(b: &[u8]) -> Option<u32> {
+    b.iter().try_fold(0, |n, &c| {
+        c.is_ascii_digit().then(|| n * 10 + u32::from(c - b'0'))
+    })
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl JsonValue {
+    /// The string as an RFC 3339 timestamp. `None` for other types and for
+    /// strings that aren't valid timestamps.
+    pub fn as_rfc3339(&self) -> Option<DateTime> {
+        DateTime::parse(self.as_str()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_rfc3339() {
+        let value = JsonValue::from("2024-02-29T13:45:00.25+01:00");
+        let time = value.as_rfc3339().unwrap();
+        assert_eq!(
+            time,
+            DateTime {
+                year: 2024,
+                month: 2,
+                day: 29,
+                hour: 13,
+                minute: 45,
+                second: 0,
+                nanosecond: 250_000_000,
+                offset_minutes: 60,
+            }
+        );
+        assert_eq!(time.unix_timestamp(), 1_709_210_700);
+        let epoch = DateTime::parse("1970-01-01t00:00:00z").unwrap();
+        assert_eq!(epoch.unix_timestamp(), 0);
+
+        for invalid in [
+            "2023-02-29T00:00:00Z",
+            "2024-13-01T00:00:00Z",
+            "2024-01-01T24:00:00Z",
+            "2024-01-01T00:00:00",
+            "2024-01-01T00:00:00.Z",
+            "2024-01-01T00:00:00+0100",
+            "2024-01-01",
+        ] {
+            assert_eq!(JsonValue::from(invalid).as_rfc3339(), None, "{invalid}");
+        }
+        assert_eq!(JsonValue::from(1_709_210_700i64).as_rfc3339(), None);
+    }
+}
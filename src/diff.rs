@@ -0,0 +1,128 @@
+use crate::pointer::push_token;
+use crate::value::JsonValue;
+
+/// One difference between two values, located by JSON Pointer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A member or trailing array element only the new value has.
+    Added { path: String, value: JsonValue },
+    /// A member or trailing array element only the old value has.
+    Removed { path: String, value: JsonValue },
+    /// A scalar that differs, or a value whose type changed.
+    Replaced {
+        path: String,
+        old: JsonValue,
+        new: JsonValue,
+    },
+}
+
+impl Change {
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. } | Change::Removed { path, .. } => path,
+            Change::Replaced { path, .. } => path,
+        }
+    }
+}
+
+impl JsonValue {
+    /// The changes that turn `self` into `other`, in document order.
+    ///
+    /// Objects are compared by key, ignoring member order; arrays by index,
+    /// so an element inserted at the front shows up as a change to every
+    /// element after it.
+    pub fn diff(&self, other: &JsonValue) -> Vec<Change> {
+        let mut changes = Vec::new();
+        diff_into(self, other, &mut String::new(), &mut changes);
+        changes
+    }
+}
+
+fn diff_into(old: &JsonValue, new: &JsonValue, path: &mut String, changes: &mut Vec<Change>) {
+    let base = path.len();
+    let push = |path: &mut String, token: &str| {
+        path.truncate(base);
+        push_token(path, token);
+    };
+    match (old, new) {
+        (JsonValue::Object(old_members), JsonValue::Object(new_members)) => {
+            for (key, old_value) in old_members {
+                push(path, key);
+                match new.get(key) {
+                    Some(new_value) => diff_into(old_value, new_value, path, changes),
+                    None => changes.push(Change::Removed {
+                        path: path.clone(),
+                        value: old_value.clone(),
+                    }),
+                }
+            }
+            for (key, new_value) in new_members {
+                if !old.contains_key(key) {
+                    push(path, key);
+                    changes.push(Change::Added {
+                        path: path.clone(),
+                        value: new_value.clone(),
+                    });
+                }
+            }
+        }
+        (JsonValue::Array(old_elements), JsonValue::Array(new_elements)) => {
+            for (i, old_value) in old_elements.iter().enumerate() {
+                push(path, &i.to_string());
+                match new_elements.get(i) {
+                    Some(new_value) => diff_into(old_value, new_value, path, changes),
+                    None => changes.push(Change::Removed {
+                        path: path.clone(),
+                        value: old_value.clone(),
+                    }),
+                }
+            }
+            for (i, new_value) in new_elements.iter().enumerate().skip(old_elements.len()) {
+                push(path, &i.to_string());
+                changes.push(Change::Added {
+                    path: path.clone(),
+                    value: new_value.clone(),
+                });
+            }
+        }
+        _ if old != new => changes.push(Change::Replaced {
+            path: path.clone(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+    path.truncate(base);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_diff() -> anyhow::Result<()> {
+        let old = from_str(r#"{"a": 1, "b": [1, 2, 3], "c": {"d": true, "e/f": null}, "g": "x"}"#)?;
+        let new = from_str(r#"{"g": "x", "c": {"d": false}, "b": [1, 5], "a": [1], "h": 0}"#)?;
+        let paths: Vec<_> = old
+            .diff(&new)
+            .iter()
+            .map(|c| match c {
+                Change::Added { .. } => format!("+{}", c.path()),
+                Change::Removed { .. } => format!("-{}", c.path()),
+                Change::Replaced { .. } => format!("~{}", c.path()),
+            })
+            .collect();
+        assert_eq!(paths, ["~/a", "~/b/1", "-/b/2", "~/c/d", "-/c/e~1f", "+/h"]);
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(
+            from_str("1")?.diff(&from_str("2")?),
+            [Change::Replaced {
+                path: String::new(),
+                old: from_str("1")?,
+                new: from_str("2")?,
+            }]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+//! Differential tests against serde_json: random documents, valid and
+//! corrupted, must be accepted or rejected exactly when serde_json does,
+//! and the accepted ones must parse to the same structure. A disagreement
+//! is a bug in this crate.
+//!
+//! serde_json is built with `arbitrary_precision`, so it keeps every
+//! number literal instead of rejecting those beyond `f64` range. Integer
+//! literals are compared digit for digit, others by their `f64` value,
+//! both as read with `keep_number_text` and as read by default. Objects are compared as maps in which a repeated key
+//! keeps its last value, which is what both parsers do by default.
+//!
+//! On top of that, the crate's own readers must agree with each other:
+//! [`validate`], [`parse_partial`] and [`prettify_stream`] with
+//! [`from_str`], and serializing must give text that parses back to the
+//! same value.
+//!
+//! The suite needs the `differential` feature, which pulls in serde_json.
+//! The default run is short; for a longer one, set the number of
+//! documents and, to replay a failure, the seed its message names:
+//!
+//! ```text
+//! cargo test --features differential differential
+//! JSON_PARSER_DIFF_CASES=1000000 cargo test --release --features differential differential
+//! JSON_PARSER_DIFF_SEED=12345 cargo test --features differential differential
+//! ```
+
+use crate::{
+    IndentStyle, JsonValue, ParserOptions, from_str, from_str_with, parse_partial, prettify_stream,
+    tokenize, validate,
+};
+use std::collections::BTreeMap;
+
+/// xorshift64*, enough to spread the cases.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, choices: &[&'a str]) -> &'a str {
+        choices[self.below(choices.len())]
+    }
+}
+
+fn env(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn whitespace(rng: &mut Rng, out: &mut String) {
+    for _ in 0..rng.below(3) {
+        out.push_str(rng.pick(&[" ", "\t", "\n", "\r\n", "  "]));
+    }
+}
+
+fn string(rng: &mut Rng, out: &mut String) {
+    out.push('"');
+    for _ in 0..rng.below(6) {
+        out.push_str(rng.pick(&[
+            "a",
+            "Z",
+            " ",
+            "é",
+            "😀",
+            "/",
+            ",",
+            ":",
+            "[",
+            "}",
+            "\\n",
+            "\\\"",
+            "\\\\",
+            "\\/",
+            "\\t",
+            "\\u0041",
+            "\\u00e9",
+            "\\ud83d\\ude00",
+            "\\u0000",
+        ]));
+    }
+    out.push('"');
+}
+
+fn number(rng: &mut Rng, out: &mut String) {
+    if rng.below(3) == 0 {
+        out.push('-');
+    }
+    if rng.below(4) == 0 {
+        out.push('0');
+    } else {
+        out.push(char::from(b'1' + rng.below(9) as u8));
+        // Up to 21 digits, past the range of both i64 and u64.
+        for _ in 0..rng.below(21) {
+            out.push(char::from(b'0' + rng.below(10) as u8));
+        }
+    }
+    if rng.below(3) == 0 {
+        out.push('.');
+        for _ in 0..=rng.below(4) {
+            out.push(char::from(b'0' + rng.below(10) as u8));
+        }
+    }
+    if rng.below(4) == 0 {
+        out.push_str(rng.pick(&["e", "E", "e+", "e-", "E-"]));
+        out.push_str(&rng.below(60).to_string());
+    }
+}
+
+/// Appends a random valid value nested at most `depth` levels.
+fn value(rng: &mut Rng, depth: usize, out: &mut String) {
+    let kinds = if depth == 0 { 4 } else { 6 };
+    match rng.below(kinds) {
+        0 => out.push_str(rng.pick(&["true", "false", "null"])),
+        1 | 2 => number(rng, out),
+        3 => string(rng, out),
+        4 => {
+            out.push('[');
+            whitespace(rng, out);
+            for i in 0..rng.below(4) {
+                if i > 0 {
+                    out.push(',');
+                    whitespace(rng, out);
+                }
+                value(rng, depth - 1, out);
+                whitespace(rng, out);
+            }
+            out.push(']');
+        }
+        _ => {
+            out.push('{');
+            whitespace(rng, out);
+            for i in 0..rng.below(4) {
+                if i > 0 {
+                    out.push(',');
+                    whitespace(rng, out);
+                }
+                string(rng, out);
+                whitespace(rng, out);
+                out.push(':');
+                whitespace(rng, out);
+                value(rng, depth - 1, out);
+                whitespace(rng, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/// Damages `text` in one random place; the result may still be valid.
+fn corrupt(rng: &mut Rng, text: &str) -> String {
+    let boundaries: Vec<_> = (0..=text.len())
+        .filter(|&i| text.is_char_boundary(i))
+        .collect();
+    let at = boundaries[rng.below(boundaries.len())];
+    let (head, tail) = text.split_at(at);
+    let mut rest = tail.chars();
+    let noise = rng.pick(&[
+        "[", "]", "{", "}", ",", ":", "\"", "\\", " ", "-", "+", ".", "e", "0", "01", "x", "tru",
+        "nul", "\u{1}", "é", "//",
+    ]);
+    match rng.below(4) {
+        0 => head.to_string(),
+        1 => {
+            rest.next();
+            format!("{head}{}", rest.as_str())
+        }
+        2 => format!("{head}{noise}{tail}"),
+        _ => {
+            rest.next();
+            format!("{head}{noise}{}", rest.as_str())
+        }
+    }
+}
+
+fn prettify(input: &str) -> anyhow::Result<String> {
+    let mut out = Vec::new();
+    prettify_stream(input.as_bytes(), &mut out, IndentStyle::default())?;
+    Ok(String::from_utf8(out)?)
+}
+
+/// A parsed document in the form both parsers are compared in.
+#[derive(Debug, PartialEq)]
+enum Canonical {
+    Null,
+    Bool(bool),
+    /// See [`canonical_number`].
+    Number(String),
+    String(String),
+    Array(Vec<Canonical>),
+    Object(BTreeMap<String, Canonical>),
+}
+
+impl Canonical {
+    /// From a value parsed with `keep_number_text`.
+    fn from_value(value: &JsonValue) -> Self {
+        match value {
+            JsonValue::Null => Canonical::Null,
+            JsonValue::Bool(b) => Canonical::Bool(*b),
+            JsonValue::Number(n) => Canonical::Number(canonical_number(&n.to_string())),
+            JsonValue::String(s) => Canonical::String(s.clone()),
+            JsonValue::Array(elements) => {
+                Canonical::Array(elements.iter().map(Canonical::from_value).collect())
+            }
+            JsonValue::Object(members) => Canonical::Object(
+                members
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Canonical::from_value(v)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn from_serde(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Canonical::Null,
+            serde_json::Value::Bool(b) => Canonical::Bool(*b),
+            serde_json::Value::Number(n) => Canonical::Number(canonical_number(&n.to_string())),
+            serde_json::Value::String(s) => Canonical::String(s.clone()),
+            serde_json::Value::Array(elements) => {
+                Canonical::Array(elements.iter().map(Canonical::from_serde).collect())
+            }
+            serde_json::Value::Object(members) => Canonical::Object(
+                members
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Canonical::from_serde(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// An integer literal as written, other literals as their `f64` value,
+/// since serde_json rewrites exponents (`1e5` as `1e+5`).
+fn canonical_number(literal: &str) -> String {
+    if literal.contains(['.', 'e', 'E']) {
+        format!("{:?}", literal.parse::<f64>().unwrap())
+    } else {
+        literal.to_string()
+    }
+}
+
+/// Every number of `value` as an `f64`, in document order.
+fn numbers(value: &JsonValue) -> Vec<f64> {
+    let mut found = Vec::new();
+    let _ = value.walk(|_, node| {
+        found.extend(node.as_f64());
+        std::ops::ControlFlow::<()>::Continue(())
+    });
+    found
+}
+
+/// Compares a document serde_json accepts, `expected`, with what the
+/// crate's readers make of it.
+fn check_valid(input: &str, expected: &serde_json::Value, case: &str) {
+    let value = from_str(input).unwrap_or_else(|e| panic!("{case}: {e}"));
+    let as_text = ParserOptions {
+        keep_number_text: true,
+        ..Default::default()
+    };
+    let text_value = from_str_with(input, &as_text).unwrap_or_else(|e| panic!("{case}: {e}"));
+    assert_eq!(
+        Canonical::from_value(&text_value),
+        Canonical::from_serde(expected),
+        "{case}"
+    );
+    let literals: Vec<f64> = numbers(&text_value);
+    let values = numbers(&value);
+    assert_eq!(values.len(), literals.len(), "{case}");
+    for (value, literal) in values.iter().zip(&literals) {
+        // NaN never occurs, so `==` also matches the sign of zero.
+        assert!(
+            value == literal && value.is_sign_negative() == literal.is_sign_negative(),
+            "{case}: {value} vs {literal}"
+        );
+    }
+
+    assert!(validate(input).is_ok(), "{case}");
+    assert!(tokenize(input).is_ok(), "{case}");
+    assert_eq!(parse_partial(input), (value.clone(), true), "{case}");
+    let pretty = prettify(input).unwrap_or_else(|e| panic!("{case}: {e}"));
+    assert_eq!(from_str(&pretty).ok().as_ref(), Some(&value), "{case}");
+    for text in [value.to_string(), value.to_string_pretty()] {
+        assert_eq!(from_str(&text).ok().as_ref(), Some(&value), "{case}");
+    }
+}
+
+fn check(input: &str, case: &str) {
+    let expected = serde_json::from_str::<serde_json::Value>(input);
+    let ok = expected.is_ok();
+    assert_eq!(
+        from_str(input).is_ok(),
+        ok,
+        "{case}: serde_json says {expected:?}"
+    );
+    assert_eq!(validate(input).is_ok(), ok, "{case}");
+    assert_eq!(parse_partial(input).1, ok, "{case}");
+    if let Ok(expected) = expected {
+        check_valid(input, &expected, case);
+    }
+}
+
+#[test]
+fn test_differential() {
+    let seed = env("JSON_PARSER_DIFF_SEED", 0x5eed);
+    let cases = env("JSON_PARSER_DIFF_CASES", 2_000);
+    let mut rng = Rng(seed.max(1));
+    for i in 0..cases {
+        let mut input = String::new();
+        whitespace(&mut rng, &mut input);
+        value(&mut rng, 4, &mut input);
+        whitespace(&mut rng, &mut input);
+        check(&input, &format!("seed {seed}, case {i}: {input:?}"));
+        let corrupted = corrupt(&mut rng, &input);
+        check(
+            &corrupted,
+            &format!("seed {seed}, case {i} corrupted: {corrupted:?}"),
+        );
+    }
+}
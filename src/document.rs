@@ -0,0 +1,250 @@
+use crate::error::Error;
+use crate::spans::{SpanKind, SpanTree};
+use crate::value::JsonValue;
+use anyhow::{Context, Result, bail};
+use std::fmt;
+use std::ops::Range;
+
+/// A change to a [`Document`]'s text: the bytes in `range` replaced by
+/// `text`, as an editor reports a keystroke or a paste.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// A parsed document that remembers its source text, for editors that must
+/// keep diffs minimal.
+///
+/// Everything outside an edited value is preserved byte for byte: all
+/// whitespace, the original spelling of numbers and strings (escapes
+/// included) and member order. An edited value is replaced by its compact
+/// serialization, so whitespace *inside* a replaced container is lost, while
+/// the whitespace around it is kept.
+#[derive(Debug, Clone)]
+pub struct Document {
+    source: String,
+    value: JsonValue,
+    spans: SpanTree,
+}
+
+impl Document {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let (value, spans) = crate::parse_with_spans(input)?;
+        Ok(Document {
+            source: input.to_string(),
+            value,
+            spans,
+        })
+    }
+
+    pub fn value(&self) -> &JsonValue {
+        &self.value
+    }
+
+    /// Where each node of the value is in the source text.
+    pub fn spans(&self) -> &SpanTree {
+        &self.spans
+    }
+
+    /// Applies `edit` to the source text and brings the value and spans up
+    /// to date, reparsing as little as it can.
+    ///
+    /// Only the innermost array element or object member value enclosing
+    /// the whole edit is reparsed, so an edit to a key or a separator
+    /// reparses the container around it. The new value is spliced in
+    /// without touching the rest of the tree, whose spans are shifted past
+    /// it. If no value below the root encloses the edit, or the reparsed
+    /// text is no longer a single value (after typing a `,`, say), the
+    /// whole document is reparsed instead. On error the document is left
+    /// as it was.
+    pub fn reparse(&mut self, edit: Edit) -> Result<()> {
+        let Range { start, end } = edit.range;
+        if start > end || !self.source.is_char_boundary(start) || !self.source.is_char_boundary(end)
+        {
+            bail!("Edit range {start}..{end} is not within the document text");
+        }
+        let mut source = self.source.clone();
+        source.replace_range(start..end, &edit.text);
+
+        // Child indices down to the innermost value below the root that
+        // encloses the edit.
+        let mut path = Vec::new();
+        let mut node = &self.spans;
+        while let Some((i, child)) = children(node)
+            .enumerate()
+            .find(|(_, c)| c.span.start <= start && end <= c.span.end)
+        {
+            path.push(i);
+            node = child;
+        }
+        let old = node.span;
+        let new_end = old.end + edit.text.len() - (end - start);
+        let fragment = (!path.is_empty())
+            .then(|| crate::parse_with_spans(&source[old.start..new_end]).ok())
+            .flatten();
+        let Some((value, mut tree)) = fragment else {
+            *self = Document::parse(&source)?;
+            return Ok(());
+        };
+
+        shift_spans(&mut tree, 0, |offset| offset + old.start);
+        shift_spans(&mut self.spans, old.end, |offset| {
+            offset + edit.text.len() - (end - start)
+        });
+        let (mut node, mut target) = (&mut self.spans, &mut self.value);
+        for &i in &path {
+            node = match &mut node.kind {
+                SpanKind::Array(elements) => &mut elements[i],
+                SpanKind::Object(members) => &mut members[i].2,
+                SpanKind::Scalar => unreachable!("paths only descend into containers"),
+            };
+            target = match target {
+                JsonValue::Array(elements) => &mut elements[i],
+                JsonValue::Object(members) => &mut members[i].1,
+                _ => unreachable!("span trees mirror their values"),
+            };
+        }
+        *node = tree;
+        *target = value;
+        self.source = source;
+        Ok(())
+    }
+
+    /// Replaces the value at `ptr` (a JSON Pointer), which must exist.
+    pub fn replace(&mut self, ptr: &str, value: JsonValue) -> Result<()> {
+        let span = self
+            .spans
+            .pointer(ptr)
+            .with_context(|| format!("No value at {ptr:?}"))?
+            .span;
+        let mut source = self.source.clone();
+        source.replace_range(span.start..span.end, &value.to_string());
+        // Reparsing keeps the spans of every node in step with the new text.
+        *self = Document::parse(&source)?;
+        Ok(())
+    }
+}
+
+/// The nodes of the element or member values directly inside `node`.
+fn children(node: &SpanTree) -> Box<dyn Iterator<Item = &SpanTree> + '_> {
+    match &node.kind {
+        SpanKind::Scalar => Box::new(std::iter::empty()),
+        SpanKind::Array(elements) => Box::new(elements.iter()),
+        SpanKind::Object(members) => Box::new(members.iter().map(|(_, _, v)| v)),
+    }
+}
+
+/// Moves every span offset in `tree` at or after `from` through `f`.
+fn shift_spans(tree: &mut SpanTree, from: usize, f: impl Fn(usize) -> usize) {
+    let shift = |offset: &mut usize| {
+        if *offset >= from {
+            *offset = f(*offset);
+        }
+    };
+    let mut stack = vec![tree];
+    while let Some(node) = stack.pop() {
+        shift(&mut node.span.start);
+        shift(&mut node.span.end);
+        match &mut node.kind {
+            SpanKind::Scalar => {}
+            SpanKind::Array(elements) => stack.extend(elements),
+            SpanKind::Object(members) => {
+                for (_, key, value) in members {
+                    shift(&mut key.start);
+                    shift(&mut key.end);
+                    stack.push(value);
+                }
+            }
+        }
+    }
+}
+
+/// Writes the source text, with edits applied.
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Number;
+
+    #[test]
+    fn test_minimal_edit() -> Result<()> {
+        let input =
+            "{\n    \"name\":   \"a\\u0062c\",\n\t\"n\": [1.50, 2e0],\n  \"x\" : {\"y\": 1}\n}\n";
+        let mut doc = Document::parse(input)?;
+        assert_eq!(doc.to_string(), input);
+
+        doc.replace("/n/1", JsonValue::Number(Number::Int(3)))?;
+        doc.replace("/x", crate::from_str(r#"{ "z" : [ true ] }"#)?)?;
+        assert_eq!(
+            doc.to_string(),
+            "{\n    \"name\":   \"a\\u0062c\",\n\t\"n\": [1.50, 3],\n  \"x\" : {\"z\":[true]}\n}\n"
+        );
+        assert_eq!(
+            doc.value().pointer("/x/z/0").unwrap(),
+            &JsonValue::Bool(true)
+        );
+        assert!(doc.replace("/missing", JsonValue::Null).is_err());
+        Ok(())
+    }
+
+    /// Applies `edit` with `reparse`, checks the result against a full
+    /// parse, and returns whether the array at `/a` was reused.
+    fn check_reparse(doc: &mut Document, at: &str, replaced: &str, text: &str) -> Result<bool> {
+        let elements = |doc: &Document| match doc.value().get("a") {
+            Some(JsonValue::Array(elements)) => elements.as_ptr(),
+            _ => std::ptr::null(),
+        };
+        let before = elements(doc);
+        let start = doc.source.find(at).unwrap();
+        let edit = Edit {
+            range: start..start + replaced.len(),
+            text: text.to_string(),
+        };
+        let mut expected = doc.source.clone();
+        expected.replace_range(edit.range.clone(), text);
+        doc.reparse(edit)?;
+        let full = Document::parse(&expected)?;
+        assert_eq!(doc.to_string(), expected);
+        assert_eq!(doc.value(), full.value());
+        assert_eq!(doc.spans(), full.spans());
+        Ok(elements(doc) == before)
+    }
+
+    #[test]
+    fn test_reparse() -> Result<()> {
+        let mut doc = Document::parse(
+            "{\"a\": [1, {\"b\": \"x\", \"c\": [true]}, 30], \"d\": {\"e\": null}}  ",
+        )?;
+        // Within a scalar, and growing a nested container: local.
+        assert!(check_reparse(&mut doc, "\"x\"", "\"x", "\"longer")?);
+        assert!(check_reparse(&mut doc, "true", "true", "[false, \"é\"]")?);
+        assert!(check_reparse(&mut doc, "null", "null", "{\"f\": 1}")?);
+        assert!(check_reparse(&mut doc, "30", "", " ")?);
+        // A key is reparsed with its object.
+        assert!(check_reparse(&mut doc, "\"b\"", "\"b\"", "\"bb\"")?);
+        // Adding an element, and editing around the root: the whole document.
+        assert!(!check_reparse(&mut doc, "30", "30", "30, 40")?);
+        assert!(!check_reparse(&mut doc, "  ", "", "\n")?);
+        // A value that no longer fits: the full reparse reports the error.
+        let before = doc.to_string();
+        let start = before.find("40").unwrap();
+        let edit = Edit {
+            range: start..start + 2,
+            text: "4 0".to_string(),
+        };
+        assert!(doc.reparse(edit).is_err());
+        assert_eq!(doc.to_string(), before);
+        let edit = Edit {
+            range: 1..before.len() + 1,
+            text: String::new(),
+        };
+        assert!(doc.reparse(edit).is_err());
+        Ok(())
+    }
+}
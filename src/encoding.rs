@@ -0,0 +1,252 @@
+use crate::error::{Error, LexError, Position};
+use crate::value::JsonValue;
+
+/// Byte order of UTF-16 input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+/// A Unicode encoding of JSON text, as told apart by [`detect_encoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16(Endian),
+    Utf32(Endian),
+}
+
+/// Guesses the encoding of `bytes` from their start.
+///
+/// A byte order mark decides if there is one: `EF BB BF` for UTF-8,
+/// `FE FF` or `FF FE` for UTF-16 and `00 00 FE FF` or `FF FE 00 00` for
+/// UTF-32, big- and little-endian respectively. Otherwise, following
+/// RFC 4627 §3, the first two characters of a JSON text are ASCII, so the
+/// pattern of NUL bytes among the first four gives the encoding away:
+///
+/// | bytes         | encoding  |
+/// |---------------|-----------|
+/// | `00 00 00 xx` | UTF-32BE  |
+/// | `00 xx 00 xx` | UTF-16BE  |
+/// | `xx 00 00 00` | UTF-32LE  |
+/// | `xx 00 xx 00` | UTF-16LE  |
+/// | anything else | UTF-8     |
+///
+/// A text only one character long is told apart by its first two bytes,
+/// `00 xx` or `xx 00`, as UTF-16.
+pub fn detect_encoding(bytes: &[u8]) -> Encoding {
+    use Encoding::*;
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => Utf8,
+        [0x00, 0x00, 0xFE, 0xFF, ..] => Utf32(Endian::Big),
+        [0xFF, 0xFE, 0x00, 0x00, ..] => Utf32(Endian::Little),
+        [0xFE, 0xFF, ..] => Utf16(Endian::Big),
+        [0xFF, 0xFE, ..] => Utf16(Endian::Little),
+        [0, 0, 0, _, ..] => Utf32(Endian::Big),
+        [_, 0, 0, 0, ..] => Utf32(Endian::Little),
+        [0, _, ..] => Utf16(Endian::Big),
+        [_, 0, ..] => Utf16(Endian::Little),
+        _ => Utf8,
+    }
+}
+
+/// Parses JSON text in UTF-8, UTF-16 or UTF-32, whichever
+/// [`detect_encoding`] finds, for ingesting documents from sources that
+/// don't say. A byte order mark is skipped. UTF-32 needs the `utf32`
+/// feature and is rejected without it.
+pub fn from_bytes_autodetect(bytes: &[u8]) -> Result<JsonValue, Error> {
+    match detect_encoding(bytes) {
+        Encoding::Utf8 => crate::from_slice(bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes)),
+        Encoding::Utf16(endian) => from_utf16_with(bytes, endian),
+        #[cfg(feature = "utf32")]
+        Encoding::Utf32(endian) => crate::from_str(&utf32_to_string(bytes, endian)?),
+        #[cfg(not(feature = "utf32"))]
+        Encoding::Utf32(_) => Err(Error::Lex(LexError::new(
+            "UTF-32 input needs the `utf32` feature",
+            Position::locate("", 0),
+        ))),
+    }
+}
+
+/// Parses UTF-16 input whose byte order is given by a leading byte order
+/// mark. Input without a BOM is rejected; use [`from_utf16_with`] for it.
+pub fn from_utf16(bytes: &[u8]) -> Result<JsonValue, Error> {
+    match bytes {
+        [0xFF, 0xFE, ..] => from_utf16_with(bytes, Endian::Little),
+        [0xFE, 0xFF, ..] => from_utf16_with(bytes, Endian::Big),
+        _ => Err(Error::Lex(LexError::new(
+            "Missing UTF-16 byte order mark",
+            Position::locate("", 0),
+        ))),
+    }
+}
+
+/// Parses UTF-16 input in the given byte order. A BOM matching `endian` is
+/// skipped.
+pub fn from_utf16_with(bytes: &[u8], endian: Endian) -> Result<JsonValue, Error> {
+    crate::from_str(&utf16_to_string(bytes, endian)?)
+}
+
+/// Transcodes to UTF-8. On failure the error's `offset` is a byte offset
+/// into `bytes`, while `line` and `column` refer to the text decoded so far.
+pub(crate) fn utf16_to_string(bytes: &[u8], endian: Endian) -> Result<String, Error> {
+    let units = bytes.chunks(2).map(|pair| match (pair, endian) {
+        ([a, b], Endian::Little) => Ok(u16::from_le_bytes([*a, *b])),
+        ([a, b], Endian::Big) => Ok(u16::from_be_bytes([*a, *b])),
+        _ => Err(()),
+    });
+    let mut out = String::with_capacity(bytes.len() / 2);
+    let mut offset = 0;
+    let error = |out: &str, offset, message| {
+        let mut position = Position::locate(out, out.len());
+        position.offset = offset;
+        Error::Lex(LexError::new(message, position))
+    };
+    let units: Vec<u16> = units
+        .collect::<Result<_, _>>()
+        .map_err(|()| error("", bytes.len() - 1, "Odd number of bytes in UTF-16 input"))?;
+    for c in char::decode_utf16(units) {
+        match c {
+            Ok('\u{FEFF}') if offset == 0 => {}
+            Ok(c) => out.push(c),
+            Err(_) => return Err(error(&out, offset, "Unpaired surrogate in UTF-16 input")),
+        }
+        offset += c.map_or(2, |c| c.len_utf16() * 2);
+    }
+    Ok(out)
+}
+
+/// Transcodes to UTF-8, with errors located as by `utf16_to_string`.
+#[cfg(feature = "utf32")]
+fn utf32_to_string(bytes: &[u8], endian: Endian) -> Result<String, Error> {
+    let mut out = String::with_capacity(bytes.len() / 4);
+    let error = |out: &str, offset, message| {
+        let mut position = Position::locate(out, out.len());
+        position.offset = offset;
+        Error::Lex(LexError::new(message, position))
+    };
+    for (i, quad) in bytes.chunks(4).enumerate() {
+        let code = match (quad, endian) {
+            ([a, b, c, d], Endian::Little) => u32::from_le_bytes([*a, *b, *c, *d]),
+            ([a, b, c, d], Endian::Big) => u32::from_be_bytes([*a, *b, *c, *d]),
+            _ => {
+                let message = "Number of bytes in UTF-32 input not a multiple of 4";
+                return Err(error(&out, i * 4, message));
+            }
+        };
+        match char::from_u32(code) {
+            Some('\u{FEFF}') if i == 0 => {}
+            Some(c) => out.push(c),
+            None => return Err(error(&out, i * 4, "Invalid code point in UTF-32 input")),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(text: &str, endian: Endian, bom: bool) -> Vec<u8> {
+        let bom = bom.then_some('\u{FEFF}');
+        bom.into_iter()
+            .chain(text.chars())
+            .collect::<String>()
+            .encode_utf16()
+            .flat_map(|u| match endian {
+                Endian::Little => u.to_le_bytes(),
+                Endian::Big => u.to_be_bytes(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_utf16() -> anyhow::Result<()> {
+        let text = r#"{"name": "Zoë 😀"}"#;
+        let expected = crate::from_str(text)?;
+        assert_eq!(from_utf16(&encode(text, Endian::Little, true))?, expected);
+        assert_eq!(from_utf16(&encode(text, Endian::Big, true))?, expected);
+        assert_eq!(
+            from_utf16_with(&encode(text, Endian::Big, false), Endian::Big)?,
+            expected
+        );
+        assert!(from_utf16(&encode(text, Endian::Big, false)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_encoding() -> anyhow::Result<()> {
+        let text = r#"{"name": "Zoë 😀"}"#;
+        let expected = crate::from_str(text)?;
+        for (bytes, encoding) in [
+            (text.as_bytes().to_vec(), Encoding::Utf8),
+            ([b"\xEF\xBB\xBF", text.as_bytes()].concat(), Encoding::Utf8),
+            (
+                encode(text, Endian::Little, false),
+                Encoding::Utf16(Endian::Little),
+            ),
+            (
+                encode(text, Endian::Big, false),
+                Encoding::Utf16(Endian::Big),
+            ),
+            (
+                encode(text, Endian::Little, true),
+                Encoding::Utf16(Endian::Little),
+            ),
+            (
+                encode(text, Endian::Big, true),
+                Encoding::Utf16(Endian::Big),
+            ),
+        ] {
+            assert_eq!(detect_encoding(&bytes), encoding);
+            assert_eq!(from_bytes_autodetect(&bytes)?, expected);
+        }
+        // One-character documents.
+        assert_eq!(from_bytes_autodetect(&encode("7", Endian::Big, false))?, 7);
+        assert_eq!(
+            from_bytes_autodetect(&encode("7", Endian::Little, false))?,
+            7
+        );
+        assert_eq!(from_bytes_autodetect(b"7")?, 7);
+        assert!(from_bytes_autodetect(b"").is_err());
+
+        let utf32 = |endian: Endian| -> Vec<u8> {
+            text.chars()
+                .flat_map(|c| match endian {
+                    Endian::Little => (c as u32).to_le_bytes(),
+                    Endian::Big => (c as u32).to_be_bytes(),
+                })
+                .collect()
+        };
+        assert_eq!(
+            detect_encoding(&utf32(Endian::Big)),
+            Encoding::Utf32(Endian::Big)
+        );
+        assert_eq!(
+            detect_encoding(&utf32(Endian::Little)),
+            Encoding::Utf32(Endian::Little)
+        );
+        assert_eq!(
+            detect_encoding(&[0xFF, 0xFE, 0, 0, b'1', 0, 0, 0]),
+            Encoding::Utf32(Endian::Little)
+        );
+        #[cfg(feature = "utf32")]
+        {
+            assert_eq!(from_bytes_autodetect(&utf32(Endian::Big))?, expected);
+            assert_eq!(from_bytes_autodetect(&utf32(Endian::Little))?, expected);
+            let err = from_bytes_autodetect(&[b'[', 0, 0, 0, 0, 0, 0xD8, 0]).unwrap_err();
+            assert_eq!(err.position().offset, 4);
+        }
+        #[cfg(not(feature = "utf32"))]
+        assert!(from_bytes_autodetect(&utf32(Endian::Big)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lone_surrogate() {
+        // `["` followed by a lone high surrogate.
+        let bytes = [0xFF, 0xFE, b'[', 0, b'"', 0, 0x3D, 0xD8, b'"', 0, b']', 0];
+        let err = from_utf16(&bytes).unwrap_err();
+        assert_eq!(err.position().offset, 6);
+    }
+}
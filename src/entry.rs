@@ -0,0 +1,98 @@
+use crate::value::JsonValue;
+use anyhow::{Result, bail};
+
+/// A view into one key of an object, which may or may not be present. Made
+/// by [`JsonValue::entry`].
+pub enum Entry<'a> {
+    Occupied(&'a mut JsonValue),
+    Vacant {
+        members: &'a mut Vec<(String, JsonValue)>,
+        key: String,
+    },
+}
+
+impl<'a> Entry<'a> {
+    /// Inserts `default` if the key is absent, then returns its value.
+    pub fn or_insert(self, default: JsonValue) -> &'a mut JsonValue {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> JsonValue>(self, default: F) -> &'a mut JsonValue {
+        match self {
+            Entry::Occupied(value) => value,
+            Entry::Vacant { members, key } => {
+                members.push((key, default()));
+                &mut members.last_mut().unwrap().1
+            }
+        }
+    }
+
+    /// Inserts `null` if the key is absent.
+    pub fn or_default(self) -> &'a mut JsonValue {
+        self.or_insert_with(JsonValue::default)
+    }
+
+    /// Runs `f` on the value if the key is present.
+    pub fn and_modify<F: FnOnce(&mut JsonValue)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(value) => {
+                f(value);
+                Entry::Occupied(value)
+            }
+            vacant => vacant,
+        }
+    }
+}
+
+impl JsonValue {
+    /// Gets the entry for `key` for in-place insertion or update. New keys
+    /// are appended after the existing members.
+    ///
+    /// A `Null` value is first replaced by an empty object, so entries can
+    /// be chained on a fresh `JsonValue::default()`. Any other non-object
+    /// value (scalars and arrays) is an error and is left untouched.
+    pub fn entry(&mut self, key: &str) -> Result<Entry<'_>> {
+        if *self == JsonValue::Null {
+            *self = JsonValue::empty_object();
+        }
+        let JsonValue::Object(members) = self else {
+            bail!("Cannot take an entry of a {}", self.type_name());
+        };
+        Ok(match members.iter().position(|(k, _)| k == key) {
+            Some(i) => Entry::Occupied(&mut members[i].1),
+            None => Entry::Vacant {
+                members,
+                key: key.to_string(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Number;
+
+    #[test]
+    fn test_entry() -> Result<()> {
+        let mut value = JsonValue::default();
+        value.entry("count")?.or_insert(JsonValue::Number(0.into()));
+        for _ in 0..2 {
+            value
+                .entry("count")?
+                .and_modify(|v| {
+                    if let JsonValue::Number(Number::Int(n)) = v {
+                        *n += 1;
+                    }
+                })
+                .or_insert(JsonValue::Number(0.into()));
+        }
+        value.entry("tags")?.or_insert_with(JsonValue::empty_array);
+        *value.entry("tags")?.or_default() = JsonValue::Bool(true);
+        assert_eq!(value.to_string(), r#"{"count":2,"tags":true}"#);
+
+        assert!(JsonValue::Number(1.into()).entry("a").is_err());
+        assert!(JsonValue::empty_array().entry("a").is_err());
+        Ok(())
+    }
+}
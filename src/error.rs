@@ -0,0 +1,292 @@
+use crate::value::JsonValue;
+use std::fmt;
+
+/// A location in the input. `line` and `column` are 1-based, with columns
+/// counted in characters; `offset` is the 0-based byte offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub(crate) fn locate(input: &str, offset: usize) -> Self {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// The input could not be split into tokens: a malformed keyword, number or
+/// string literal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub message: String,
+    pub position: Position,
+    /// Where in the document the error happened, outermost first, e.g.
+    /// `["object value for key \"a\"", "array element 2"]`.
+    pub context: Vec<String>,
+}
+
+/// The input was tokenized fine, but the tokens do not form a JSON document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub position: Position,
+    /// See [`LexError::context`].
+    pub context: Vec<String>,
+}
+
+impl LexError {
+    pub(crate) fn new(message: impl Into<String>, position: Position) -> Self {
+        LexError {
+            message: message.into(),
+            position,
+            context: Vec::new(),
+        }
+    }
+}
+
+impl LexError {
+    /// The error as a JSON object for machine consumption; see
+    /// [`ParseError::to_json`].
+    pub fn to_json(&self) -> JsonValue {
+        located_json(&self.message, &self.position, &self.context)
+    }
+}
+
+impl ParseError {
+    pub(crate) fn new(message: impl Into<String>, position: Position) -> Self {
+        ParseError {
+            message: message.into(),
+            position,
+            context: Vec::new(),
+        }
+    }
+
+    /// The error as a JSON object for machine consumption, e.g.
+    /// `{"error":"Expecting ...","line":1,"column":5,"offset":4}`, with a
+    /// `"context"` array of strings after those when there is any.
+    pub fn to_json(&self) -> JsonValue {
+        located_json(&self.message, &self.position, &self.context)
+    }
+}
+
+/// Any failure to turn text into a `JsonValue`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Lex(LexError),
+    Parse(ParseError),
+    /// Parsing was stopped at this position by
+    /// [`ParserOptions::cancel`](crate::ParserOptions::cancel) or
+    /// [`ParserOptions::deadline`](crate::ParserOptions::deadline).
+    Cancelled(Position),
+}
+
+impl Error {
+    pub fn position(&self) -> Position {
+        match self {
+            Error::Lex(e) => e.position,
+            Error::Parse(e) => e.position,
+            Error::Cancelled(position) => *position,
+        }
+    }
+
+    pub fn context(&self) -> &[String] {
+        match self {
+            Error::Lex(e) => &e.context,
+            Error::Parse(e) => &e.context,
+            Error::Cancelled(_) => &[],
+        }
+    }
+
+    /// [`ParseError::to_json`], with a leading `"kind"` of `"lex"`,
+    /// `"parse"` or `"cancelled"`.
+    pub fn to_json(&self) -> JsonValue {
+        let (kind, json) = match self {
+            Error::Lex(e) => ("lex", e.to_json()),
+            Error::Parse(e) => ("parse", e.to_json()),
+            Error::Cancelled(position) => ("cancelled", located_json(CANCELLED, position, &[])),
+        };
+        let JsonValue::Object(mut members) = json else {
+            unreachable!("errors serialize as objects")
+        };
+        members.insert(0, ("kind".to_string(), kind.into()));
+        JsonValue::Object(members)
+    }
+
+    /// Records that the error happened inside `frame`, which encloses every
+    /// frame recorded so far.
+    pub(crate) fn within(mut self, frame: String) -> Self {
+        match &mut self {
+            Error::Lex(e) => e.context.insert(0, frame),
+            Error::Parse(e) => e.context.insert(0, frame),
+            // Where a cancelled parse was is of no interest.
+            Error::Cancelled(_) => {}
+        }
+        self
+    }
+}
+
+const CANCELLED: &str = "Parsing cancelled";
+
+fn write_located(
+    f: &mut fmt::Formatter<'_>,
+    message: &str,
+    position: &Position,
+    context: &[String],
+) -> fmt::Result {
+    write!(f, "{message} at {position}")?;
+    for (i, frame) in context.iter().enumerate() {
+        let sep = if i == 0 { ", while parsing " } else { " → " };
+        write!(f, "{sep}{frame}")?;
+    }
+    Ok(())
+}
+
+fn located_json(message: &str, position: &Position, context: &[String]) -> JsonValue {
+    let number = |n: usize| JsonValue::from(n as i64);
+    let mut members = vec![
+        ("error".to_string(), message.into()),
+        ("line".to_string(), number(position.line)),
+        ("column".to_string(), number(position.column)),
+        ("offset".to_string(), number(position.offset)),
+    ];
+    if !context.is_empty() {
+        let frames = context.iter().map(|frame| frame.as_str().into()).collect();
+        members.push(("context".to_string(), JsonValue::Array(frames)));
+    }
+    JsonValue::Object(members)
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_located(f, &self.message, &self.position, &self.context)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_located(f, &self.message, &self.position, &self.context)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Lex(e) => write!(f, "lex error: {e}"),
+            Error::Parse(e) => write!(f, "parse error: {e}"),
+            Error::Cancelled(position) => write!(f, "{CANCELLED} at {position}"),
+        }
+    }
+}
+
+/// A value nests containers deeper than the configured `depth_budget`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthLimitExceeded {
+    pub limit: usize,
+}
+
+impl fmt::Display for DepthLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nesting deeper than depth budget of {}", self.limit)
+    }
+}
+
+impl std::error::Error for DepthLimitExceeded {}
+
+/// Why a value couldn't be serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerializeError {
+    DepthLimitExceeded(DepthLimitExceeded),
+    /// A NaN or infinite [`Number::Float`](crate::Number::Float), which
+    /// JSON has no text for.
+    NonFiniteNumber,
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializeError::DepthLimitExceeded(e) => e.fmt(f),
+            SerializeError::NonFiniteNumber => f.write_str("NaN or infinite number"),
+        }
+    }
+}
+
+impl From<DepthLimitExceeded> for SerializeError {
+    fn from(e: DepthLimitExceeded) -> Self {
+        SerializeError::DepthLimitExceeded(e)
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+impl std::error::Error for LexError {}
+
+impl std::error::Error for ParseError {}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Lex(e) => Some(e),
+            Error::Parse(e) => Some(e),
+            Error::Cancelled(_) => None,
+        }
+    }
+}
+
+impl From<LexError> for Error {
+    fn from(e: LexError) -> Self {
+        Error::Lex(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate() {
+        let input = "{\n  \"é\": x";
+        let position = Position::locate(input, input.len() - 1);
+        assert_eq!((position.line, position.column), (2, 8));
+    }
+
+    #[test]
+    fn test_to_json() -> anyhow::Result<()> {
+        let err = crate::from_str("{\"a\": [1, }").unwrap_err();
+        let json = err.to_json();
+        assert_eq!(
+            json.to_string(),
+            r#"{"kind":"parse","error":"Expecting an expression. Got EndObject.","line":1,"column":11,"offset":10,"context":["object value for key \"a\"","array element 1"]}"#
+        );
+        assert_eq!(crate::from_str(&json.to_string())?, json);
+        let Error::Parse(e) = err else {
+            panic!("expected a parse error")
+        };
+        assert_eq!(e.to_json().get("offset"), Some(&JsonValue::from(10i64)));
+
+        let json = crate::from_str("nul").unwrap_err().to_json();
+        assert_eq!(json.get("kind"), Some(&JsonValue::from("lex")));
+        assert!(!json.contains_key("context"));
+        assert_eq!(crate::from_str(&json.to_string())?, json);
+        Ok(())
+    }
+}
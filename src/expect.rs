@@ -0,0 +1,34 @@
+/// What the grammar allows next, for the passes that check a document
+/// token by token without building it: [`transform`](crate::transform())
+/// and [`prettify_stream`](crate::prettify_stream).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Expect {
+    Value,
+    /// Just after `[`.
+    ValueOrEnd,
+    /// Just after `{`.
+    KeyOrEnd,
+    Key,
+    Colon,
+    /// After a value inside a container.
+    SeparatorOrEnd,
+    /// After the root value.
+    Nothing,
+}
+
+impl Expect {
+    /// What errors say was expected; `in_object` tells which closing
+    /// bracket may follow a value.
+    pub(crate) fn describe(self, in_object: bool) -> &'static str {
+        match self {
+            Expect::Value => "a value",
+            Expect::ValueOrEnd => "a value or `]`",
+            Expect::KeyOrEnd => "an object key or `}`",
+            Expect::Key => "an object key",
+            Expect::Colon => "`:`",
+            Expect::SeparatorOrEnd if in_object => "`,` or `}`",
+            Expect::SeparatorOrEnd => "`,` or `]`",
+            Expect::Nothing => "end of input",
+        }
+    }
+}
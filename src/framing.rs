@@ -0,0 +1,66 @@
+use crate::value::JsonValue;
+use anyhow::{Context, Result, bail};
+use std::io::{Read, Write};
+
+/// Reads one frame of a `<u32 little-endian length><JSON bytes>` protocol
+/// and parses its payload, which must be exactly one document.
+///
+/// Fails if the stream ends inside the frame, including before its length.
+/// The payload is read as it arrives rather than allocated up front, so a
+/// corrupt length can't by itself exhaust memory.
+pub fn parse_length_prefixed<R: Read>(r: &mut R) -> Result<JsonValue> {
+    let mut prefix = [0; 4];
+    r.read_exact(&mut prefix)
+        .context("Stream ended before the frame length")?;
+    let len = u32::from_le_bytes(prefix) as usize;
+    let mut payload = Vec::new();
+    r.take(len as u64).read_to_end(&mut payload)?;
+    if payload.len() < len {
+        bail!(
+            "Frame promised {len} bytes but the stream ended after {}",
+            payload.len()
+        );
+    }
+    Ok(crate::from_slice(&payload)?)
+}
+
+/// Writes `value` compactly as one frame for [`parse_length_prefixed`].
+pub fn write_length_prefixed<W: Write>(w: &mut W, value: &JsonValue) -> Result<()> {
+    let payload = value.to_string();
+    let len = u32::try_from(payload.len()).context("Frame payload over 4 GiB")?;
+    w.write_all(&len.to_le_bytes())?;
+    w.write_all(payload.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_length_prefixed() -> anyhow::Result<()> {
+        let values = [from_str(r#"{"a": [1, "é"]}"#)?, JsonValue::Null];
+        let mut buffer = Vec::new();
+        for value in &values {
+            write_length_prefixed(&mut buffer, value)?;
+        }
+        assert_eq!(&buffer[..4], &14u32.to_le_bytes());
+        let mut reader = buffer.as_slice();
+        for value in &values {
+            assert_eq!(&parse_length_prefixed(&mut reader)?, value);
+        }
+        assert!(parse_length_prefixed(&mut reader).is_err());
+
+        let frame = |len: u32, payload: &[u8]| [&len.to_le_bytes()[..], payload].concat();
+        // Short payload, short length, and data after the document.
+        assert!(parse_length_prefixed(&mut frame(10, b"[1]").as_slice()).is_err());
+        assert!(parse_length_prefixed(&mut &b"\x03\x00"[..]).is_err());
+        assert!(parse_length_prefixed(&mut frame(6, b"[1] [2]").as_slice()).is_err());
+        // Only `len` bytes belong to the frame.
+        let mut reader: &[u8] = &frame(3, b"[1]garbage");
+        assert_eq!(parse_length_prefixed(&mut reader)?.to_string(), "[1]");
+        assert_eq!(reader, b"garbage");
+        Ok(())
+    }
+}
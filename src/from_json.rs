@@ -0,0 +1,308 @@
+use crate::pointer::push_token;
+use crate::value::JsonValue;
+use anyhow::Result;
+use std::fmt;
+
+/// A value couldn't be converted by [`FromJson`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertError {
+    /// JSON Pointer to the offending value; empty for the root.
+    pub path: String,
+    pub message: String,
+}
+
+impl ConvertError {
+    /// An error for `value`, which is not the `expected` type.
+    pub fn mismatch(expected: &str, value: &JsonValue) -> Self {
+        ConvertError {
+            path: String::new(),
+            message: format!("Expected {expected}, found {}", value.type_name()),
+        }
+    }
+
+    /// Records that the error happened under `token`, a key or an index,
+    /// which encloses the path recorded so far.
+    fn within(mut self, token: &str) -> Self {
+        let mut path = String::new();
+        push_token(&mut path, token);
+        self.path.insert_str(0, &path);
+        self
+    }
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+/// Conversion from a parsed value into a Rust type, for reading typed
+/// configuration without a serialization framework.
+///
+/// Implementations for structs read their fields with
+/// [`JsonValue::field`], which records the key in any error:
+///
+/// ```
+/// use json_parser::{ConvertError, FromJson, JsonValue, parse_into};
+///
+/// #[derive(Debug)]
+/// struct Server {
+///     host: String,
+///     port: u64,
+///     tags: Vec<String>,
+///     timeout: Option<f64>,
+/// }
+///
+/// impl FromJson for Server {
+///     fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+///         Ok(Server {
+///             host: value.field("host")?,
+///             port: value.field("port")?,
+///             tags: value.field("tags")?,
+///             timeout: value.field("timeout")?,
+///         })
+///     }
+/// }
+///
+/// let server: Server = parse_into(r#"{"host": "a", "port": 80, "tags": ["x"]}"#).unwrap();
+/// assert_eq!((server.port, server.timeout), (80, None));
+/// let err = parse_into::<Server>(r#"{"host": "a", "port": 80, "tags": [1]}"#).unwrap_err();
+/// assert_eq!(err.to_string(), "/tags/0: Expected string, found number");
+/// ```
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, ConvertError>;
+
+    /// The value to use for a missing object member, if a member may be
+    /// missing at all. Only `Option` allows it.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+impl JsonValue {
+    /// Converts the member named `key` of an object, for use in
+    /// [`FromJson`] implementations. Errors carry the key in their path. A
+    /// missing member is an error unless `T` is an `Option`.
+    pub fn field<T: FromJson>(&self, key: &str) -> Result<T, ConvertError> {
+        let JsonValue::Object(_) = self else {
+            return Err(ConvertError::mismatch("object", self));
+        };
+        match self.get(key) {
+            Some(value) => T::from_json(value).map_err(|e| e.within(key)),
+            None => T::from_missing().ok_or_else(|| ConvertError {
+                path: String::new(),
+                message: format!("Missing field {key:?}"),
+            }),
+        }
+    }
+}
+
+/// Parses `input` and converts it to a `T`, e.g.
+/// `let config: Config = parse_into(&text)?;`.
+///
+/// Errors are an [`Error`](crate::Error) for malformed JSON or a
+/// [`ConvertError`] for a value of the wrong shape, either way saying
+/// where in the document it happened.
+pub fn parse_into<T: FromJson>(input: &str) -> Result<T> {
+    let value = crate::from_str(input)?;
+    Ok(T::from_json(&value)?)
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+        Ok(value.clone())
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+        value
+            .as_bool()
+            .ok_or_else(|| ConvertError::mismatch("bool", value))
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| ConvertError::mismatch("string", value))
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+        value
+            .as_f64()
+            .ok_or_else(|| ConvertError::mismatch("number", value))
+    }
+}
+
+/// Whole numbers only, as for [`Number::as_i64`](crate::Number::as_i64).
+impl FromJson for i64 {
+    fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+        integer(value, JsonValue::as_i64, "i64")
+    }
+}
+
+/// Whole numbers only, as for [`Number::as_u64`](crate::Number::as_u64).
+impl FromJson for u64 {
+    fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+        integer(value, JsonValue::as_u64, "u64")
+    }
+}
+
+fn integer<T>(
+    value: &JsonValue,
+    get: fn(&JsonValue) -> Option<T>,
+    name: &str,
+) -> Result<T, ConvertError> {
+    match value {
+        JsonValue::Number(n) => get(value).ok_or_else(|| ConvertError {
+            path: String::new(),
+            message: format!("Expected {name}, found {n}"),
+        }),
+        _ => Err(ConvertError::mismatch("number", value)),
+    }
+}
+
+/// `null` is `None`.
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            _ => T::from_json(value).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+        let JsonValue::Array(elements) = value else {
+            return Err(ConvertError::mismatch("array", value));
+        };
+        elements
+            .iter()
+            .enumerate()
+            .map(|(i, e)| T::from_json(e).map_err(|err| err.within(&i.to_string())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        name: String,
+        debug: bool,
+        servers: Vec<Server>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Server {
+        port: u64,
+        weight: Option<f64>,
+    }
+
+    impl FromJson for Config {
+        fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+            Ok(Config {
+                name: value.field("name")?,
+                debug: value.field("debug")?,
+                servers: value.field("servers")?,
+            })
+        }
+    }
+
+    impl FromJson for Server {
+        fn from_json(value: &JsonValue) -> Result<Self, ConvertError> {
+            Ok(Server {
+                port: value.field("port")?,
+                weight: value.field("weight")?,
+            })
+        }
+    }
+
+    fn convert_error(input: &str) -> ConvertError {
+        let err = parse_into::<Config>(input).unwrap_err();
+        err.downcast().expect("expected a conversion error")
+    }
+
+    #[test]
+    fn test_parse_into() -> anyhow::Result<()> {
+        let config: Config = parse_into(
+            r#"{"name": "api", "debug": false, "servers": [{"port": 80, "weight": 0.5}, {"port": 8080.0, "weight": null}, {"port": 1}]}"#,
+        )?;
+        assert_eq!(
+            config,
+            Config {
+                name: "api".to_string(),
+                debug: false,
+                servers: vec![
+                    Server {
+                        port: 80,
+                        weight: Some(0.5)
+                    },
+                    Server {
+                        port: 8080,
+                        weight: None
+                    },
+                    Server {
+                        port: 1,
+                        weight: None
+                    },
+                ],
+            }
+        );
+        assert_eq!(parse_into::<Vec<i64>>("[1, -2]")?, [1, -2]);
+        assert_eq!(parse_into::<JsonValue>("[1]")?, crate::from_str("[1]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_into_errors() {
+        let err = convert_error(
+            r#"{"name": "api", "debug": false, "servers": [{"port": 80}, {"port": "81"}]}"#,
+        );
+        assert_eq!(err.path, "/servers/1/port");
+        assert_eq!(
+            err.to_string(),
+            "/servers/1/port: Expected number, found string"
+        );
+
+        let err = convert_error(r#"{"name": "api", "debug": false, "servers": [{"port": -1}]}"#);
+        assert_eq!(err.to_string(), "/servers/0/port: Expected u64, found -1");
+        let err = convert_error(r#"{"name": "api", "servers": []}"#);
+        assert_eq!(err.to_string(), r#"/: Missing field "debug""#);
+        let err = convert_error("[]");
+        assert_eq!(err.to_string(), "/: Expected object, found array");
+        let err = convert_error(r#"{"name": "api", "debug": 1, "servers": []}"#);
+        assert_eq!(err.path, "/debug");
+
+        let err = parse_into::<Config>(r#"{"name": "api", "servers": [{"port": }]}"#).unwrap_err();
+        let err: Error = err.downcast().expect("expected a parse error");
+        assert_eq!(
+            err.context(),
+            [
+                r#"object value for key "servers""#,
+                "array element 0",
+                r#"object value for key "port""#
+            ]
+        );
+    }
+}
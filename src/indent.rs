@@ -0,0 +1,197 @@
+use crate::error::LexError;
+use crate::ser::IndentStyle;
+use crate::tokenizer::{Token::*, Tokenizer};
+use std::collections::HashMap;
+use std::fmt;
+
+/// How a document is indented, from [`analyze_indentation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndentReport {
+    /// The indentation added per nesting level by most lines, or `None` if
+    /// no line is indented (e.g. compact JSON).
+    pub unit: Option<IndentStyle>,
+    /// The lines not indented by `unit` times their nesting depth, in
+    /// order. With no unit, that is every indented line.
+    pub deviations: Vec<IndentDeviation>,
+}
+
+/// A line whose indentation doesn't match the detected unit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndentDeviation {
+    /// 1-based.
+    pub line: usize,
+    /// Nesting depth of the line's first token; a closing bracket is at
+    /// the depth of its opening one.
+    pub depth: usize,
+    /// The line's leading whitespace.
+    pub found: String,
+    /// What it should be.
+    pub expected: String,
+}
+
+impl IndentDeviation {
+    /// Whether the line is indented with both tabs and spaces.
+    pub fn is_mixed(&self) -> bool {
+        self.found.contains('\t') && self.found.contains(' ')
+    }
+}
+
+fn describe(indent: &str) -> String {
+    let count = |c, name: &str| match indent.matches(c).count() {
+        0 => None,
+        1 => Some(format!("1 {name}")),
+        n => Some(format!("{n} {name}s")),
+    };
+    let parts: Vec<_> = [count('\t', "tab"), count(' ', "space")]
+        .into_iter()
+        .flatten()
+        .collect();
+    if parts.is_empty() {
+        "no indentation".to_string()
+    } else {
+        parts.join(" and ")
+    }
+}
+
+/// `line 3: expected 4 spaces, found 1 tab and 2 spaces`
+impl fmt::Display for IndentDeviation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: expected {}, found {}",
+            self.line,
+            describe(&self.expected),
+            describe(&self.found)
+        )
+    }
+}
+
+/// Reports the indentation style of `input`, e.g. for a linter enforcing a
+/// consistent one: the unit most lines use, and the lines that don't use it.
+///
+/// Only lines starting with a token count; lines inside a comment and
+/// blank lines are skipped. A line's nesting depth is that of its first
+/// token, and the unit is found by a vote among the lines indented with
+/// only spaces or only tabs. Comments are accepted; otherwise only the
+/// lexical syntax is checked, so this fails only where
+/// [`tokenize`](crate::tokenize) does.
+pub fn analyze_indentation(input: &str) -> Result<IndentReport, LexError> {
+    let mut tokenizer = Tokenizer::borrowed(input).allow_comments(true);
+    // The leading whitespace and depth of every line starting with a token.
+    let mut lines = Vec::new();
+    let mut depth = 0usize;
+    let mut line = 1;
+    let mut scanned = 0;
+    while let Some((token, span)) = tokenizer.next_spanned().transpose()? {
+        // Whitespace and comments since the previous token.
+        let gap = &input[scanned..span.start];
+        line += gap.matches('\n').count();
+        let indent = match gap.rfind('\n') {
+            Some(i) => Some(&gap[i + 1..]),
+            None => (scanned == 0).then_some(gap),
+        };
+        // Lines that start with a comment don't count.
+        let first_on_line = indent.filter(|s| s.bytes().all(|b| b == b' ' || b == b'\t'));
+        if matches!(token, EndArray | EndObject) {
+            depth = depth.saturating_sub(1);
+        }
+        if let Some(indent) = first_on_line {
+            lines.push((line, depth, indent));
+        }
+        if matches!(token, BeginArray | BeginObject) {
+            depth += 1;
+        }
+        line += input[span.start..span.end].matches('\n').count();
+        scanned = span.end;
+    }
+
+    let unit = detect_unit(&lines);
+    let expected = |depth: usize| match unit {
+        Some(IndentStyle::Spaces(n)) => " ".repeat(n * depth),
+        Some(IndentStyle::Tab) => "\t".repeat(depth),
+        None => String::new(),
+    };
+    let deviations = lines
+        .into_iter()
+        .filter(|&(_, depth, indent)| indent != expected(depth))
+        .map(|(line, depth, indent)| IndentDeviation {
+            line,
+            depth,
+            found: indent.to_string(),
+            expected: expected(depth),
+        })
+        .collect();
+    Ok(IndentReport { unit, deviations })
+}
+
+/// Each nested line indented with only tabs, or only spaces in a multiple
+/// of its depth, votes for the unit it implies. Ties go to the smaller
+/// width, and to spaces over tabs.
+fn detect_unit(lines: &[(usize, usize, &str)]) -> Option<IndentStyle> {
+    let mut votes: HashMap<usize, usize> = HashMap::new();
+    let mut tabs = 0;
+    for &(_, depth, indent) in lines {
+        if depth == 0 || indent.is_empty() {
+            continue;
+        }
+        if indent.bytes().all(|b| b == b'\t') {
+            if indent.len() == depth {
+                tabs += 1;
+            }
+        } else if indent.bytes().all(|b| b == b' ') && indent.len().is_multiple_of(depth) {
+            *votes.entry(indent.len() / depth).or_default() += 1;
+        }
+    }
+    let spaces = votes
+        .into_iter()
+        .max_by_key(|&(width, count)| (count, std::cmp::Reverse(width)));
+    match spaces {
+        Some((width, count)) if count >= tabs => Some(IndentStyle::Spaces(width)),
+        _ if tabs > 0 => Some(IndentStyle::Tab),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_analyze_indentation() -> anyhow::Result<()> {
+        let value = from_str(r#"{"a": [1, {"b": []}], "c": {}}"#)?;
+        let report = analyze_indentation(&value.to_string_pretty())?;
+        assert_eq!(report.unit, Some(IndentStyle::Spaces(2)));
+        assert!(report.deviations.is_empty());
+        assert_eq!(
+            analyze_indentation(&value.to_string())?,
+            IndentReport {
+                unit: None,
+                deviations: Vec::new()
+            }
+        );
+
+        let input = "{\n\t\"a\": [\n\t\t1,\n\t  2,\n    3\n\t],\n\t// note\n\t\"b\": 0\n  }";
+        let report = analyze_indentation(input)?;
+        assert_eq!(report.unit, Some(IndentStyle::Tab));
+        let messages: Vec<_> = report.deviations.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "line 4: expected 2 tabs, found 1 tab and 2 spaces",
+                "line 5: expected 2 tabs, found 4 spaces",
+                "line 9: expected no indentation, found 2 spaces",
+            ]
+        );
+        assert!(report.deviations[0].is_mixed());
+        assert!(!report.deviations[1].is_mixed());
+        assert_eq!(report.deviations[1].depth, 2);
+
+        let report = analyze_indentation("[\n    1,\n    [\n        2\n    ],\n  3\n]")?;
+        assert_eq!(report.unit, Some(IndentStyle::Spaces(4)));
+        assert_eq!(report.deviations.len(), 1);
+        assert_eq!(report.deviations[0].line, 6);
+        assert!(analyze_indentation("[1, \"open").is_err());
+        Ok(())
+    }
+}
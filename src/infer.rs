@@ -0,0 +1,151 @@
+use crate::pointer::push_token;
+use crate::value::JsonValue;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// What was observed at one path of an [`InferredSchema`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathInfo {
+    /// How many values were seen at the path.
+    pub count: usize,
+    /// How often each type was seen, keyed by [`JsonValue::type_name`].
+    pub types: BTreeMap<&'static str, usize>,
+    /// Whether some object at the parent path lacked this member. Always
+    /// `false` for the root and for array elements.
+    pub optional: bool,
+    /// Whether the path's last segment is `*`, standing for any element of
+    /// an array, rather than a member named `*`.
+    pub element: bool,
+}
+
+/// A rough schema inferred from sample records by [`infer_schema`].
+///
+/// Paths are JSON Pointers, except that `*` stands for every element of an
+/// array: `/users/*/name` is the `name` of any user. `Display` gives a
+/// summary with one line per path, in path order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InferredSchema {
+    records: usize,
+    paths: BTreeMap<String, PathInfo>,
+}
+
+impl InferredSchema {
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    pub fn get(&self, path: &str) -> Option<&PathInfo> {
+        self.paths.get(path)
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = (&str, &PathInfo)> {
+        self.paths.iter().map(|(path, info)| (path.as_str(), info))
+    }
+}
+
+/// Merges the shape of every record, e.g. the lines of an NDJSON file, into
+/// one schema: the paths seen, the types found at each, and which object
+/// members are missing from some records.
+pub fn infer_schema(values: impl IntoIterator<Item = JsonValue>) -> InferredSchema {
+    let mut schema = InferredSchema::default();
+    for value in values {
+        schema.records += 1;
+        let mut stack = vec![(&value, String::new(), false)];
+        while let Some((node, path, element)) = stack.pop() {
+            match node {
+                JsonValue::Array(elements) => {
+                    let child = format!("{path}/*");
+                    stack.extend(elements.iter().map(|e| (e, child.clone(), true)));
+                }
+                JsonValue::Object(members) => {
+                    for (key, member) in members {
+                        let mut child = path.clone();
+                        push_token(&mut child, key);
+                        stack.push((member, child, false));
+                    }
+                }
+                _ => {}
+            }
+            let info = schema.paths.entry(path).or_default();
+            info.count += 1;
+            info.element = element;
+            *info.types.entry(node.type_name()).or_default() += 1;
+        }
+    }
+
+    let optional: Vec<String> = schema
+        .paths
+        .iter()
+        .filter(|(path, info)| {
+            let parent = &path[..path.rfind('/').unwrap_or(0)];
+            let parent_objects = schema
+                .paths
+                .get(parent)
+                .and_then(|p| p.types.get("object"))
+                .copied();
+            !path.is_empty() && !info.element && parent_objects.is_some_and(|n| info.count < n)
+        })
+        .map(|(path, _)| path.clone())
+        .collect();
+    for path in optional {
+        schema.paths.get_mut(&path).unwrap().optional = true;
+    }
+    schema
+}
+
+impl fmt::Display for InferredSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} records", self.records)?;
+        for (path, info) in &self.paths {
+            let types: Vec<_> = info
+                .types
+                .iter()
+                .map(|(name, n)| format!("{name} ({n})"))
+                .collect();
+            let path = if path.is_empty() { "/" } else { path };
+            write!(f, "{path}: {}", types.join(" | "))?;
+            if info.optional {
+                f.write_str(", optional")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_infer_schema() -> anyhow::Result<()> {
+        let records = [
+            r#"{"id": 1, "name": "a", "tags": ["x", "y"]}"#,
+            r#"{"id": 2, "tags": [], "extra": {"k": null}}"#,
+            r#"{"id": 3.5, "name": null, "tags": [1]}"#,
+        ]
+        .into_iter()
+        .map(from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+        let schema = infer_schema(records);
+        assert_eq!(schema.records(), 3);
+        assert!(!schema.get("/id").unwrap().optional);
+        assert!(schema.get("/name").unwrap().optional);
+        assert!(!schema.get("/extra/k").unwrap().optional);
+        assert_eq!(schema.get("/tags/*").unwrap().count, 3);
+        assert_eq!(
+            schema.to_string(),
+            "3 records
+/: object (3)
+/extra: object (1), optional
+/extra/k: null (1)
+/id: number (3)
+/name: null (1) | string (1), optional
+/tags: array (3)
+/tags/*: number (1) | string (2)
+"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+use crate::error::Error;
+use crate::number::Number;
+use crate::options::ParserOptions;
+use crate::parser::{Parser, Sink};
+use crate::tokenizer::Tokenizer;
+use crate::value::JsonValue;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// A parsed document whose strings are reference counted, so that equal
+/// strings can share one allocation. Produced by [`Interner::parse`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedValue {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Rc<str>),
+    Array(Vec<SharedValue>),
+    Object(Vec<(Rc<str>, SharedValue)>),
+}
+
+impl SharedValue {
+    /// Looks up `key` in an object. `None` for missing keys and non-objects.
+    pub fn get(&self, key: &str) -> Option<&SharedValue> {
+        match self {
+            SharedValue::Object(members) => {
+                members.iter().find(|(k, _)| &**k == key).map(|(_, v)| v)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SharedValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Copies the value into an owned [`JsonValue`].
+    pub fn to_value(&self) -> JsonValue {
+        match self {
+            SharedValue::Null => JsonValue::Null,
+            SharedValue::Bool(b) => JsonValue::Bool(*b),
+            SharedValue::Number(n) => JsonValue::Number(n.clone()),
+            SharedValue::String(s) => JsonValue::String(s.to_string()),
+            SharedValue::Array(elements) => {
+                JsonValue::Array(elements.iter().map(SharedValue::to_value).collect())
+            }
+            SharedValue::Object(members) => JsonValue::Object(
+                members
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Parses documents into [`SharedValue`]s, deduplicating strings through a
+/// pool that lasts until [`clear`](Self::clear), so it can span many
+/// records.
+///
+/// Object keys are interned by default; string values only with
+/// [`intern_values`](Self::intern_values), because their cardinality varies
+/// far more.
+///
+/// Strings are interned as the parser decodes them, so a string already in
+/// the pool is never allocated again, and no owned copy of the document is
+/// built along the way. Interning costs a hash and a lookup for every
+/// pooled string, plus a copy into the pool the first time a string is
+/// seen. It pays off when the same strings repeat — keys, enum-like values
+/// such as `"status": "active"` — and only adds overhead for unique ones
+/// like IDs.
+#[derive(Debug)]
+pub struct Interner {
+    options: ParserOptions,
+    pool: HashSet<Rc<str>>,
+    keys: bool,
+    values: bool,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Interner {
+            options: ParserOptions::default(),
+            pool: HashSet::new(),
+            keys: true,
+            values: false,
+        }
+    }
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: ParserOptions) -> Self {
+        Interner {
+            options,
+            ..Self::default()
+        }
+    }
+
+    pub fn intern_keys(mut self, intern: bool) -> Self {
+        self.keys = intern;
+        self
+    }
+
+    pub fn intern_values(mut self, intern: bool) -> Self {
+        self.values = intern;
+        self
+    }
+
+    /// Parses a complete JSON document, like
+    /// [`from_str_with`](crate::from_str_with) with this interner's options.
+    pub fn parse(&mut self, input: &str) -> Result<SharedValue, Error> {
+        let mut tokenizer = Tokenizer::borrowed(input).configured(&self.options);
+        let tokens = std::iter::from_fn(|| tokenizer.next_spanned());
+        let sink = Pooling {
+            pool: &mut self.pool,
+            keys: self.keys,
+            values: self.values,
+        };
+        Parser::with_sink(input, tokens, &self.options, sink).parse()
+    }
+
+    /// Distinct strings in the pool.
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_empty()
+    }
+
+    /// Total length of the pooled strings, in bytes.
+    pub fn pool_bytes(&self) -> usize {
+        self.pool.iter().map(|s| s.len()).sum()
+    }
+
+    /// Empties the pool. Values already returned keep their strings.
+    pub fn clear(&mut self) {
+        self.pool.clear();
+    }
+}
+
+/// Builds [`SharedValue`]s for [`Interner::parse`], taking strings from and
+/// adding them to its pool.
+#[derive(Debug)]
+struct Pooling<'p> {
+    pool: &'p mut HashSet<Rc<str>>,
+    keys: bool,
+    values: bool,
+}
+
+impl Pooling<'_> {
+    fn intern(&mut self, text: &str, pooled: bool) -> Rc<str> {
+        if !pooled {
+            return text.into();
+        }
+        if let Some(shared) = self.pool.get(text) {
+            return shared.clone();
+        }
+        let shared: Rc<str> = text.into();
+        self.pool.insert(shared.clone());
+        shared
+    }
+}
+
+impl Sink for Pooling<'_> {
+    type Value = SharedValue;
+    type Key = Rc<str>;
+
+    fn null(&mut self) -> SharedValue {
+        SharedValue::Null
+    }
+
+    fn bool(&mut self, b: bool) -> SharedValue {
+        SharedValue::Bool(b)
+    }
+
+    fn number(&mut self, n: Number) -> SharedValue {
+        SharedValue::Number(n)
+    }
+
+    fn string(&mut self, text: &str) -> SharedValue {
+        SharedValue::String(self.intern(text, self.values))
+    }
+
+    fn key(&mut self, text: &str) -> Rc<str> {
+        self.intern(text, self.keys)
+    }
+
+    fn array(&mut self, elements: impl ExactSizeIterator<Item = SharedValue>) -> SharedValue {
+        SharedValue::Array(elements.collect())
+    }
+
+    fn object(
+        &mut self,
+        members: impl ExactSizeIterator<Item = (Rc<str>, SharedValue)>,
+    ) -> SharedValue {
+        SharedValue::Object(members.collect())
+    }
+
+    fn combine(&mut self, existing: &mut SharedValue, value: SharedValue, first: bool) {
+        if first {
+            *existing = SharedValue::Array(vec![std::mem::replace(existing, SharedValue::Null)]);
+        }
+        if let SharedValue::Array(values) = existing {
+            values.push(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() -> anyhow::Result<()> {
+        let input = r#"[{"status": "active", "id": "a"}, {"status": "active", "id": "b"}]"#;
+        let mut interner = Interner::new();
+        let keys_only = interner.parse(input)?;
+        assert_eq!(interner.len(), 2);
+        assert_eq!(keys_only.to_value(), crate::from_str(input)?);
+
+        let mut interner = Interner::new().intern_values(true);
+        let value = interner.parse(input)?;
+        let SharedValue::Array(records) = &value else {
+            panic!("expected an array");
+        };
+        let status = |i: usize| match records[i].get("status") {
+            Some(SharedValue::String(s)) => s.clone(),
+            _ => panic!("expected a string"),
+        };
+        assert!(Rc::ptr_eq(&status(0), &status(1)));
+        assert_eq!(
+            records[1].get("id").and_then(SharedValue::as_str),
+            Some("b")
+        );
+        // "status", "id", "active", "a", "b"
+        assert_eq!(interner.len(), 5);
+        assert_eq!(interner.pool_bytes(), 16);
+
+        // The pool carries over to the next document.
+        interner.parse(r#"{"status": "active"}"#)?;
+        assert_eq!(interner.len(), 5);
+        interner.clear();
+        assert!(interner.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_intern_while_parsing() -> anyhow::Result<()> {
+        // Strings are pooled once decoded, so escapes don't matter.
+        let input = r#"[{"k": "\u0061b"}, {"\u006b": "ab"}, {"k": 1, "k": 2}]"#;
+        let options = ParserOptions {
+            duplicate_keys: crate::DuplicateKeys::Combine,
+            ..Default::default()
+        };
+        let mut interner = Interner::with_options(options.clone()).intern_values(true);
+        let value = interner.parse(input)?;
+        assert_eq!(value.to_value(), crate::from_str_with(input, &options)?);
+        // "k", "ab"
+        assert_eq!(interner.len(), 2);
+
+        let SharedValue::Array(records) = &value else {
+            panic!("expected an array");
+        };
+        let key = |i: usize| match &records[i] {
+            SharedValue::Object(members) => members[0].0.clone(),
+            _ => panic!("expected an object"),
+        };
+        assert!(Rc::ptr_eq(&key(0), &key(1)));
+        assert!(interner.parse(r#"{"a": [1,"#).is_err());
+        Ok(())
+    }
+}
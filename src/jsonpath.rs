@@ -0,0 +1,344 @@
+use crate::value::JsonValue;
+use anyhow::{Result, bail};
+
+/// What one step of a path picks out of each node it is applied to.
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    /// The value of the member with this key.
+    Name(String),
+    /// Every element or member value.
+    Wildcard,
+    /// One array element; negative indices count from the end.
+    Index(i64),
+    /// Array elements from `start` up to `end` by `step`, as in Python.
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: i64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    /// Written with `..`: applies to the node and all of its descendants.
+    descendants: bool,
+    selector: Selector,
+}
+
+/// Evaluates a JSONPath query against `value`, returning every matching
+/// node in document order.
+///
+/// Supported is the core of the syntax: the root `$`, children `.name` or
+/// `['name']`, recursive descent `..name` (and `..*`, `..[0]`), the
+/// wildcard `*` or `[*]`, array indices `[0]` and `[-1]`, and slices
+/// `[start:end:step]`. Filters (`[?(@.price < 10)]`), script expressions
+/// and unions (`[0,1]`) are not supported and fail to parse, as does any
+/// path not starting at `$`.
+///
+/// Unlike [`JsonValue::pointer`], a path can match any number of nodes;
+/// one matching nothing is not an error.
+///
+/// ```
+/// use json_parser::{from_str, jsonpath};
+///
+/// let store = from_str(r#"{"book": [{"price": 8}, {"price": 12}], "bike": {"price": 20}}"#).unwrap();
+/// let prices: Vec<_> = jsonpath(&store, "$..price").unwrap().into_iter().filter_map(|v| v.as_i64()).collect();
+/// assert_eq!(prices, [8, 12, 20]);
+/// ```
+pub fn jsonpath<'a>(value: &'a JsonValue, path: &str) -> Result<Vec<&'a JsonValue>> {
+    let steps = parse(path)?;
+    let mut nodes = vec![value];
+    for step in &steps {
+        if step.descendants {
+            nodes = nodes.into_iter().flat_map(descendants).collect();
+        }
+        let mut selected = Vec::new();
+        for node in nodes {
+            select(node, &step.selector, &mut selected);
+        }
+        nodes = selected;
+    }
+    Ok(nodes)
+}
+
+/// `node` and everything below it, in pre-order and document order.
+fn descendants(node: &JsonValue) -> Vec<&JsonValue> {
+    let mut found = Vec::new();
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        found.push(node);
+        match node {
+            JsonValue::Array(elements) => stack.extend(elements.iter().rev()),
+            JsonValue::Object(members) => stack.extend(members.iter().rev().map(|(_, v)| v)),
+            _ => {}
+        }
+    }
+    found
+}
+
+fn select<'a>(node: &'a JsonValue, selector: &Selector, out: &mut Vec<&'a JsonValue>) {
+    match (selector, node) {
+        (Selector::Name(name), JsonValue::Object(members)) => {
+            out.extend(members.iter().filter(|(k, _)| k == name).map(|(_, v)| v))
+        }
+        (Selector::Wildcard, JsonValue::Array(elements)) => out.extend(elements),
+        (Selector::Wildcard, JsonValue::Object(members)) => {
+            out.extend(members.iter().map(|(_, v)| v))
+        }
+        (&Selector::Index(i), JsonValue::Array(elements)) => {
+            let len = elements.len() as i64;
+            let i = if i < 0 { len + i } else { i };
+            if (0..len).contains(&i) {
+                out.push(&elements[i as usize]);
+            }
+        }
+        (&Selector::Slice { start, end, step }, JsonValue::Array(elements)) => {
+            let len = elements.len() as i64;
+            let bound = |i: i64, lo, hi| if i < 0 { len + i } else { i }.clamp(lo, hi);
+            if step > 0 {
+                let start = start.map_or(0, |i| bound(i, 0, len));
+                let end = end.map_or(len, |i| bound(i, 0, len));
+                let mut i = start;
+                while i < end {
+                    out.push(&elements[i as usize]);
+                    let Some(next) = i.checked_add(step) else {
+                        break;
+                    };
+                    i = next;
+                }
+            } else if step < 0 {
+                let start = start.map_or(len - 1, |i| bound(i, -1, len - 1));
+                let end = end.map_or(-1, |i| bound(i, -1, len - 1));
+                let mut i = start;
+                while i > end {
+                    out.push(&elements[i as usize]);
+                    let Some(next) = i.checked_add(step) else {
+                        break;
+                    };
+                    i = next;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn parse(path: &str) -> Result<Vec<Step>> {
+    let Some(mut rest) = path.strip_prefix('$') else {
+        bail!("JSONPath {path:?} must start with `$`");
+    };
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        let offset = path.len() - rest.len();
+        let descendants = rest.starts_with("..");
+        if descendants {
+            rest = &rest[2..];
+        } else if rest.starts_with('.') {
+            rest = &rest[1..];
+        } else if !rest.starts_with('[') {
+            bail!("Expected `.` or `[` at offset {offset} of JSONPath {path:?}");
+        }
+
+        let selector = if let Some(bracketed) = rest.strip_prefix('[') {
+            let (selector, after) = parse_bracketed(bracketed)
+                .map_err(|e| anyhow::anyhow!("{e} at offset {offset} of JSONPath {path:?}"))?;
+            rest = after;
+            selector
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let name = &rest[..end];
+            rest = &rest[end..];
+            match name {
+                "" => bail!("Expected a name at offset {offset} of JSONPath {path:?}"),
+                "*" => Selector::Wildcard,
+                _ => Selector::Name(name.to_string()),
+            }
+        };
+        steps.push(Step {
+            descendants,
+            selector,
+        });
+    }
+    Ok(steps)
+}
+
+/// Parses the inside of `[...]`, returning the selector and what follows
+/// the `]`.
+fn parse_bracketed(s: &str) -> Result<(Selector, &str)> {
+    let s = s.trim_start();
+    if let Some(quote) = s.chars().next().filter(|c| matches!(c, '\'' | '"')) {
+        let mut name = String::new();
+        let mut chars = s[1..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, c)) => name.push(c),
+                    None => break,
+                },
+                c if c == quote => {
+                    let after = s[1 + i + 1..].trim_start();
+                    let Some(after) = after.strip_prefix(']') else {
+                        bail!("Expected `]` after quoted name");
+                    };
+                    return Ok((Selector::Name(name), after));
+                }
+                c => name.push(c),
+            }
+        }
+        bail!("Unterminated quoted name");
+    }
+
+    let Some(end) = s.find(']') else {
+        bail!("Unclosed `[`");
+    };
+    let (inside, after) = (s[..end].trim(), &s[end + 1..]);
+    if inside.starts_with('?') {
+        bail!("Filter expressions are not supported");
+    }
+    if inside.starts_with('(') {
+        bail!("Script expressions are not supported");
+    }
+    if inside.contains(',') {
+        bail!("Unions are not supported");
+    }
+    if inside == "*" {
+        return Ok((Selector::Wildcard, after));
+    }
+
+    let integer = |part: &str| -> Result<Option<i64>> {
+        let part = part.trim();
+        if part.is_empty() {
+            return Ok(None);
+        }
+        match part.parse() {
+            Ok(i) => Ok(Some(i)),
+            Err(_) => bail!("Invalid array index {part:?}"),
+        }
+    };
+    let parts: Vec<_> = inside.split(':').collect();
+    let selector = match parts[..] {
+        [index] => match integer(index)? {
+            Some(i) => Selector::Index(i),
+            None => bail!("Empty brackets"),
+        },
+        [start, end] => Selector::Slice {
+            start: integer(start)?,
+            end: integer(end)?,
+            step: 1,
+        },
+        [start, end, step] => Selector::Slice {
+            start: integer(start)?,
+            end: integer(end)?,
+            step: integer(step)?.unwrap_or(1),
+        },
+        _ => bail!("Too many `:` in slice"),
+    };
+    Ok((selector, after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    const STORE: &str = r#"{"store": {
+        "book": [
+            {"author": "Rees", "price": 8.95},
+            {"author": "Waugh", "price": 12.99},
+            {"author": "Melville", "price": 8.99, "isbn": "0-553"},
+            {"author": "Tolkien", "price": 22.99}
+        ],
+        "bicycle": {"color": "red", "price": 19.95}
+    }}"#;
+
+    fn query(value: &JsonValue, path: &str) -> Vec<String> {
+        jsonpath(value, path)
+            .unwrap()
+            .into_iter()
+            .map(JsonValue::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_children_and_wildcards() -> anyhow::Result<()> {
+        let store = from_str(STORE)?;
+        assert_eq!(
+            query(&store, "$.store.book[*].author"),
+            [r#""Rees""#, r#""Waugh""#, r#""Melville""#, r#""Tolkien""#]
+        );
+        assert_eq!(query(&store, "$['store']['bicycle'].color"), [r#""red""#]);
+        assert_eq!(query(&store, "$.store.bicycle.*"), [r#""red""#, "19.95"]);
+        assert_eq!(query(&store, "$"), [store.to_string()]);
+        assert_eq!(query(&from_str("[1, {}]")?, "$.*"), ["1", "{}"]);
+        assert!(query(&store, "$.store.missing.x").is_empty());
+        assert!(query(&store, "$.store.book.author").is_empty());
+        let odd = from_str(r#"{"a.b": 1, "it's": 2}"#)?;
+        assert_eq!(query(&odd, r#"$["a.b"]"#), ["1"]);
+        assert_eq!(query(&odd, r"$['it\'s']"), ["2"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_recursive_descent() -> anyhow::Result<()> {
+        let store = from_str(STORE)?;
+        assert_eq!(
+            query(&store, "$..price"),
+            ["8.95", "12.99", "8.99", "22.99", "19.95"]
+        );
+        assert_eq!(query(&store, "$.store..isbn"), [r#""0-553""#]);
+        assert_eq!(query(&store, "$..book[2].author"), [r#""Melville""#]);
+        let nested = from_str(r#"{"a": {"a": 1}, "b": [{"a": 2}]}"#)?;
+        assert_eq!(query(&nested, "$..a"), [r#"{"a":1}"#, "1", "2"]);
+        assert_eq!(query(&nested, "$..[0]"), [r#"{"a":2}"#]);
+        assert_eq!(query(&nested, "$..*").len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_indices_and_slices() -> anyhow::Result<()> {
+        let array = from_str("[0, 1, 2, 3, 4]")?;
+        assert_eq!(query(&array, "$[0]"), ["0"]);
+        assert_eq!(query(&array, "$[-1]"), ["4"]);
+        assert!(query(&array, "$[5]").is_empty());
+        assert!(query(&array, "$[-6]").is_empty());
+        assert_eq!(query(&array, "$[1:3]"), ["1", "2"]);
+        assert_eq!(query(&array, "$[:2]"), ["0", "1"]);
+        assert_eq!(query(&array, "$[-2:]"), ["3", "4"]);
+        assert_eq!(query(&array, "$[::2]"), ["0", "2", "4"]);
+        assert_eq!(query(&array, "$[::-1]"), ["4", "3", "2", "1", "0"]);
+        assert_eq!(query(&array, "$[3:0:-2]"), ["3", "1"]);
+        assert!(query(&array, "$[::0]").is_empty());
+        assert!(query(&array, "$[10:20]").is_empty());
+        let three = from_str("[0, 1, 2]")?;
+        assert_eq!(query(&three, "$[1::9223372036854775807]"), ["1"]);
+        assert_eq!(query(&three, "$[1::-9223372036854775808]"), ["1"]);
+        assert!(query(&from_str(r#"{"0": 1}"#)?, "$[0]").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let value = JsonValue::Null;
+        for (path, message) in [
+            ("store", "must start with `$`"),
+            (
+                "$.book[?(@.price < 10)]",
+                "Filter expressions are not supported",
+            ),
+            (
+                "$.book[(@.length-1)]",
+                "Script expressions are not supported",
+            ),
+            ("$[0,1]", "Unions are not supported"),
+            ("$.", "Expected a name"),
+            ("$[x]", "Invalid array index"),
+            ("$[0", "Unclosed `[`"),
+            ("$['a", "Unterminated quoted name"),
+            ("$['a' x]", "Expected `]` after quoted name"),
+            ("$a", "Expected `.` or `[`"),
+        ] {
+            let err = jsonpath(&value, path).unwrap_err().to_string();
+            assert!(err.contains(message), "{path}: {err}");
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::error::{Error, ParseError, Position};
+use crate::tokenizer::{Span, Token::*, Tokenizer};
+use crate::value::JsonValue;
+
+/// A top-level array whose elements are parsed only when accessed.
+///
+/// Building the index costs one tokenizer pass over the whole input, which
+/// checks that every token is well formed and the brackets balance, plus
+/// one `Span` (two `usize`s) per element. Each element's grammar is checked
+/// when it is parsed by [`get`](Self::get), so a malformed element errors
+/// there rather than up front.
+pub struct LazyArray<'a> {
+    input: &'a str,
+    elements: Vec<Span>,
+}
+
+impl<'a> LazyArray<'a> {
+    pub fn parse(input: &'a str) -> Result<Self, Error> {
+        let error = |offset, message: &str| {
+            Error::Parse(ParseError::new(message, Position::locate(input, offset)))
+        };
+
+        let mut tokenizer = Tokenizer::borrowed(input);
+        match tokenizer.next_spanned().transpose()? {
+            Some((BeginArray, _)) => {}
+            Some((_, span)) => return Err(error(span.start, "Expecting an array at root")),
+            None => return Err(error(input.len(), "Expecting an array at root")),
+        }
+
+        let mut elements = Vec::new();
+        let mut depth = 0;
+        // Span of the element being scanned, if it has any tokens yet.
+        let mut current: Option<Span> = None;
+        loop {
+            let Some((token, span)) = tokenizer.next_spanned().transpose()? else {
+                return Err(error(input.len(), "Unterminated array"));
+            };
+            match token {
+                ValueSeparator | EndArray if depth == 0 => {
+                    match current.take() {
+                        Some(element) => elements.push(element),
+                        None if token == EndArray && elements.is_empty() => {}
+                        None => return Err(error(span.start, "Expecting an array element")),
+                    }
+                    if token == EndArray {
+                        break;
+                    }
+                    continue;
+                }
+                BeginArray | BeginObject => depth += 1,
+                EndArray | EndObject => depth -= 1,
+                _ => {}
+            }
+            let start = current.map_or(span.start, |c| c.start);
+            current = Some(Span {
+                start,
+                end: span.end,
+            });
+        }
+        if let Some((_, span)) = tokenizer.next_spanned().transpose()? {
+            return Err(error(span.start, "Expecting end of input"));
+        }
+        Ok(LazyArray { input, elements })
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Source text of element `i`.
+    pub fn raw(&self, i: usize) -> Option<&'a str> {
+        let span = self.elements.get(i)?;
+        Some(&self.input[span.start..span.end])
+    }
+
+    /// Parses element `i`. `None` if out of bounds. Error positions are
+    /// relative to the element's text.
+    pub fn get(&self, i: usize) -> Option<Result<JsonValue, Error>> {
+        self.raw(i).map(crate::from_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lazy_array() -> anyhow::Result<()> {
+        let array = LazyArray::parse(r#"[1, {"a": [2, 3]}, "x,y" , [] ]"#)?;
+        assert_eq!(array.len(), 4);
+        assert_eq!(array.raw(1), Some(r#"{"a": [2, 3]}"#));
+        assert_eq!(array.get(2).unwrap()?, JsonValue::String("x,y".to_string()));
+        assert_eq!(array.get(3).unwrap()?, JsonValue::empty_array());
+        assert!(array.get(4).is_none());
+
+        assert!(LazyArray::parse("[]")?.is_empty());
+        assert!(LazyArray::parse("[1,,2]").is_err());
+        assert!(LazyArray::parse("[1, 2").is_err());
+        assert!(LazyArray::parse(r#"{"a": 1}"#).is_err());
+        // Malformed elements are only noticed when parsed.
+        let array = LazyArray::parse("[1, [2 3]]")?;
+        assert!(array.get(1).unwrap().is_err());
+        Ok(())
+    }
+}
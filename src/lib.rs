@@ -0,0 +1,446 @@
+#[cfg(feature = "arena")]
+mod arena;
+mod backing;
+#[cfg(feature = "base64")]
+mod base64;
+mod binary;
+mod coerce;
+mod count;
+#[cfg(feature = "rfc3339")]
+mod datetime;
+mod diff;
+#[cfg(all(test, feature = "differential"))]
+mod differential;
+mod document;
+mod encoding;
+mod entry;
+mod error;
+mod expect;
+mod framing;
+mod from_json;
+mod indent;
+mod infer;
+mod intern;
+mod jsonpath;
+mod lazy;
+mod macros;
+mod ndjson;
+mod number;
+mod options;
+mod parser;
+mod partial;
+mod patch;
+mod pointer;
+mod prettify;
+mod profile;
+mod prune;
+mod query;
+mod reload;
+mod report;
+mod reuse;
+mod schema;
+mod ser;
+mod shape;
+mod spans;
+mod tokenizer;
+mod transform;
+mod value;
+
+#[cfg(feature = "arena")]
+pub use arena::{Arena, ArenaValue, parse_in};
+pub use backing::{Hashed, ObjectBacking, Ordered, Sorted, from_str_backed};
+pub use count::{count_array_elements, count_object_members};
+#[cfg(feature = "rfc3339")]
+pub use datetime::DateTime;
+pub use diff::Change;
+pub use document::{Document, Edit};
+pub use encoding::{
+    Encoding, Endian, detect_encoding, from_bytes_autodetect, from_utf16, from_utf16_with,
+};
+pub use entry::Entry;
+pub use error::{DepthLimitExceeded, Error, LexError, ParseError, Position, SerializeError};
+pub use framing::{parse_length_prefixed, write_length_prefixed};
+pub use from_json::{ConvertError, FromJson, parse_into};
+pub use indent::{IndentDeviation, IndentReport, analyze_indentation};
+pub use infer::{InferredSchema, PathInfo, infer_schema};
+pub use intern::{Interner, SharedValue};
+pub use jsonpath::jsonpath;
+pub use lazy::LazyArray;
+pub use ndjson::write_ndjson;
+pub use number::Number;
+pub use options::{DuplicateKeys, ParserOptions, StringSanitizer};
+pub use partial::parse_partial;
+pub use pointer::Segment;
+pub use prettify::prettify_stream;
+pub use profile::{LeafInfo, LeafKind};
+pub use prune::PruneOptions;
+pub use reload::ReloadableConfig;
+pub use report::{ReportOptions, ValidationReport, Warning, WarningKind, validate_and_report};
+pub use reuse::JsonParser;
+pub use schema::{Schema, SchemaError, SchemaType};
+pub use ser::{IndentStyle, LineEnding, SerializeOptions, SerializeStyle};
+pub use shape::HomogeneityError;
+pub use spans::{SpanKind, SpanTree};
+pub use tokenizer::{CommentSpan, Span, Spanned, Token, Tokenizer};
+pub use transform::transform;
+pub use value::{JsonValue, ValueKind};
+
+use parser::Parser;
+use std::collections::HashMap;
+
+/// Parses a complete JSON document.
+pub fn from_str(input: &str) -> Result<JsonValue, Error> {
+    from_str_with(input, &ParserOptions::default())
+}
+
+/// Parses a complete JSON document, as configured by `options`.
+pub fn from_str_with(input: &str, options: &ParserOptions) -> Result<JsonValue, Error> {
+    let mut tokenizer = Tokenizer::borrowed(input).configured(options);
+    let tokens = std::iter::from_fn(|| tokenizer.next_spanned());
+    Parser::new(input, tokens, options).parse()
+}
+
+/// Parses a document whose root must be an object, returning its members.
+/// Duplicate keys are resolved the default way, keeping the last value.
+pub fn from_str_object(input: &str) -> Result<HashMap<String, JsonValue>, Error> {
+    match from_str(input)? {
+        JsonValue::Object(members) => Ok(members.into_iter().collect()),
+        other => Err(root_mismatch(input, "object", &other)),
+    }
+}
+
+/// Parses a document whose root must be an array, returning its elements.
+pub fn from_str_array(input: &str) -> Result<Vec<JsonValue>, Error> {
+    match from_str(input)? {
+        JsonValue::Array(elements) => Ok(elements),
+        other => Err(root_mismatch(input, "array", &other)),
+    }
+}
+
+fn root_mismatch(input: &str, expected: &str, found: &JsonValue) -> Error {
+    let offset = input.len() - input.trim_start().len();
+    Error::Parse(ParseError::new(
+        format!("Expected {expected} at root, found {}", found.type_name()),
+        Position::locate(input, offset),
+    ))
+}
+
+/// Parses a complete JSON document and reports where each of its nodes
+/// came from, e.g. for editor tooling.
+pub fn parse_with_spans(input: &str) -> Result<(JsonValue, SpanTree), Error> {
+    let mut tokenizer = Tokenizer::borrowed(input);
+    let tokens = std::iter::from_fn(|| tokenizer.next_spanned());
+    let options = ParserOptions::default();
+    Parser::new(input, tokens, &options)
+        .recording_spans()
+        .parse_with_spans()
+}
+
+/// Splits `input` into tokens with their spans, failing at the first
+/// malformed one. Only the lexical syntax is checked: `]]` tokenizes fine.
+pub fn tokenize(input: &str) -> Result<Vec<(Token, Span)>, LexError> {
+    let mut tokenizer = Tokenizer::borrowed(input);
+    std::iter::from_fn(|| tokenizer.next_spanned()).collect()
+}
+
+/// Parses a JSONC document, also returning where each comment was, e.g. for
+/// a linter. Comments are accepted whatever `options.allow_comments` says.
+pub fn parse_with_comments(
+    input: &str,
+    options: &ParserOptions,
+) -> Result<(JsonValue, Vec<CommentSpan>), Error> {
+    let mut tokenizer = Tokenizer::borrowed(input)
+        .configured(options)
+        .allow_comments(true)
+        .recording_comments();
+    let tokens = std::iter::from_fn(|| tokenizer.next_spanned());
+    let value = Parser::new(input, tokens, options).parse()?;
+    Ok((value, tokenizer.take_comments()))
+}
+
+/// Checks that `input` is a JSON document without building its value.
+pub fn validate(input: &str) -> Result<(), Error> {
+    validate_with(input, &ParserOptions::default())
+}
+
+/// Whether `input` is a JSON document, for callers that don't need to know
+/// what is wrong with it.
+///
+/// ```
+/// assert!(json_parser::is_valid(r#"{"a": [1, true, null]}"#));
+/// assert!(!json_parser::is_valid("[1, 2"));
+/// ```
+pub fn is_valid(input: &str) -> bool {
+    validate(input).is_ok()
+}
+
+/// Where `input` first stops being valid JSON, as a byte offset, or `None`
+/// if it is valid: the cheapest way to reject a malformed upload. Runs the
+/// validator, which builds no values and only decodes strings with escapes.
+pub fn first_error_offset(input: &str) -> Option<usize> {
+    validate(input).err().map(|e| e.position().offset)
+}
+
+/// Like [`validate`], as configured by `options`.
+pub fn validate_with(input: &str, options: &ParserOptions) -> Result<(), Error> {
+    let mut tokenizer = Tokenizer::borrowed(input).configured(options);
+    let tokens = std::iter::from_fn(|| tokenizer.next_spanned());
+    let mut parser: Parser<_> = Parser::new(input, tokens, options).validating();
+    parser.parse()?;
+    Ok(())
+}
+
+/// Parses a complete JSON document from UTF-8 bytes, borrowing them.
+pub fn from_slice(input: &[u8]) -> Result<JsonValue, Error> {
+    from_slice_with(input, &ParserOptions::default())
+}
+
+/// Like [`from_slice`], as configured by `options`.
+pub fn from_slice_with(input: &[u8], options: &ParserOptions) -> Result<JsonValue, Error> {
+    let text = std::str::from_utf8(input).map_err(|e| {
+        let valid = &input[..e.valid_up_to()];
+        // SAFETY: `from_utf8` just checked this prefix.
+        let valid = unsafe { std::str::from_utf8_unchecked(valid) };
+        Error::Lex(LexError::new(
+            "Invalid UTF-8",
+            Position::locate(valid, valid.len()),
+        ))
+    })?;
+    from_str_with(text, options)
+}
+
+/// Like [`from_slice`], without checking that `input` is UTF-8, for
+/// trusted input on hot paths. Text passed to [`from_str`] is never
+/// checked either, as a `&str` is UTF-8 already.
+///
+/// # Safety
+///
+/// `input` must be valid UTF-8. Nothing downstream checks it again, so
+/// invalid input is undefined behavior rather than an error.
+pub unsafe fn from_utf8_unchecked_slice(input: &[u8]) -> Result<JsonValue, Error> {
+    // SAFETY: the caller guarantees `input` is valid UTF-8.
+    let text = unsafe { std::str::from_utf8_unchecked(input) };
+    from_str(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds() {
+        let Err(Error::Lex(e)) = from_str("[1, nul]") else {
+            panic!("expected a lex error");
+        };
+        assert_eq!(e.position.offset, 4);
+
+        let Err(Error::Parse(e)) = from_str("{\"a\": 1,\n 2}") else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((e.position.line, e.position.column), (2, 2));
+    }
+
+    #[test]
+    fn test_fast_numbers() {
+        let fast = ParserOptions {
+            fast_numbers: true,
+            ..Default::default()
+        };
+        let input = "[1, -2.5e3, 0.25, 18446744073709551615]";
+        assert_eq!(from_str_with(input, &fast), from_str(input));
+        assert!(from_str("[1-2]").is_err());
+        assert!(matches!(from_str_with("[1-2]", &fast), Err(Error::Lex(_))));
+        // Validation defers checking the literal.
+        assert!(validate_with("[1-2]", &fast).is_ok());
+        assert!(validate("[1-2]").is_err());
+        assert!(validate_with("[1-2", &fast).is_err());
+    }
+
+    #[test]
+    fn test_leading_plus() {
+        let Err(Error::Lex(e)) = from_str("[1, +5]") else {
+            panic!("expected a lex error");
+        };
+        assert_eq!(e.message, "Numbers cannot start with `+`");
+        assert_eq!(e.position.offset, 4);
+        assert!(validate("+5").is_err());
+
+        for fast_numbers in [false, true] {
+            let lenient = ParserOptions {
+                allow_leading_plus: true,
+                fast_numbers,
+                ..Default::default()
+            };
+            let value = from_str_with("[+5, +0, +2.5e1, -1]", &lenient).unwrap();
+            assert_eq!(value.to_string(), "[5,0,25.0,-1]");
+            assert_eq!(value, from_str("[5, 0, 2.5e1, -1]").unwrap());
+            assert!(validate_with("+5", &lenient).is_ok());
+            assert!(from_str_with("[+-5]", &lenient).is_err());
+            assert!(from_str_with("[+]", &lenient).is_err());
+        }
+    }
+
+    #[test]
+    fn test_digit_separators() {
+        for input in ["1_000", "[1, 2_0]", "1.0_1", "1e1_0"] {
+            assert!(from_str(input).is_err(), "{input}");
+        }
+        for fast_numbers in [false, true] {
+            let options = ParserOptions {
+                allow_digit_separators: true,
+                fast_numbers,
+                ..Default::default()
+            };
+            let value = from_str_with("[1_000.5, -1_2, 1_0e1_0, 0.2_5, 1_2_3]", &options).unwrap();
+            assert_eq!(value, from_str("[1000.5, -12, 10e10, 0.25, 123]").unwrap());
+            let kept = ParserOptions {
+                keep_number_text: true,
+                ..options.clone()
+            };
+            assert_eq!(
+                from_str_with("1_000.50", &kept).unwrap().to_string(),
+                "1000.50"
+            );
+
+            for input in [
+                "_1", "1_", "1._5", "1_.5", "1_e5", "1e_5", "-_1", "1__0", "[1_, 2]",
+            ] {
+                assert!(from_str_with(input, &options).is_err(), "{input}");
+                assert!(validate_with(input, &options).is_err(), "{input}");
+            }
+        }
+        let options = ParserOptions {
+            allow_digit_separators: true,
+            ..Default::default()
+        };
+        let err = from_str_with("1_", &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lex error: Digit separators must be between two digits at line 1, column 2"
+        );
+    }
+
+    #[test]
+    fn test_empty_as_null() {
+        for input in ["", "  \n\t"] {
+            assert!(from_str(input).is_err());
+            assert!(validate(input).is_err());
+            let options = ParserOptions {
+                empty_as_null: true,
+                ..Default::default()
+            };
+            assert_eq!(from_str_with(input, &options).unwrap(), JsonValue::Null);
+            assert!(validate_with(input, &options).is_ok());
+            assert!(from_str_with(" ]", &options).is_err());
+        }
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let input = r#"{"a": [1, null]}"#;
+        let tokens = tokenize(input).unwrap();
+        let kinds: Vec<_> = tokens.iter().map(|(t, _)| *t).collect();
+        assert_eq!(
+            kinds,
+            [
+                Token::BeginObject,
+                Token::Stringy,
+                Token::NameSeparator,
+                Token::BeginArray,
+                Token::Number,
+                Token::ValueSeparator,
+                Token::Null,
+                Token::EndArray,
+                Token::EndObject,
+            ]
+        );
+        assert_eq!(tokens[4].1, Span { start: 7, end: 8 });
+        assert_eq!(tokenize("]],").unwrap().len(), 3);
+        assert!(tokenize("").unwrap().is_empty());
+        assert_eq!(tokenize("[1, nul]").unwrap_err().position.offset, 4);
+    }
+
+    #[test]
+    fn test_comments() -> anyhow::Result<()> {
+        let input = "{\n  // port to listen on\n  \"port\": 80, /* default */\n}";
+        let options = ParserOptions {
+            allow_comments: true,
+            ..Default::default()
+        };
+        assert!(from_str(input).is_err());
+        assert!(validate_with(input, &options).is_err());
+        let input = input.replace(", /*", " /*");
+        assert_eq!(
+            from_str_with(&input, &options)?.to_string(),
+            r#"{"port":80}"#
+        );
+        let (value, comments) = parse_with_comments(&input, &ParserOptions::default())?;
+        assert_eq!(value.pointer_i64("/port"), Some(80));
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].text, "// port to listen on");
+        assert_eq!(comments[1].span, Span { start: 38, end: 51 });
+        assert_eq!(&input[38..51], "/* default */");
+        Ok(())
+    }
+
+    #[test]
+    fn test_first_error_offset() {
+        assert_eq!(first_error_offset(r#"{"a": [1, "b\n"]}"#), None);
+        // Missing comma, unclosed brace, bad number, bad escape.
+        assert_eq!(first_error_offset("[1, 2 3]"), Some(6));
+        assert_eq!(first_error_offset(r#"{"a": {"b": 1}"#), Some(14));
+        assert_eq!(first_error_offset("[1, 2.x]"), Some(5));
+        assert_eq!(first_error_offset(r#"["ok", "\q"]"#), Some(7));
+    }
+
+    #[test]
+    fn test_from_slice() {
+        assert_eq!(
+            from_slice(b"[\"\xc3\xa9\"]").unwrap().to_string(),
+            "[\"\u{e9}\"]"
+        );
+        let Err(Error::Lex(e)) = from_slice(b"[\"\xff\"]") else {
+            panic!("expected a lex error");
+        };
+        assert_eq!(e.position.offset, 2);
+
+        let input = "{\"name\": \"Zo\u{eb} \u{1f600}\", \"n\": [1, 2]}".as_bytes();
+        // SAFETY: `input` came from a `&str`.
+        let unchecked = unsafe { from_utf8_unchecked_slice(input) };
+        assert_eq!(unchecked, from_slice(input));
+        // SAFETY: ASCII is UTF-8.
+        assert!(unsafe { from_utf8_unchecked_slice(b"[1,") }.is_err());
+    }
+
+    #[test]
+    fn test_typed_roots() -> anyhow::Result<()> {
+        let object = from_str_object(r#"{"a": 1, "b": [true]}"#)?;
+        assert_eq!(object.len(), 2);
+        assert_eq!(object["b"].to_string(), "[true]");
+        assert_eq!(from_str_array("[1, {}]")?.len(), 2);
+
+        let err = from_str_object("  [1, 2]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parse error: Expected object at root, found array at line 1, column 3"
+        );
+        let err = from_str_array(r#"{"a": 1}"#).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Expected array at root, found object")
+        );
+        assert!(from_str_array("null").is_err());
+        // Syntax errors come through unchanged.
+        assert!(matches!(from_str_object("{\"a\"}"), Err(Error::Parse(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unterminated_string() {
+        let err = from_str("{\"a\": \"unterminated}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lex error: Unterminated string at line 1, column 7, while parsing object value for key \"a\""
+        );
+    }
+}
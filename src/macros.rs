@@ -0,0 +1,152 @@
+/// Builds a [`JsonValue`](crate::JsonValue) from JSON-like syntax.
+///
+/// `null`, `true`, `false`, arrays and objects are written as in JSON, and
+/// trailing commas are allowed. Anything else is a Rust expression,
+/// converted with `JsonValue::from`, so variables and nested `json!` calls
+/// can be spliced in. Object keys are a string literal, or any single
+/// token that converts into a `String`, such as a variable or a
+/// parenthesized expression.
+///
+/// ```
+/// use json_parser::json;
+///
+/// let name = "alice";
+/// let value = json!({
+///     "name": name,
+///     "tags": ["admin", null, 2.5],
+///     (format!("{name}_id")): 7,
+/// });
+/// assert_eq!(value.to_string(), r#"{"name":"alice","tags":["admin",null,2.5],"alice_id":7}"#);
+/// ```
+///
+/// Each element and member takes one step of macro recursion, so very
+/// long literals may need a higher `#![recursion_limit]`.
+#[macro_export]
+macro_rules! json {
+    // Arrays, munched one element at a time into `[$($elements,)*]`.
+    (@array [$($elements:expr,)*]) => {
+        ::std::vec![$($elements,)*]
+    };
+    (@array [$($elements:expr),*]) => {
+        ::std::vec![$($elements),*]
+    };
+    (@array [$($elements:expr,)*] null $($rest:tt)*) => {
+        $crate::json!(@array [$($elements,)* $crate::json!(null)] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] true $($rest:tt)*) => {
+        $crate::json!(@array [$($elements,)* $crate::json!(true)] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] false $($rest:tt)*) => {
+        $crate::json!(@array [$($elements,)* $crate::json!(false)] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json!(@array [$($elements,)* $crate::json!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json!(@array [$($elements,)* $crate::json!({$($object)*})] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::json!(@array [$($elements,)* $crate::json!($next),] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] $last:expr) => {
+        $crate::json!(@array [$($elements,)* $crate::json!($last)])
+    };
+    (@array [$($elements:expr),*] , $($rest:tt)*) => {
+        $crate::json!(@array [$($elements,)*] $($rest)*)
+    };
+
+    // Objects, munched one member at a time in the same way.
+    (@object [$($members:expr,)*]) => {
+        ::std::vec![$($members,)*]
+    };
+    (@object [$($members:expr),*]) => {
+        ::std::vec![$($members),*]
+    };
+    (@object [$($members:expr,)*] $key:tt : null $($rest:tt)*) => {
+        $crate::json!(@object [$($members,)* $crate::json!(@member $key, null)] $($rest)*)
+    };
+    (@object [$($members:expr,)*] $key:tt : true $($rest:tt)*) => {
+        $crate::json!(@object [$($members,)* $crate::json!(@member $key, true)] $($rest)*)
+    };
+    (@object [$($members:expr,)*] $key:tt : false $($rest:tt)*) => {
+        $crate::json!(@object [$($members,)* $crate::json!(@member $key, false)] $($rest)*)
+    };
+    (@object [$($members:expr,)*] $key:tt : [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json!(@object [$($members,)* $crate::json!(@member $key, [$($array)*])] $($rest)*)
+    };
+    (@object [$($members:expr,)*] $key:tt : {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json!(@object [$($members,)* $crate::json!(@member $key, {$($object)*})] $($rest)*)
+    };
+    (@object [$($members:expr,)*] $key:tt : $next:expr, $($rest:tt)*) => {
+        $crate::json!(@object [$($members,)* $crate::json!(@member $key, $next),] $($rest)*)
+    };
+    (@object [$($members:expr,)*] $key:tt : $last:expr) => {
+        $crate::json!(@object [$($members,)* $crate::json!(@member $key, $last)])
+    };
+    (@object [$($members:expr),*] , $($rest:tt)*) => {
+        $crate::json!(@object [$($members,)*] $($rest)*)
+    };
+    (@member $key:tt, $($value:tt)+) => {
+        (::std::string::String::from($key), $crate::json!($($value)+))
+    };
+
+    (null) => {
+        <$crate::JsonValue>::Null
+    };
+    (true) => {
+        <$crate::JsonValue>::Bool(true)
+    };
+    (false) => {
+        <$crate::JsonValue>::Bool(false)
+    };
+    ([$($array:tt)*]) => {
+        <$crate::JsonValue>::Array($crate::json!(@array [] $($array)*))
+    };
+    ({$($object:tt)*}) => {
+        <$crate::JsonValue>::Object($crate::json!(@object [] $($object)*))
+    };
+    ($other:expr) => {
+        <$crate::JsonValue>::from($other)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JsonValue;
+
+    #[test]
+    fn test_literals() -> anyhow::Result<()> {
+        let input = r#"{"a": [1, 2, true], "b": null, "c": {"d": [[], {}, -1.5, "x"]}}"#;
+        let value = json!({
+            "a": [1, 2, true],
+            "b": null,
+            "c": {"d": [[], {}, -1.5, "x"]},
+        });
+        assert_eq!(value, crate::from_str(input)?);
+        assert_eq!(json!([1, 2,]), crate::from_str("[1, 2]")?);
+        assert_eq!(json!([]), JsonValue::empty_array());
+        assert_eq!(json!({}), JsonValue::empty_object());
+        assert_eq!(json!(null), JsonValue::Null);
+        assert_eq!(json!(u64::MAX).to_string(), "18446744073709551615");
+        Ok(())
+    }
+
+    #[test]
+    fn test_interpolation() -> anyhow::Result<()> {
+        let id = 7i64;
+        let name = String::from("alice");
+        let key = "dynamic";
+        let tags = json!(["a", "b"]);
+        let value = json!({
+            "user": {"id": id, "name": name.clone(), "tags": tags.clone()},
+            key: [id + 1, {"nested": json!({"deep": [name.as_str(), id > 5]})}],
+            (format!("{key}_2")): -id,
+        });
+        assert_eq!(
+            value.to_string(),
+            r#"{"user":{"id":7,"name":"alice","tags":["a","b"]},"dynamic":[8,{"nested":{"deep":["alice",true]}}],"dynamic_2":-7}"#
+        );
+        assert_eq!(value.pointer("/user/tags"), Some(&tags));
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use crate::ser::SerializeOptions;
+use crate::value::JsonValue;
+use std::io::{self, Write};
+
+/// Writes `values` as JSON Lines: each value compactly on its own line,
+/// terminated by `\n`.
+///
+/// Lines are written straight to `w` and `w` is flushed once, after the last
+/// value. No buffering is added, so pass a `BufWriter` for unbuffered sinks,
+/// and flush it yourself if you need lines to appear as they are produced.
+pub fn write_ndjson<W: Write, I: IntoIterator<Item = JsonValue>>(
+    w: &mut W,
+    values: I,
+) -> io::Result<()> {
+    let options = SerializeOptions::default();
+    for value in values {
+        value.write_to(&mut *w, &options)?;
+        w.write_all(b"\n")?;
+    }
+    w.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_write_ndjson() -> anyhow::Result<()> {
+        let values = vec![
+            from_str(r#"{"msg": "line\nbreak", "n": [1, 2]}"#)?,
+            JsonValue::Null,
+            JsonValue::empty_object(),
+        ];
+        let mut out = Vec::new();
+        write_ndjson(&mut out, values.clone())?;
+        let text = String::from_utf8(out)?;
+        assert_eq!(text, "{\"msg\":\"line\\nbreak\",\"n\":[1,2]}\nnull\n{}\n");
+        let reparsed: Vec<_> = text.lines().map(from_str).collect::<Result<_, _>>()?;
+        assert_eq!(reparsed, values);
+        Ok(())
+    }
+}
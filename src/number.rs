@@ -0,0 +1,292 @@
+use std::fmt;
+
+/// A JSON number, remembering whether it was written as an integer or with
+/// a fraction/exponent.
+///
+/// Integer literals that fit are stored exactly as `Int` (or `UInt` above
+/// `i64::MAX`); everything else is a `Float`. Two literals that neither
+/// form can hold are kept as `Text`, so they serialize as written: `-0`,
+/// whose sign an `Int` would lose, and literals beyond the range of `f64`
+/// such as `1e400`, whose value is then infinite.
+///
+/// Under [`ParserOptions::keep_number_text`](crate::ParserOptions::keep_number_text)
+/// every literal is a `Text` instead: its `f64` value plus the literal as
+/// written, which is what it serializes as. Two `Text`s are equal only if
+/// their literals are, so `1.10` and `1.1` differ.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Text(f64, Box<str>),
+}
+
+impl Number {
+    /// Interprets a number literal. `None` if it isn't one, which can only
+    /// happen for text from the tokenizer's fast number scanner.
+    pub(crate) fn from_literal(text: &str) -> Option<Self> {
+        if !text.contains(['.', 'e', 'E']) {
+            if text == "-0" {
+                return Some(Number::Text(-0.0, text.into()));
+            }
+            if let Ok(i) = text.parse() {
+                return Some(Number::Int(i));
+            }
+            if let Ok(u) = text.parse() {
+                return Some(Number::UInt(u));
+            }
+        }
+        match text.parse::<f64>().ok()? {
+            f if f.is_finite() => Some(Number::Float(f)),
+            // Only a valid literal can stand in for the value.
+            f if is_json_literal(text) => Some(Number::Text(f, text.into())),
+            _ => None,
+        }
+    }
+
+    /// Interprets a number literal, keeping its text. `None` as for
+    /// `from_literal`. A literal outside the JSON grammar, which only the
+    /// lenient scanning options let through, would not serialize as JSON,
+    /// so it is interpreted as by `from_literal` instead.
+    pub(crate) fn from_literal_text(text: &str) -> Option<Self> {
+        if !is_json_literal(text) {
+            return Self::from_literal(text);
+        }
+        let value = text.parse().ok()?;
+        Some(Number::Text(value, text.into()))
+    }
+
+    /// The literal as written in the source, for a `Text` number.
+    pub fn source_text(&self) -> Option<&str> {
+        match self {
+            Number::Text(_, text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The value as an `i64`, if it is a whole number in range. Floats count
+    /// only when exactly integral; nothing is truncated or rounded.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Number::Int(i) => Some(i),
+            Number::UInt(u) => i64::try_from(u).ok(),
+            // Both bounds are powers of two, so they are exact as f64.
+            Number::Float(f) | Number::Text(f, _)
+                if f.fract() == 0.0 && (-(2f64.powi(63))..2f64.powi(63)).contains(&f) =>
+            {
+                Some(f as i64)
+            }
+            Number::Float(_) | Number::Text(..) => None,
+        }
+    }
+
+    /// The value as a `u64`, if it is a non-negative whole number in range.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Number::Int(i) => u64::try_from(i).ok(),
+            Number::UInt(u) => Some(u),
+            Number::Float(f) | Number::Text(f, _)
+                if f.fract() == 0.0 && (0.0..2f64.powi(64)).contains(&f) =>
+            {
+                Some(f as u64)
+            }
+            Number::Float(_) | Number::Text(..) => None,
+        }
+    }
+
+    /// The value as an `f64`. Always available, but integers beyond 2^53
+    /// are rounded.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Number::Int(i) => i as f64,
+            Number::UInt(u) => u as f64,
+            Number::Float(f) | Number::Text(f, _) => f,
+        }
+    }
+
+    /// Whether the number is stored as a float. A `Text` number is, even if
+    /// its literal is an integer.
+    pub fn is_float(&self) -> bool {
+        matches!(self, Number::Float(_) | Number::Text(..))
+    }
+
+    /// The canonical form of the value: whole floats that fit become
+    /// integers, so `1.0`, `1e0` and `1` are all `Int(1)`. `-0.0` becomes
+    /// `0`. Other floats are unchanged, and `Text` numbers lose their text.
+    pub fn normalized(self) -> Number {
+        match self {
+            Number::Float(_) | Number::Text(..) => self
+                .as_i64()
+                .map(Number::Int)
+                .or_else(|| self.as_u64().map(Number::UInt))
+                .unwrap_or(Number::Float(self.as_f64())),
+            _ => self,
+        }
+    }
+}
+
+/// Whether `text` is exactly a number under the JSON grammar: no leading
+/// `+` or zeros, and digits on both sides of a `.`.
+fn is_json_literal(text: &str) -> bool {
+    fn digits(bytes: &[u8]) -> usize {
+        bytes.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+    let bytes = text.as_bytes();
+    let mut at = usize::from(bytes.first() == Some(&b'-'));
+    match digits(&bytes[at..]) {
+        0 => return false,
+        n if n > 1 && bytes[at] == b'0' => return false,
+        n => at += n,
+    }
+    if bytes.get(at) == Some(&b'.') {
+        match digits(&bytes[at + 1..]) {
+            0 => return false,
+            n => at += 1 + n,
+        }
+    }
+    if matches!(bytes.get(at), Some(b'e' | b'E')) {
+        at += 1 + usize::from(matches!(bytes.get(at + 1), Some(b'+' | b'-')));
+        match digits(&bytes[at..]) {
+            0 => return false,
+            n => at += n,
+        }
+    }
+    at == bytes.len()
+}
+
+/// Integers print as integers. Floats always keep a decimal point or an
+/// exponent (`2.0`, `-0.0`, `1e300`) so they read back as floats. A NaN or
+/// infinite `Float` has no JSON form, so formatting one fails; parsing
+/// never produces one.
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Number::Int(i) => write!(f, "{i}"),
+            Number::UInt(u) => write!(f, "{u}"),
+            // `Debug` is the shortest round-tripping form and keeps the `.0`.
+            Number::Float(x) if x.is_finite() => write!(f, "{x:?}"),
+            Number::Float(_) => Err(fmt::Error),
+            Number::Text(_, ref text) => f.write_str(text),
+        }
+    }
+}
+
+impl From<i64> for Number {
+    fn from(i: i64) -> Self {
+        Number::Int(i)
+    }
+}
+
+impl From<i32> for Number {
+    fn from(i: i32) -> Self {
+        Number::Int(i.into())
+    }
+}
+
+impl From<u64> for Number {
+    fn from(u: u64) -> Self {
+        i64::try_from(u).map_or(Number::UInt(u), Number::Int)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(f: f64) -> Self {
+        Number::Float(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_forms() {
+        for (literal, expected) in [
+            ("-0.0", "-0.0"),
+            ("0.0", "0.0"),
+            ("2.0", "2.0"),
+            ("2", "2"),
+            ("-0", "-0"),
+            ("0", "0"),
+            ("1.5e3", "1500.0"),
+            ("1e300", "1e300"),
+            ("18446744073709551615", "18446744073709551615"),
+            ("1e400", "1e400"),
+            ("-1e400", "-1e400"),
+        ] {
+            assert_eq!(Number::from_literal(literal).unwrap().to_string(), expected);
+        }
+        assert!(
+            Number::from_literal("-0")
+                .unwrap()
+                .as_f64()
+                .is_sign_negative()
+        );
+        assert_eq!(Number::from_literal("2"), Some(Number::Int(2)));
+        assert_eq!(Number::from_literal("-0").unwrap().as_i64(), Some(0));
+        assert_eq!(
+            Number::from_literal("1e400").unwrap().as_f64(),
+            f64::INFINITY
+        );
+        let mut text = String::new();
+        assert!(fmt::write(&mut text, format_args!("{}", Number::Float(f64::NAN))).is_err());
+    }
+
+    #[test]
+    fn test_coercions() {
+        let n = |s| Number::from_literal(s).unwrap();
+        assert_eq!(n("9223372036854775808").as_i64(), None);
+        assert_eq!(n("9223372036854775808").as_u64(), Some(9223372036854775808));
+        assert_eq!(n("9223372036854775807").as_i64(), Some(i64::MAX));
+        assert_eq!(n("-1").as_u64(), None);
+        assert_eq!(n("-1").as_i64(), Some(-1));
+        assert_eq!(n("2.0").as_i64(), Some(2));
+        assert_eq!(n("2.5").as_i64(), None);
+        assert_eq!(n("2.5").as_u64(), None);
+        assert_eq!(n("1e19").as_i64(), None);
+        assert_eq!(n("1e19").as_u64(), Some(10_000_000_000_000_000_000));
+        assert_eq!(n("1e20").as_u64(), None);
+        assert_eq!(n("-9223372036854775808.0").as_i64(), Some(i64::MIN));
+        assert_eq!(n("2.5").as_f64(), 2.5);
+    }
+
+    #[test]
+    fn test_normalized() {
+        let n = |s| Number::from_literal(s).unwrap().normalized();
+        assert_eq!(n("1.0"), Number::Int(1));
+        assert_eq!(n("-0"), Number::Int(0));
+        assert_eq!(n("1e19"), Number::UInt(10_000_000_000_000_000_000));
+        assert_eq!(n("1.5"), Number::Float(1.5));
+        assert_eq!(n("1e300"), Number::Float(1e300));
+    }
+
+    #[test]
+    fn test_text() {
+        let n = Number::from_literal_text("1.10").unwrap();
+        assert_eq!(n.to_string(), "1.10");
+        assert_eq!(n.as_f64(), 1.1);
+        assert_eq!(n.source_text(), Some("1.10"));
+        assert_ne!(n, Number::from_literal_text("1.1").unwrap());
+        assert_eq!(n.clone().normalized(), Number::Float(1.1));
+
+        let n = Number::from_literal_text("1e2").unwrap();
+        assert_eq!(n.to_string(), "1e2");
+        assert_eq!((n.as_i64(), n.as_u64()), (Some(100), Some(100)));
+        assert_eq!(Number::Int(5).source_text(), None);
+
+        // Literals outside the grammar aren't kept.
+        for (literal, expected) in [
+            ("1.", Number::Float(1.0)),
+            ("-.5", Number::Float(-0.5)),
+            ("1.e5", Number::Float(1e5)),
+            ("01", Number::Int(1)),
+        ] {
+            assert_eq!(
+                Number::from_literal_text(literal),
+                Some(expected),
+                "{literal}"
+            );
+        }
+        assert_eq!(Number::from_literal("1.e400"), None);
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Instant;
+
+/// What to do when an object contains the same key more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// Keep the first value, ignore later ones.
+    First,
+    /// Keep the last value, in the position of the first occurrence.
+    #[default]
+    Last,
+    /// Fail with a parse error at the repeated key.
+    Error,
+    /// Collect every value for the key into an array, in source order.
+    Combine,
+}
+
+/// What to do with U+0000 and Unicode noncharacters (such as U+FFFE) in
+/// strings, escaped or not. They are valid JSON, but some databases and
+/// protocols can't store them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StringSanitizer {
+    #[default]
+    Accept,
+    /// Fail with a parse error at the string.
+    Reject,
+    /// Substitute U+FFFD REPLACEMENT CHARACTER.
+    Replace,
+}
+
+/// Knobs controlling how text is parsed. `ParserOptions::default()` accepts
+/// standard JSON.
+#[derive(Debug, Clone, Default)]
+pub struct ParserOptions {
+    pub duplicate_keys: DuplicateKeys,
+    /// Longest allowed string or object key, in bytes after decoding
+    /// escapes. `None` means unlimited.
+    pub max_string_length: Option<usize>,
+    /// Longest allowed object key, in bytes after decoding escapes, for
+    /// inputs whose keys end up in maps. Applies on top of
+    /// `max_string_length`. `None` means unlimited.
+    pub max_key_length: Option<usize>,
+    /// Scan numbers as any run of `-+0-9.eE` instead of matching the exact
+    /// JSON grammar. When building values the run must still parse as a
+    /// number, but Rust's looser syntax applies (`1.`, `+1` are accepted);
+    /// when validating, the run is only checked for leading zeros.
+    pub fast_numbers: bool,
+    /// Accept numbers written with a leading `+`, as some lenient producers
+    /// emit. The sign is dropped: the value serializes without it.
+    pub allow_leading_plus: bool,
+    /// Accept integer parts with redundant leading zeros, such as `01` or
+    /// `-007.5`, which the JSON grammar forbids. The value is read as if
+    /// they weren't there.
+    pub allow_leading_zeros: bool,
+    /// Accept `_` between two digits of a number, as in `1_000_000`, and
+    /// drop it: the value, and the text kept by `keep_number_text`, are
+    /// those of the number without separators. A `_` at either end of a
+    /// digit run, e.g. next to the sign, `.` or `e`, is still an error.
+    pub allow_digit_separators: bool,
+    /// Parse an empty or whitespace-only document as `null` instead of
+    /// failing, as some HTTP handlers treat an empty body.
+    pub empty_as_null: bool,
+    /// Deepest allowed nesting of arrays and objects; the root container is
+    /// at depth 1. `None` means unlimited. Parsing recurses once per level,
+    /// so set this for untrusted input. The same budget on
+    /// [`SerializeOptions`](crate::SerializeOptions) bounds serialization.
+    pub depth_budget: Option<usize>,
+    /// Most members allowed in any one object, or elements in any one
+    /// array. Repeated keys count each time they appear. `None` means
+    /// unlimited.
+    pub max_container_entries: Option<usize>,
+    /// Most digits allowed in one number literal, counting the integer,
+    /// fraction and exponent parts. Checked while scanning, so an overlong
+    /// literal is rejected without being read to the end, even when only
+    /// validating. `None` means unlimited.
+    pub max_number_digits: Option<usize>,
+    /// Skip `// line` and `/* block */` comments between tokens, as in
+    /// JSONC configuration files. Block comments don't nest.
+    pub allow_comments: bool,
+    /// Store numbers in canonical form (see [`Number::normalized`]), so
+    /// that `1.0`, `1e0` and `1` parse to equal values and all serialize as
+    /// `1`. Off by default, keeping the integer/float distinction of the
+    /// source.
+    ///
+    /// [`Number::normalized`]: crate::Number::normalized
+    pub normalize_numbers: bool,
+    /// Applies to object keys as well as string values.
+    pub string_sanitizer: StringSanitizer,
+    /// Store every number as a [`Number::Text`]: its `f64` value, used by
+    /// the numeric accessors, and its literal, used when serializing, so
+    /// that `1.10` or `1e2` are written back exactly as in the source.
+    /// `normalize_numbers` takes precedence.
+    ///
+    /// [`Number::Text`]: crate::Number::Text
+    pub keep_number_text: bool,
+    /// Abort with [`Error::Cancelled`] once this flag is set, e.g. by a
+    /// request handler that timed out. Checked every few hundred tokens,
+    /// so it takes effect promptly without slowing parsing down.
+    ///
+    /// [`Error::Cancelled`]: crate::Error::Cancelled
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// Abort with [`Error::Cancelled`] once this instant has passed,
+    /// checked as for `cancel`.
+    ///
+    /// [`Error::Cancelled`]: crate::Error::Cancelled
+    pub deadline: Option<Instant>,
+}
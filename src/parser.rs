@@ -0,0 +1,1123 @@
+use crate::backing::{ObjectBacking, Ordered};
+use crate::error::{Error, LexError, ParseError, Position};
+use crate::number::Number;
+use crate::options::{DuplicateKeys, ParserOptions, StringSanitizer};
+use crate::spans::{SpanKind, SpanTree};
+use crate::tokenizer::{Span, Spanned, Token, Token::*};
+use crate::value::JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// What the parser builds values with. Strings and keys are handed over
+/// decoded and sanitized, borrowed from the input or a scratch buffer, so
+/// a sink that shares strings allocates nothing for one it already holds.
+pub(crate) trait Sink {
+    type Value: fmt::Debug;
+    type Key: AsRef<str> + fmt::Debug;
+
+    fn null(&mut self) -> Self::Value;
+
+    fn bool(&mut self, b: bool) -> Self::Value;
+
+    fn number(&mut self, n: Number) -> Self::Value;
+
+    fn string(&mut self, text: &str) -> Self::Value;
+
+    fn key(&mut self, text: &str) -> Self::Key;
+
+    fn array(&mut self, elements: impl ExactSizeIterator<Item = Self::Value>) -> Self::Value;
+
+    /// Builds an object from its members in source order, repeated keys
+    /// already resolved.
+    fn object(
+        &mut self,
+        members: impl ExactSizeIterator<Item = (Self::Key, Self::Value)>,
+    ) -> Self::Value;
+
+    /// Adds `value` under a key repeated under [`DuplicateKeys::Combine`],
+    /// first wrapping `existing` in an array if this is the first repeat.
+    fn combine(&mut self, existing: &mut Self::Value, value: Self::Value, first: bool);
+}
+
+/// Builds owned [`JsonValue`]s whose objects are stored as `B` says, each
+/// map built once from its members.
+pub(crate) struct Owned<B: ObjectBacking = Ordered>(PhantomData<B>);
+
+impl<B: ObjectBacking> Default for Owned<B> {
+    fn default() -> Self {
+        Owned(PhantomData)
+    }
+}
+
+impl<B: ObjectBacking> fmt::Debug for Owned<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Owned")
+    }
+}
+
+impl<B: ObjectBacking> Sink for Owned<B> {
+    type Value = JsonValue<B>;
+    type Key = String;
+
+    fn null(&mut self) -> JsonValue<B> {
+        JsonValue::Null
+    }
+
+    fn bool(&mut self, b: bool) -> JsonValue<B> {
+        JsonValue::Bool(b)
+    }
+
+    fn number(&mut self, n: Number) -> JsonValue<B> {
+        JsonValue::Number(n)
+    }
+
+    fn string(&mut self, text: &str) -> JsonValue<B> {
+        JsonValue::String(text.to_owned())
+    }
+
+    fn key(&mut self, text: &str) -> String {
+        text.to_owned()
+    }
+
+    fn array(&mut self, elements: impl ExactSizeIterator<Item = JsonValue<B>>) -> JsonValue<B> {
+        JsonValue::Array(elements.collect())
+    }
+
+    fn object(
+        &mut self,
+        members: impl ExactSizeIterator<Item = (String, JsonValue<B>)>,
+    ) -> JsonValue<B> {
+        JsonValue::Object(B::collect(members))
+    }
+
+    fn combine(&mut self, existing: &mut JsonValue<B>, value: JsonValue<B>, first: bool) {
+        if first {
+            *existing = JsonValue::Array(vec![std::mem::replace(existing, JsonValue::Null)]);
+        }
+        if let JsonValue::Array(values) = existing {
+            values.push(value);
+        }
+    }
+}
+
+/// Stacks that collect the children of open containers, shared by all
+/// containers of a document. Each container's children end up in a `Vec`
+/// allocated once at its exact size, instead of one grown by doubling, and
+/// the stacks themselves can be kept for the next document.
+///
+/// Members are collected in source order whatever the sink builds, so a
+/// repeated key is found and handled as `duplicate_keys` says before the
+/// object is built.
+#[derive(Debug)]
+pub(crate) struct Scratch<S: Sink = Owned> {
+    elements: Vec<S::Value>,
+    members: Vec<(S::Key, S::Value)>,
+    /// Where strings with escapes are decoded, for the same reason: the
+    /// decoded text is then copied out at its exact size.
+    string: String,
+}
+
+impl<S: Sink> Default for Scratch<S> {
+    fn default() -> Self {
+        Scratch {
+            elements: Vec::new(),
+            members: Vec::new(),
+            string: String::new(),
+        }
+    }
+}
+
+impl<S: Sink> Scratch<S> {
+    pub fn clear(&mut self) {
+        self.elements.clear();
+        self.members.clear();
+        self.string.clear();
+    }
+}
+
+/// Builds values through the sink `S`, owned ones by default.
+pub(crate) struct Parser<'a, I: Iterator<Item = Spanned>, S: Sink = Owned> {
+    input: &'a str,
+    tokens: Peekable<I>,
+    options: &'a ParserOptions,
+    /// Check the grammar only: numbers are not converted and containers are
+    /// not filled in.
+    validate_only: bool,
+    /// When recording spans, the nodes of values parsed so far whose parent
+    /// hasn't been completed yet.
+    spans: Option<Vec<SpanTree>>,
+    /// End offset of the last consumed token.
+    last_end: usize,
+    /// Containers currently open.
+    depth: usize,
+    /// Tokens consumed, for spacing out cancellation checks.
+    consumed: usize,
+    scratch: Scratch<S>,
+    sink: S,
+}
+
+/// Most members an object can have before its keys are indexed, see
+/// [`KeyIndex`].
+const KEY_INDEX_THRESHOLD: usize = 8;
+
+/// The keys of one object's members so far, for spotting repeats. Small
+/// objects are scanned; larger ones get a hash index, so that finding a
+/// repeat takes constant time however many members the object has.
+#[derive(Default)]
+struct KeyIndex {
+    positions: HashMap<String, usize>,
+}
+
+impl KeyIndex {
+    /// Where `key` is among `members`, the object's members so far.
+    fn find<K: AsRef<str>, V>(&mut self, members: &[(K, V)], key: &str) -> Option<usize> {
+        if members.len() <= KEY_INDEX_THRESHOLD {
+            return members.iter().position(|(k, _)| k.as_ref() == key);
+        }
+        if self.positions.is_empty() {
+            let keys = members
+                .iter()
+                .enumerate()
+                .map(|(i, (k, _))| (k.as_ref().to_string(), i));
+            self.positions.extend(keys);
+        }
+        self.positions.get(key).copied()
+    }
+
+    /// Records a new member with `key` at position `i`.
+    fn insert(&mut self, key: &str, i: usize) {
+        if !self.positions.is_empty() {
+            self.positions.insert(key.to_string(), i);
+        }
+    }
+}
+
+/// How many tokens are consumed between checks of
+/// [`ParserOptions::cancel`] and [`ParserOptions::deadline`].
+const CANCEL_CHECK_INTERVAL: usize = 256;
+
+impl<'a, I: Iterator<Item = Spanned>, B: ObjectBacking> Parser<'a, I, Owned<B>> {
+    pub fn new(input: &'a str, tokens: I, options: &'a ParserOptions) -> Self {
+        Self::with_sink(input, tokens, options, Owned::default())
+    }
+}
+
+impl<'a, I: Iterator<Item = Spanned>, S: Sink> Parser<'a, I, S> {
+    pub fn with_sink(input: &'a str, tokens: I, options: &'a ParserOptions, sink: S) -> Self {
+        Parser {
+            input,
+            tokens: tokens.peekable(),
+            options,
+            validate_only: false,
+            spans: None,
+            last_end: 0,
+            depth: 0,
+            consumed: 0,
+            scratch: Scratch::default(),
+            sink,
+        }
+    }
+
+    /// Collects children in `scratch`, which must be empty, instead of in
+    /// fresh stacks.
+    pub fn with_scratch(mut self, scratch: Scratch<S>) -> Self {
+        self.scratch = scratch;
+        self
+    }
+
+    /// Gives the scratch stacks back for reuse. They are left empty only if
+    /// parsing succeeded.
+    pub fn into_scratch(self) -> Scratch<S> {
+        self.scratch
+    }
+
+    pub fn recording_spans(mut self) -> Self {
+        self.spans = Some(Vec::new());
+        self
+    }
+
+    /// Parses a complete document along with the source span of every node.
+    pub fn parse_with_spans(&mut self) -> Result<(S::Value, SpanTree)> {
+        let value = self.parse()?;
+        let tree = self.spans.as_mut().and_then(Vec::pop).unwrap();
+        Ok((value, tree))
+    }
+
+    /// Accounts for a container about to be opened at `offset`.
+    fn enter(&mut self, offset: usize) -> Result<()> {
+        self.depth += 1;
+        match self.options.depth_budget {
+            Some(limit) if self.depth > limit => Err(self.error(
+                offset,
+                format!("Nesting deeper than depth budget of {limit}"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Accounts for entry `index` of an open container, about to be parsed.
+    fn entry(&mut self, index: usize, what: &str) -> Result<()> {
+        match self.options.max_container_entries {
+            Some(limit) if index >= limit => {
+                let offset = self.next_offset();
+                Err(self.error(offset, format!("{what} has more than {limit} entries")))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn record(&mut self, start: usize, kind: SpanKind) {
+        let span = Span {
+            start,
+            end: self.last_end,
+        };
+        if let Some(stack) = &mut self.spans {
+            stack.push(SpanTree { span, kind });
+        }
+    }
+
+    pub fn validating(mut self) -> Self {
+        self.validate_only = true;
+        self
+    }
+
+    fn consume_token(&mut self, token: Token) -> Result<&'a str> {
+        self.consumed += 1;
+        if self.consumed.is_multiple_of(CANCEL_CHECK_INTERVAL) {
+            self.check_cancelled()?;
+        }
+        match self.tokens.next().transpose()? {
+            Some((t, span)) if t == token => {
+                self.last_end = span.end;
+                Ok(&self.input[span.start..span.end])
+            }
+            Some((t, span)) => Err(self.error(
+                span.start,
+                format!("Expecting token {:?}. Got {:?}", token, t),
+            )),
+            None => Err(self.error(
+                self.input.len(),
+                format!("Expecting token {:?}. Got end of input", token),
+            )),
+        }
+    }
+
+    fn check_cancelled(&mut self) -> Result<()> {
+        let cancelled = self
+            .options
+            .cancel
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed));
+        let expired = self
+            .options
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        if cancelled || expired {
+            let offset = self.next_offset();
+            return Err(Error::Cancelled(Position::locate(self.input, offset)));
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Token> {
+        match self.tokens.peek() {
+            Some(Ok((t, _))) => Ok(*t),
+            Some(Err(e)) => Err(e.clone().into()),
+            None => Err(self.error(
+                self.input.len(),
+                "Expecting to peek a token but there aren't any more.",
+            )),
+        }
+    }
+
+    fn error(&self, offset: usize, message: impl Into<String>) -> Error {
+        Error::Parse(ParseError::new(
+            message,
+            Position::locate(self.input, offset),
+        ))
+    }
+
+    /// Parses a complete document: a single value and nothing after it.
+    pub fn parse(&mut self) -> Result<S::Value> {
+        if self.options.empty_as_null && self.tokens.peek().is_none() {
+            self.record(0, SpanKind::Scalar);
+            return Ok(self.sink.null());
+        }
+        let value = self.parse_expr()?;
+        if let Some((t, span)) = self.tokens.next().transpose()? {
+            return Err(self.error(span.start, format!("Expecting end of input. Got {:?}", t)));
+        }
+        Ok(value)
+    }
+
+    /// Parses an object, starting at its `{`. Like every `parse_*` method,
+    /// it consumes all of its own tokens; `parse_expr` only peeks.
+    fn parse_object(&mut self) -> Result<S::Value> {
+        let start = self.next_offset();
+        self.enter(start)?;
+        self.consume_token(BeginObject)?;
+
+        let mark = self.scratch.members.len();
+        let mut member_spans = Vec::new();
+        let mut keys = KeyIndex::default();
+        // Indices of members already turned into arrays by `Combine`.
+        let mut combined = HashSet::new();
+        if self.peek()? != EndObject {
+            for index in 0.. {
+                self.entry(index, "Object")?;
+                self.parse_member(mark, &mut member_spans, &mut keys, &mut combined)?;
+                if self.peek()? != ValueSeparator {
+                    break;
+                }
+                self.consume_token(ValueSeparator)?;
+            }
+        }
+
+        self.consume_token(EndObject)?;
+        self.depth -= 1;
+        self.record(start, SpanKind::Object(member_spans));
+        Ok(self.sink.object(self.scratch.members.drain(mark..)))
+    }
+
+    /// Parses one member onto the scratch stack, where this object's
+    /// members start at `mark`.
+    fn parse_member(
+        &mut self,
+        mark: usize,
+        member_spans: &mut Vec<(String, Span, SpanTree)>,
+        keys: &mut KeyIndex,
+        combined: &mut HashSet<usize>,
+    ) -> Result<()> {
+        let offset = self.next_offset();
+        let key = self.read_string(true, |sink, text| sink.key(text))?;
+        let key_span = Span {
+            start: offset,
+            end: self.last_end,
+        };
+        let found = match self.tokens.peek() {
+            Some(Ok((NameSeparator, _))) | Some(Err(_)) => None,
+            Some(Ok((t, span))) => Some((span.start, format!("{t:?}"))),
+            None => Some((self.input.len(), "end of input".to_string())),
+        };
+        if let Some((offset, found)) = found {
+            return Err(self.error(
+                offset,
+                format!("Expected ':' after object key {key:?}, found {found}"),
+            ));
+        }
+        self.consume_token(NameSeparator)?;
+        let value = self
+            .parse_expr()
+            .map_err(|e| e.within(format!("object value for key {:?}", key.as_ref())))?;
+        let value_span = self.spans.as_mut().and_then(Vec::pop);
+        if self.validate_only && self.options.duplicate_keys != DuplicateKeys::Error {
+            return Ok(());
+        }
+
+        let members = &mut self.scratch.members;
+        let Some(i) = keys.find(&members[mark..], key.as_ref()) else {
+            keys.insert(key.as_ref(), members.len() - mark);
+            if let Some(node) = value_span {
+                member_spans.push((key.as_ref().to_string(), key_span, node));
+            }
+            members.push((key, value));
+            return Ok(());
+        };
+        let members = &mut members[mark..];
+        match self.options.duplicate_keys {
+            DuplicateKeys::First => {}
+            DuplicateKeys::Last => {
+                members[i].1 = value;
+                if let Some(node) = value_span {
+                    member_spans[i] = (key.as_ref().to_string(), key_span, node);
+                }
+            }
+            DuplicateKeys::Error => {
+                let message = format!("Duplicate key {:?}", key.as_ref());
+                return Err(self.error(offset, message));
+            }
+            DuplicateKeys::Combine => {
+                let first = combined.insert(i);
+                self.sink.combine(&mut members[i].1, value, first);
+                // The combined array has no source text of its own; it
+                // takes the span of the first value.
+                if let Some(node) = value_span {
+                    let existing = &mut member_spans[i].2;
+                    if first {
+                        let original = existing.clone();
+                        existing.kind = SpanKind::Array(vec![original]);
+                    }
+                    if let SpanKind::Array(nodes) = &mut existing.kind {
+                        nodes.push(node);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_array(&mut self) -> Result<S::Value> {
+        let start = self.next_offset();
+        self.enter(start)?;
+        self.consume_token(BeginArray)?;
+        let span_mark = self.spans.as_ref().map_or(0, Vec::len);
+        let mark = self.scratch.elements.len();
+        let first = self
+            .peek()
+            .map_err(|e| e.within("array element 0".to_string()))?;
+        if first == EndArray {
+            self.consume_token(EndArray)?;
+            self.depth -= 1;
+            self.record(start, SpanKind::Array(Vec::new()));
+            return Ok(self.sink.array(std::iter::empty()));
+        }
+
+        let mut index = 0;
+        loop {
+            self.entry(index, "Array")?;
+            let element = self
+                .parse_expr()
+                .map_err(|e| e.within(format!("array element {index}")))?;
+            if !self.validate_only {
+                self.scratch.elements.push(element);
+            }
+            if self.peek()? != ValueSeparator {
+                break;
+            }
+            self.consume_token(ValueSeparator)?;
+            index += 1;
+        }
+
+        self.consume_token(EndArray)?;
+        self.depth -= 1;
+        let children = self
+            .spans
+            .as_mut()
+            .map_or_else(Vec::new, |stack| stack.split_off(span_mark));
+        self.record(start, SpanKind::Array(children));
+        Ok(self.sink.array(self.scratch.elements.drain(mark..)))
+    }
+
+    fn parse_expr(&mut self) -> Result<S::Value> {
+        let start = self.next_offset();
+        let value = match self.peek()? {
+            BeginArray => return self.parse_array(),
+            BeginObject => return self.parse_object(),
+            _ => self.parse_scalar()?,
+        };
+        self.record(start, SpanKind::Scalar);
+        Ok(value)
+    }
+
+    fn parse_scalar(&mut self) -> Result<S::Value> {
+        match self.peek()? {
+            Number => {
+                let offset = self.next_offset();
+                let text = self.consume_token(Number)?;
+                if self.validate_only {
+                    return Ok(self.sink.null());
+                }
+                // A `+` only gets this far under `allow_leading_plus`.
+                let text = text.strip_prefix('+').unwrap_or(text);
+                // And a `_` only under `allow_digit_separators`.
+                let stripped;
+                let text = if text.contains('_') {
+                    stripped = text.replace('_', "");
+                    stripped.as_str()
+                } else {
+                    text
+                };
+                // Only literals from the fast scanner can fail here.
+                let number = if self.options.keep_number_text && !self.options.normalize_numbers {
+                    crate::number::Number::from_literal_text(text)
+                } else {
+                    crate::number::Number::from_literal(text)
+                };
+                match number {
+                    Some(n) if self.options.normalize_numbers => {
+                        Ok(self.sink.number(n.normalized()))
+                    }
+                    Some(n) => Ok(self.sink.number(n)),
+                    None => Err(Error::Lex(LexError::new(
+                        format!("Invalid number {text:?}"),
+                        Position::locate(self.input, offset),
+                    ))),
+                }
+            }
+            Boolean => {
+                let b = self.consume_token(Boolean)? == "true";
+                Ok(self.sink.bool(b))
+            }
+            Null => {
+                self.consume_token(Null)?;
+                Ok(self.sink.null())
+            }
+            Stringy if self.validate_only => {
+                self.read_string(false, |_, _| ())?;
+                Ok(self.sink.null())
+            }
+            Stringy => self.read_string(false, |sink, text| sink.string(text)),
+            t @ (BeginArray | BeginObject) => unreachable!("{t:?} is not a scalar"),
+            t => {
+                let offset = self.next_offset();
+                Err(self.error(offset, format!("Expecting an expression. Got {:?}.", t)))
+            }
+        }
+    }
+
+    /// Decodes and checks the next string literal, an object key if `key`,
+    /// and hands its text to `build` along with the sink.
+    fn read_string<T>(&mut self, key: bool, build: impl FnOnce(&mut S, &str) -> T) -> Result<T> {
+        let offset = self.next_offset();
+        let literal = self.consume_token(Stringy)?;
+        let string_limit = self.options.max_string_length;
+        let key_limit = self.options.max_key_length.filter(|_| key);
+        // The key limit reports the error when it is the tighter one.
+        let limit = match (string_limit, key_limit) {
+            (Some(a), Some(b)) if a < b => Some(a),
+            (a, b) => b.or(a),
+        };
+        let body = &literal[1..literal.len() - 1];
+        let text = if !body.contains('\\') && limit.is_none_or(|max| body.len() <= max) {
+            body
+        } else {
+            let buffer = &mut self.scratch.string;
+            buffer.clear();
+            if let Err(e) = decode_into(literal, limit, buffer) {
+                return Err(match e {
+                    DecodeError::Malformed(message) => {
+                        Error::Lex(LexError::new(message, Position::locate(self.input, offset)))
+                    }
+                    DecodeError::TooLong if limit == key_limit => {
+                        let mut prefix: String = buffer.chars().take(KEY_PREFIX_CHARS).collect();
+                        if prefix.len() < buffer.len() {
+                            prefix.push('…');
+                        }
+                        self.error(
+                            offset,
+                            format!(
+                                "Object key {prefix:?} longer than max_key_length ({} bytes)",
+                                limit.unwrap()
+                            ),
+                        )
+                    }
+                    DecodeError::TooLong => self.error(
+                        offset,
+                        format!(
+                            "String longer than max_string_length ({} bytes)",
+                            limit.unwrap()
+                        ),
+                    ),
+                });
+            }
+            self.scratch.string.as_str()
+        };
+
+        let restricted = match self.options.string_sanitizer {
+            StringSanitizer::Accept => None,
+            _ => text.chars().find(|&c| is_restricted(c)),
+        };
+        if let Some(c) = restricted
+            && self.options.string_sanitizer == StringSanitizer::Reject
+        {
+            let message = format!("String contains restricted character U+{:04X}", c as u32);
+            return Err(self.error(offset, message));
+        }
+        Ok(match restricted {
+            None => build(&mut self.sink, text),
+            Some(_) => {
+                let replaced: String = text
+                    .chars()
+                    .map(|c| if is_restricted(c) { '\u{FFFD}' } else { c })
+                    .collect();
+                build(&mut self.sink, &replaced)
+            }
+        })
+    }
+
+    /// Byte offset of the next token, or of the end of input.
+    fn next_offset(&mut self) -> usize {
+        match self.tokens.peek() {
+            Some(Ok((_, span))) => span.start,
+            _ => self.input.len(),
+        }
+    }
+}
+
+/// How much of an overlong key is quoted in the error.
+const KEY_PREFIX_CHARS: usize = 16;
+
+/// What [`StringSanitizer`] screens for: U+0000 and the Unicode
+/// noncharacters, U+FDD0 to U+FDEF and the last two code points of every
+/// plane (U+FFFE, U+FFFF, U+1FFFE, ...).
+fn is_restricted(c: char) -> bool {
+    let c = c as u32;
+    c == 0 || (0xFDD0..=0xFDEF).contains(&c) || c & 0xFFFE == 0xFFFE
+}
+
+pub(crate) enum DecodeError {
+    Malformed(String),
+    /// The decoded string passed the caller's length limit.
+    TooLong,
+}
+
+impl From<String> for DecodeError {
+    fn from(message: String) -> Self {
+        DecodeError::Malformed(message)
+    }
+}
+
+/// Decodes a quoted string literal, resolving its escape sequences. Stops
+/// as soon as the decoded text grows past `limit` bytes.
+pub(crate) fn decode_string(
+    literal: &str,
+    limit: Option<usize>,
+) -> std::result::Result<String, DecodeError> {
+    let mut out = String::new();
+    decode_into(literal, limit, &mut out)?;
+    Ok(out)
+}
+
+/// Like [`decode_string`], appending to `out`. Text between escapes is
+/// copied a run at a time.
+pub(crate) fn decode_into(
+    literal: &str,
+    limit: Option<usize>,
+    out: &mut String,
+) -> std::result::Result<(), DecodeError> {
+    let body = &literal[1..literal.len() - 1];
+    // Escapes only ever shrink, so a short enough literal cannot go over.
+    let limit = limit.filter(|&max| body.len() > max);
+    let too_long = |out: &String| limit.is_some_and(|max| out.len() > max);
+    out.reserve(body.len());
+    let mut rest = body;
+    while let Some(i) = rest.find('\\') {
+        out.push_str(&rest[..i]);
+        if too_long(out) {
+            return Err(DecodeError::TooLong);
+        }
+        let mut chars = rest[i + 1..].chars();
+        match chars.next() {
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            Some('/') => out.push('/'),
+            Some('b') => out.push('\u{8}'),
+            Some('f') => out.push('\u{c}'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('u') => {
+                let high = read_hex4(&mut chars)?;
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    if chars.next() != Some('\\') || chars.next() != Some('u') {
+                        return Err(format!("Unpaired surrogate \\u{high:04X} in {literal}").into());
+                    }
+                    let low = read_hex4(&mut chars)?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(
+                            format!("Invalid low surrogate \\u{low:04X} in {literal}").into()
+                        );
+                    }
+                    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    high
+                };
+                out.push(
+                    char::from_u32(code)
+                        .ok_or_else(|| format!("Invalid escape \\u{code:04X} in {literal}"))?,
+                );
+            }
+            e => return Err(format!("Invalid escape {e:?} in {literal}").into()),
+        }
+        rest = chars.as_str();
+    }
+    out.push_str(rest);
+    if too_long(out) {
+        return Err(DecodeError::TooLong);
+    }
+    Ok(())
+}
+
+fn read_hex4(chars: &mut std::str::Chars) -> std::result::Result<u32, String> {
+    let hex: String = chars.take(4).collect();
+    if hex.len() != 4 {
+        return Err("Truncated \\u escape".to_string());
+    }
+    // `from_str_radix` would also accept a sign, as in `\u+0e9`.
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("Invalid \\u escape {hex:?}"));
+    }
+    Ok(u32::from_str_radix(&hex, 16).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn test_parser() -> anyhow::Result<()> {
+        let input = r#"{"key": [42,23, [112, true]], "lala": {"a": [-1e18]}}"#;
+        let mut tokenizer = Tokenizer::new(input.to_string());
+        let tokens = std::iter::from_fn(|| tokenizer.next_spanned());
+        let options = ParserOptions::default();
+        let mut parser: Parser<_> = Parser::new(input, tokens, &options);
+
+        parser.parse()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_objects() -> anyhow::Result<()> {
+        for input in [
+            "{}",
+            r#"{"a": {}}"#,
+            r#"[{}, {"a": 1}, [{"b": {"c": {}}}]]"#,
+            r#"{"a": 1, "b": {"c": [{"d": {}}, {}]}, "e": {"f": null}}"#,
+            r#"[[[{"deep": [{}]}]]]"#,
+        ] {
+            let value = crate::from_str(input)?;
+            assert_eq!(value, crate::from_str(&value.to_string())?);
+            crate::validate(input)?;
+        }
+        assert_eq!(crate::from_str("{}")?, JsonValue::empty_object());
+        for input in ["{", "{,}", r#"{"a": 1,}"#, r#"{"a" 1}"#, "[{]", "{}}"] {
+            assert!(crate::from_str(input).is_err(), "{input}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_colon() {
+        for (input, expected) in [
+            (
+                r#"{"a" 1}"#,
+                r#"Expected ':' after object key "a", found Number at line 1, column 6"#,
+            ),
+            (
+                r#"{"a", 1}"#,
+                r#"Expected ':' after object key "a", found ValueSeparator at line 1, column 5"#,
+            ),
+            (
+                "{\"x\": {\n  \"\\u0062\" true}}",
+                r#"Expected ':' after object key "b", found Boolean at line 2, column 12"#,
+            ),
+            (
+                r#"{"a""#,
+                r#"Expected ':' after object key "a", found end of input at line 1, column 5"#,
+            ),
+        ] {
+            let err = crate::from_str(input).unwrap_err();
+            assert!(err.to_string().contains(expected), "{err}");
+            assert_eq!(crate::validate(input).unwrap_err(), err);
+        }
+        // A malformed token after the key is reported as such.
+        let err = crate::from_str(r#"{"a" nul}"#).unwrap_err();
+        assert!(matches!(err, Error::Lex(_)));
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let input = r#"{"a":1,"a":2,"a":3}"#;
+        let parse = |duplicate_keys| {
+            let options = ParserOptions {
+                duplicate_keys,
+                ..Default::default()
+            };
+            crate::from_str_with(input, &options).map(|v| v.to_string())
+        };
+        assert_eq!(parse(DuplicateKeys::First).unwrap(), r#"{"a":1}"#);
+        assert_eq!(parse(DuplicateKeys::Last).unwrap(), r#"{"a":3}"#);
+        assert_eq!(parse(DuplicateKeys::Combine).unwrap(), r#"{"a":[1,2,3]}"#);
+        let err = parse(DuplicateKeys::Error).unwrap_err();
+        assert_eq!(err.position().offset, 7);
+        assert_eq!(
+            crate::from_str(input).unwrap().to_string(),
+            parse(DuplicateKeys::Last).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_duplicate_keys_large_object() -> anyhow::Result<()> {
+        // Past the indexing threshold, with repeats of early and late keys.
+        let mut input: String = (0..1000).map(|i| format!(r#""k{i}":{i},"#)).collect();
+        input = format!(r#"{{{input}"k3":"x","k999":"y","k3":"z"}}"#);
+        let parse = |duplicate_keys| {
+            let options = ParserOptions {
+                duplicate_keys,
+                ..Default::default()
+            };
+            crate::from_str_with(&input, &options)
+        };
+        let last = parse(DuplicateKeys::Last)?;
+        assert!(matches!(&last, JsonValue::Object(members) if members.len() == 1000));
+        assert_eq!(last.get("k3"), Some(&JsonValue::String("z".to_string())));
+        assert_eq!(last.get("k999"), Some(&JsonValue::String("y".to_string())));
+        let first = parse(DuplicateKeys::First)?;
+        assert_eq!(first.get("k3").and_then(JsonValue::as_i64), Some(3));
+        let combined = parse(DuplicateKeys::Combine)?;
+        assert_eq!(
+            combined.get("k3").map(|v| v.to_string()).as_deref(),
+            Some(r#"[3,"x","z"]"#)
+        );
+        let err = parse(DuplicateKeys::Error).unwrap_err();
+        assert!(err.to_string().contains(r#"Duplicate key "k3""#), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_context() {
+        let err = crate::from_str(r#"{"a": 1, "x": [true, null, {"b": [nul]}]}"#).unwrap_err();
+        assert_eq!(
+            err.context(),
+            [
+                r#"object value for key "x""#,
+                "array element 2",
+                r#"object value for key "b""#,
+                "array element 0",
+            ]
+        );
+        assert_eq!(
+            err.to_string(),
+            "lex error: Expecting keyword `null` at line 1, column 35, while parsing \
+             object value for key \"x\" → array element 2 → object value for key \"b\" → \
+             array element 0"
+        );
+    }
+
+    #[test]
+    fn test_decode_string() -> anyhow::Result<()> {
+        let decode = |s| decode_string(s, None).ok();
+        assert_eq!(decode(r#""a\"b\né😀""#).unwrap(), "a\"b\né😀");
+        assert_eq!(decode(r#""\u0041\ud83d\ude00""#).unwrap(), "A😀");
+        assert!(decode(r#""\ud83d""#).is_none());
+        assert!(decode(r#""\u+0e9""#).is_none());
+        assert!(decode(r#""\u-001""#).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_string_length() {
+        let options = ParserOptions {
+            max_string_length: Some(3),
+            ..Default::default()
+        };
+        let parse = |input| crate::from_str_with(input, &options);
+        // Six bytes of source, but only three once decoded.
+        assert!(parse(r#"["\u0041\u0042\u0043"]"#).is_ok());
+        assert!(parse(r#"["abc", {"xyz": 1}]"#).is_ok());
+        let Err(Error::Parse(e)) = parse(r#"{"a": "\u0041\u0042\u0043\u0044"}"#) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(e.position.offset, 6);
+        assert!(parse(r#"{"abcd": 1}"#).is_err());
+        assert!(crate::from_str(r#""abcd""#).is_ok());
+    }
+
+    #[test]
+    fn test_max_key_length() {
+        let options = ParserOptions {
+            max_key_length: Some(4),
+            ..Default::default()
+        };
+        let parse = |input| crate::from_str_with(input, &options);
+        let long_value = format!(r#"{{"abcd": "{}"}}"#, "x".repeat(1000));
+        assert!(parse(&long_value).is_ok());
+        assert!(parse(r#"{"\u0041bcd": 1}"#).is_ok());
+        let err = parse(r#"{"a": {"abcde": 1}}"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parse error: Object key \"abcde\" longer than max_key_length (4 bytes) \
+             at line 1, column 8, while parsing object value for key \"a\""
+        );
+        assert!(crate::validate_with(r#"{"abcde": 1}"#, &options).is_err());
+
+        // Only a prefix of a long key is quoted.
+        let err = parse(&format!(r#"{{"{}": 1}}"#, "k".repeat(10_000))).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(&format!("\"{}…\"", "k".repeat(16)))
+        );
+
+        // The tighter of the two limits reports.
+        let options = ParserOptions {
+            max_string_length: Some(2),
+            ..options
+        };
+        let err = crate::from_str_with(r#"{"abc": 1}"#, &options).unwrap_err();
+        assert!(err.to_string().contains("max_string_length (2 bytes)"));
+    }
+
+    #[test]
+    fn test_normalize_numbers() -> anyhow::Result<()> {
+        let options = ParserOptions {
+            normalize_numbers: true,
+            ..Default::default()
+        };
+        let parse = |input| crate::from_str_with(input, &options);
+        assert_eq!(parse("1.0")?, parse("1")?);
+        assert_eq!(parse("1e0")?, parse("1")?);
+        assert_eq!(
+            parse("[1, 1.0, 1e0, 10e-1, 2.5]")?.to_string(),
+            "[1,1,1,1,2.5]"
+        );
+        assert_ne!(crate::from_str("1.0")?, crate::from_str("1")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_number_text() -> anyhow::Result<()> {
+        let options = ParserOptions {
+            keep_number_text: true,
+            ..Default::default()
+        };
+        let value = crate::from_str_with(r#"{"a": 1.10, "b": [-0, 1E+2, 7]}"#, &options)?;
+        assert_eq!(value.to_string(), r#"{"a":1.10,"b":[-0,1E+2,7]}"#);
+        assert_eq!(value.get("a").and_then(JsonValue::as_f64), Some(1.1));
+        assert_eq!(value.pointer("/b/1").and_then(JsonValue::as_i64), Some(100));
+
+        // What the lenient scanners let through is written back as JSON.
+        let lenient = ParserOptions {
+            fast_numbers: true,
+            allow_leading_zeros: true,
+            ..options.clone()
+        };
+        let value = crate::from_str_with("[1., -.5, 1.e5, 007, 1.50]", &lenient)?;
+        let text = value.to_string();
+        assert_eq!(text, "[1.0,-0.5,100000.0,7,1.50]");
+        assert_eq!(crate::from_str_with(&text, &options)?.to_string(), text);
+        assert!(crate::from_str_with("[1.e400]", &lenient).is_err());
+
+        let options = ParserOptions {
+            normalize_numbers: true,
+            ..options
+        };
+        assert_eq!(
+            crate::from_str_with("[1.10, 1e2]", &options)?.to_string(),
+            "[1.1,100]"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_sanitizer() -> anyhow::Result<()> {
+        let parse = |string_sanitizer, input| {
+            let options = ParserOptions {
+                string_sanitizer,
+                ..Default::default()
+            };
+            crate::from_str_with(input, &options)
+        };
+        let input = r#"["\u0000", "a\uFFFEb", "ok"]"#;
+        assert_eq!(
+            parse(StringSanitizer::Accept, input)?,
+            crate::from_str(input)?
+        );
+        assert_eq!(
+            parse(StringSanitizer::Replace, input)?.to_string(),
+            "[\"\u{FFFD}\",\"a\u{FFFD}b\",\"ok\"]"
+        );
+        let err = parse(StringSanitizer::Reject, r#"{"k": "\u0000"}"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parse error: String contains restricted character U+0000 at line 1, column 7, \
+             while parsing object value for key \"k\""
+        );
+        // Unescaped, in keys, and beyond the first plane.
+        for input in [
+            "[\"\u{fdd0}\"]",
+            "{\"\\uDBFF\\uDFFF\": 1}",
+            "\"\u{10fffe}\"",
+        ] {
+            assert!(parse(StringSanitizer::Reject, input).is_err(), "{input}");
+            let options = ParserOptions {
+                string_sanitizer: StringSanitizer::Reject,
+                ..Default::default()
+            };
+            assert!(crate::validate_with(input, &options).is_err(), "{input}");
+        }
+        assert!(parse(StringSanitizer::Reject, r#"["\uFFFD", "\uFDCF"]"#).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_escaped_keys() {
+        let options = ParserOptions {
+            duplicate_keys: DuplicateKeys::Error,
+            ..Default::default()
+        };
+        assert!(crate::validate_with(r#"{"\u0041": 1, "\u0042": 2}"#, &options).is_ok());
+        assert!(crate::validate_with(r#"{"\u0041": 1, "A": 2}"#, &options).is_err());
+    }
+
+    #[test]
+    fn test_cancel() {
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicBool;
+        use std::time::{Duration, Instant};
+
+        let input = format!("[{}0]", "{\"a\": [1, 2]},".repeat(1_000_000));
+        let flag = Arc::new(AtomicBool::new(false));
+        let options = ParserOptions {
+            cancel: Some(flag.clone()),
+            ..Default::default()
+        };
+        assert!(crate::from_str_with("[1, 2]", &options).is_ok());
+        let canceller = std::thread::spawn({
+            let flag = flag.clone();
+            move || {
+                std::thread::sleep(Duration::from_millis(20));
+                flag.store(true, Ordering::Relaxed);
+            }
+        });
+        let Err(Error::Cancelled(position)) = crate::from_str_with(&input, &options) else {
+            panic!("expected the parse to be cancelled");
+        };
+        canceller.join().unwrap();
+        assert!(position.offset > 0 && position.offset < input.len());
+        assert!(matches!(
+            crate::validate_with(&input, &options),
+            Err(Error::Cancelled(_))
+        ));
+
+        let options = ParserOptions {
+            deadline: Some(Instant::now()),
+            ..Default::default()
+        };
+        let err = crate::from_str_with(&input, &options).unwrap_err();
+        assert!(err.position().offset < CANCEL_CHECK_INTERVAL * 8);
+        assert!(err.context().is_empty());
+        assert_eq!(
+            err.to_string(),
+            format!("Parsing cancelled at {}", err.position())
+        );
+        assert_eq!(
+            err.to_json().get("kind"),
+            Some(&JsonValue::from("cancelled"))
+        );
+    }
+
+    #[test]
+    fn test_max_container_entries() {
+        let options = ParserOptions {
+            max_container_entries: Some(3),
+            ..Default::default()
+        };
+        let parse = |input| crate::from_str_with(input, &options);
+        assert!(parse(r#"[1, 2, {"a": [], "b": 1, "c": [4, 5, 6]}]"#).is_ok());
+        let err = parse(r#"{"a": [{"b": [1, 2, 3, 4]}]}"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parse error: Array has more than 3 entries at line 1, column 24, while parsing \
+             object value for key \"a\" → array element 0 → object value for key \"b\""
+        );
+        assert!(parse(r#"{"a": 1, "b": 2, "a": 3, "d": 4}"#).is_err());
+        assert!(crate::from_str(&format!("[{}0]", "0,".repeat(10_000))).is_ok());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::number::Number;
+use crate::parser::decode_string;
+use crate::tokenizer::{Token::*, Tokenizer};
+use crate::value::JsonValue;
+
+/// An open container and what has been read of it.
+enum Frame {
+    Array(Vec<JsonValue>),
+    /// Also holds the key whose value is being read.
+    Object(Vec<(String, JsonValue)>, Option<String>),
+}
+
+/// What the grammar allows next; as in [`transform`](crate::transform).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    ValueOrEnd,
+    KeyOrEnd,
+    Key,
+    Colon,
+    SeparatorOrEnd,
+    Nothing,
+}
+
+/// Parses as much of `input` as it can, for tools such as editor
+/// completion that see documents while they are being typed. Returns the
+/// value read and whether `input` was a complete, valid document, in which
+/// case the value is what [`from_str`](crate::from_str) returns.
+///
+/// Otherwise reading stops at the end of the input or at the first error,
+/// and the best-effort value is built from what came before:
+///
+/// - open arrays and objects are closed, so `[1, [2` gives `[1,[2]]`;
+/// - a member without a complete value is dropped, so `{"a":1,"b":` and
+///   `{"a":1,"b` both give `{"a":1}`;
+/// - an incomplete string, number or keyword is dropped, so `[1, "ab` and
+///   `[1, tru` both give `[1]`, but a number that merely may go on is kept:
+///   `[1, 2` gives `[1,2]`;
+/// - a trailing comma is ignored;
+/// - if not even the start of a value was read, the result is `null`.
+///
+/// An error other than truncation, such as the `2` in `[1 2]`, ends the
+/// input just the same. Repeated keys keep the last value, as by default.
+pub fn parse_partial(input: &str) -> (JsonValue, bool) {
+    let mut tokenizer = Tokenizer::borrowed(input);
+    let mut stack = Vec::new();
+    let mut root = None;
+    let mut expect = Expect::Value;
+    let mut complete = true;
+    while let Some(next) = tokenizer.next_spanned() {
+        let Ok((token, span)) = next else {
+            complete = false;
+            break;
+        };
+        let text = &input[span.start..span.end];
+        let in_object = matches!(stack.last(), Some(Frame::Object(..)));
+        let in_array = matches!(stack.last(), Some(Frame::Array(_)));
+        let value = match (expect, token) {
+            (Expect::Value | Expect::ValueOrEnd, BeginArray) => {
+                stack.push(Frame::Array(Vec::new()));
+                expect = Expect::ValueOrEnd;
+                continue;
+            }
+            (Expect::Value | Expect::ValueOrEnd, BeginObject) => {
+                stack.push(Frame::Object(Vec::new(), None));
+                expect = Expect::KeyOrEnd;
+                continue;
+            }
+            (Expect::KeyOrEnd | Expect::Key, Stringy) => {
+                let (Ok(key), Some(Frame::Object(_, pending))) =
+                    (decode_string(text, None), stack.last_mut())
+                else {
+                    complete = false;
+                    break;
+                };
+                *pending = Some(key);
+                expect = Expect::Colon;
+                continue;
+            }
+            (Expect::Colon, NameSeparator) => {
+                expect = Expect::Value;
+                continue;
+            }
+            (Expect::SeparatorOrEnd, ValueSeparator) => {
+                expect = if in_object {
+                    Expect::Key
+                } else {
+                    Expect::Value
+                };
+                continue;
+            }
+            (Expect::KeyOrEnd | Expect::SeparatorOrEnd, EndObject) if in_object => {
+                close(stack.pop().unwrap())
+            }
+            (Expect::ValueOrEnd | Expect::SeparatorOrEnd, EndArray) if in_array => {
+                close(stack.pop().unwrap())
+            }
+            (Expect::Value | Expect::ValueOrEnd, Number) => match Number::from_literal(text) {
+                Some(n) => JsonValue::Number(n),
+                None => {
+                    complete = false;
+                    break;
+                }
+            },
+            (Expect::Value | Expect::ValueOrEnd, Stringy) => match decode_string(text, None) {
+                Ok(s) => JsonValue::String(s),
+                Err(_) => {
+                    complete = false;
+                    break;
+                }
+            },
+            (Expect::Value | Expect::ValueOrEnd, Boolean) => JsonValue::Bool(text == "true"),
+            (Expect::Value | Expect::ValueOrEnd, Null) => JsonValue::Null,
+            _ => {
+                complete = false;
+                break;
+            }
+        };
+        expect = place(&mut stack, &mut root, value);
+    }
+    if expect != Expect::Nothing {
+        complete = false;
+    }
+    // Close whatever is still open, innermost first.
+    while let Some(frame) = stack.pop() {
+        place(&mut stack, &mut root, close(frame));
+    }
+    (root.unwrap_or_default(), complete)
+}
+
+fn close(frame: Frame) -> JsonValue {
+    match frame {
+        Frame::Array(elements) => JsonValue::Array(elements),
+        Frame::Object(members, _) => JsonValue::Object(members),
+    }
+}
+
+/// Adds a finished value to the innermost open container, or makes it the
+/// root.
+fn place(stack: &mut [Frame], root: &mut Option<JsonValue>, value: JsonValue) -> Expect {
+    match stack.last_mut() {
+        None => {
+            *root = Some(value);
+            Expect::Nothing
+        }
+        Some(Frame::Array(elements)) => {
+            elements.push(value);
+            Expect::SeparatorOrEnd
+        }
+        Some(Frame::Object(members, pending)) => {
+            // Always set: a value, or a container that gets closed early,
+            // only starts after a key.
+            if let Some(key) = pending.take() {
+                match members.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, existing)) => *existing = value,
+                    None => members.push((key, value)),
+                }
+            }
+            Expect::SeparatorOrEnd
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_parse_partial() -> anyhow::Result<()> {
+        let partial = |input| {
+            let (value, complete) = parse_partial(input);
+            (value.to_string(), complete)
+        };
+        for (input, expected) in [
+            (r#"{"a":1,"b":"#, r#"{"a":1}"#),
+            (r#"{"a":1,"b"#, r#"{"a":1}"#),
+            (r#"{"a":1,"b""#, r#"{"a":1}"#),
+            (r#"{"a":1,"#, r#"{"a":1}"#),
+            (r#"[1, [2, {"x": "ab"#, r#"[1,[2,{}]]"#),
+            (r#"{"a": [true, nul"#, r#"{"a":[true]}"#),
+            (r#"[1, 2"#, r#"[1,2]"#),
+            (r#"[1, 2e"#, r#"[1]"#),
+            (r#"{"a": {"b": [], "#, r#"{"a":{"b":[]}}"#),
+            (r#"[1 2]"#, r#"[1]"#),
+            (r#"[1}"#, r#"[1]"#),
+            (r#""unterminated"#, "null"),
+            ("", "null"),
+            ("1 2", "1"),
+        ] {
+            assert_eq!(partial(input), (expected.to_string(), false), "{input}");
+        }
+
+        for input in [r#"{"a": [1, {"b": null}], "a": "x\n"}"#, "[]", "0"] {
+            let (value, complete) = parse_partial(input);
+            assert!(complete, "{input}");
+            assert_eq!(value, from_str(input)?);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+use crate::backing::Sorted;
+use crate::pointer::{index, tokens};
+use crate::value::JsonValue;
+use anyhow::{Result, anyhow, bail};
+
+impl JsonValue {
+    /// Applies an RFC 7386 JSON Merge Patch, as sent to HTTP `PATCH`
+    /// endpoints with `application/merge-patch+json`.
+    ///
+    /// An object patch is merged member by member: a `null` removes the
+    /// key, anything else is merged into the key's current value, and keys
+    /// the patch doesn't mention are left alone. Any other patch, arrays
+    /// included, replaces the target wholesale; a non-object target of an
+    /// object patch is first replaced by `{}`. New keys are appended after
+    /// the existing members. Recurses once per level of the patch.
+    pub fn merge_patch(&mut self, patch: &JsonValue) {
+        let JsonValue::Object(patch_members) = patch else {
+            *self = patch.clone();
+            return;
+        };
+        if !matches!(self, JsonValue::Object(_)) {
+            *self = JsonValue::empty_object();
+        }
+        let JsonValue::Object(members) = self else {
+            unreachable!()
+        };
+        for (key, value) in patch_members {
+            if *value == JsonValue::Null {
+                members.retain(|(k, _)| k != key);
+                continue;
+            }
+            let target = match members.iter().position(|(k, _)| k == key) {
+                Some(i) => &mut members[i].1,
+                None => {
+                    members.push((key.clone(), JsonValue::Null));
+                    &mut members.last_mut().unwrap().1
+                }
+            };
+            target.merge_patch(value);
+        }
+    }
+
+    /// Applies an RFC 6902 JSON Patch: an array of operations such as
+    /// `{"op": "add", "path": "/a/-", "value": 1}`, run in order. All six
+    /// operations are supported: `add`, `remove`, `replace`, `move`, `copy`
+    /// and `test`.
+    ///
+    /// The patch applies as a whole or not at all: if an operation fails,
+    /// whether malformed, pointing at a missing path or a `test` that
+    /// doesn't match, `self` is left unchanged and the error names the
+    /// operation by its index. As the RFC asks, `test` compares numbers by
+    /// value and objects regardless of member order.
+    pub fn apply_patch(&mut self, patch: &JsonValue) -> Result<()> {
+        let JsonValue::Array(operations) = patch else {
+            bail!("Expected a patch array, found {}", patch.type_name());
+        };
+        let mut doc = self.clone();
+        for (i, operation) in operations.iter().enumerate() {
+            let op = operation.get("op").and_then(JsonValue::as_str);
+            apply_operation(&mut doc, operation).map_err(|e| match op {
+                Some(op) => anyhow!("Patch operation {i} ({op}) failed: {e}"),
+                None => anyhow!("Patch operation {i} failed: {e}"),
+            })?;
+        }
+        *self = doc;
+        Ok(())
+    }
+}
+
+fn apply_operation(doc: &mut JsonValue, operation: &JsonValue) -> Result<()> {
+    let member = |name| {
+        operation
+            .get(name)
+            .ok_or_else(|| anyhow!("missing {name:?}"))
+    };
+    let pointer = |name| -> Result<(&str, Vec<String>)> {
+        let ptr = member(name)?
+            .as_str()
+            .ok_or_else(|| anyhow!("{name:?} is not a string"))?;
+        let tokens = tokens(ptr).ok_or_else(|| anyhow!("invalid JSON Pointer {ptr:?}"))?;
+        Ok((ptr, tokens))
+    };
+    let op = member("op")?
+        .as_str()
+        .ok_or_else(|| anyhow!("\"op\" is not a string"))?;
+    let (path, at) = pointer("path")?;
+    match op {
+        "add" => add(doc, path, &at, member("value")?.clone()),
+        "remove" => remove(doc, path, &at).map(drop),
+        "replace" => {
+            *resolve(doc, path, &at)? = member("value")?.clone();
+            Ok(())
+        }
+        "move" => {
+            let (from_path, from) = pointer("from")?;
+            if at.len() > from.len() && at.starts_with(&from) {
+                bail!("cannot move {from_path:?} into its own child {path:?}");
+            }
+            let value = remove(doc, from_path, &from)?;
+            add(doc, path, &at, value)
+        }
+        "copy" => {
+            let (from_path, from) = pointer("from")?;
+            let value = resolve(doc, from_path, &from)?.clone();
+            add(doc, path, &at, value)
+        }
+        "test" => {
+            // Compared as the RFC says: numbers by value, objects by key.
+            let canonical = |value: &JsonValue| {
+                let mut value = value.clone();
+                value.map_values(|node| {
+                    if let JsonValue::Number(n) = node {
+                        *n = n.clone().normalized();
+                    }
+                });
+                value.to_backing::<Sorted>()
+            };
+            let found = resolve(doc, path, &at)?;
+            let expected = member("value")?;
+            if canonical(found) != canonical(expected) {
+                bail!("value at {path:?} is {found}, expected {expected}");
+            }
+            Ok(())
+        }
+        other => bail!("unknown operation {other:?}"),
+    }
+}
+
+/// The value at `tokens`, which must exist.
+fn resolve<'a>(doc: &'a mut JsonValue, path: &str, tokens: &[String]) -> Result<&'a mut JsonValue> {
+    tokens
+        .iter()
+        .try_fold(doc, |value, token| match value {
+            JsonValue::Object(_) => value.get_mut(token),
+            JsonValue::Array(elements) => elements.get_mut(index(token)?),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("path {path:?} does not exist"))
+}
+
+fn add(doc: &mut JsonValue, path: &str, tokens: &[String], value: JsonValue) -> Result<()> {
+    let Some((last, parent)) = tokens.split_last() else {
+        *doc = value;
+        return Ok(());
+    };
+    match resolve(doc, path, parent)? {
+        JsonValue::Object(members) => match members.iter().position(|(k, _)| k == last) {
+            Some(i) => members[i].1 = value,
+            None => members.push((last.clone(), value)),
+        },
+        JsonValue::Array(elements) => match last.as_str() {
+            "-" => elements.push(value),
+            token => match index(token) {
+                Some(i) if i <= elements.len() => elements.insert(i, value),
+                _ => bail!("index {token:?} is out of bounds at {path:?}"),
+            },
+        },
+        other => bail!("cannot add to a {} at {path:?}", other.type_name()),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut JsonValue, path: &str, tokens: &[String]) -> Result<JsonValue> {
+    let Some((last, parent)) = tokens.split_last() else {
+        bail!("cannot remove the whole document");
+    };
+    let removed = match resolve(doc, path, parent)? {
+        JsonValue::Object(members) => members
+            .iter()
+            .position(|(k, _)| k == last)
+            .map(|i| members.remove(i).1),
+        JsonValue::Array(elements) => index(last)
+            .filter(|&i| i < elements.len())
+            .map(|i| elements.remove(i)),
+        _ => None,
+    };
+    removed.ok_or_else(|| anyhow!("path {path:?} does not exist"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::from_str;
+
+    #[test]
+    fn test_apply_patch() -> anyhow::Result<()> {
+        let patched = |target: &str, patch: &str| -> anyhow::Result<String> {
+            let mut value = from_str(target)?;
+            value.apply_patch(&from_str(patch)?)?;
+            Ok(value.to_string())
+        };
+        let target = r#"{"a": {"b": [1, 2]}, "c": "x"}"#;
+        for (patch, result) in [
+            (
+                r#"[{"op": "add", "path": "/a/b/1", "value": 9}, {"op": "add", "path": "/a/b/-", "value": 3}]"#,
+                r#"{"a":{"b":[1,9,2,3]},"c":"x"}"#,
+            ),
+            (
+                r#"[{"op": "add", "path": "/d~1e", "value": {}}, {"op": "add", "path": "/c", "value": null}]"#,
+                r#"{"a":{"b":[1,2]},"c":null,"d/e":{}}"#,
+            ),
+            (
+                r#"[{"op": "remove", "path": "/a/b/0"}, {"op": "remove", "path": "/c"}]"#,
+                r#"{"a":{"b":[2]}}"#,
+            ),
+            (
+                r#"[{"op": "replace", "path": "/a/b", "value": true}]"#,
+                r#"{"a":{"b":true},"c":"x"}"#,
+            ),
+            (
+                r#"[{"op": "move", "from": "/a/b", "path": "/b"}]"#,
+                r#"{"a":{},"c":"x","b":[1,2]}"#,
+            ),
+            (
+                r#"[{"op": "copy", "from": "/a/b/1", "path": "/a/b/0"}]"#,
+                r#"{"a":{"b":[2,1,2]},"c":"x"}"#,
+            ),
+            (
+                r#"[{"op": "test", "path": "/a", "value": {"b": [1.0, 2]}}, {"op": "test", "path": "", "value": {"c": "x", "a": {"b": [1, 2]}}}]"#,
+                r#"{"a":{"b":[1,2]},"c":"x"}"#,
+            ),
+            (r#"[{"op": "replace", "path": "", "value": 5}]"#, "5"),
+        ] {
+            assert_eq!(patched(target, patch)?, result, "{patch}");
+        }
+
+        for (patch, message) in [
+            (
+                r#"[{"op": "test", "path": "/c", "value": "x"}, {"op": "test", "path": "/c", "value": "y"}]"#,
+                r#"Patch operation 1 (test) failed: value at "/c" is "x", expected "y""#,
+            ),
+            (
+                r#"[{"op": "remove", "path": "/z"}]"#,
+                r#"Patch operation 0 (remove) failed: path "/z" does not exist"#,
+            ),
+            (
+                r#"[{"op": "add", "path": "/a/b/5", "value": 0}]"#,
+                r#"Patch operation 0 (add) failed: index "5" is out of bounds at "/a/b/5""#,
+            ),
+            (
+                r#"[{"op": "move", "from": "/a", "path": "/a/x"}]"#,
+                r#"Patch operation 0 (move) failed: cannot move "/a" into its own child "/a/x""#,
+            ),
+            (
+                r#"[{"op": "copy", "path": "/x"}]"#,
+                r#"Patch operation 0 (copy) failed: missing "from""#,
+            ),
+            (
+                r#"[{"op": "frob", "path": "/x"}]"#,
+                r#"Patch operation 0 (frob) failed: unknown operation "frob""#,
+            ),
+            (
+                r#"[{"path": "/x"}]"#,
+                r#"Patch operation 0 failed: missing "op""#,
+            ),
+        ] {
+            assert_eq!(patched(target, patch).unwrap_err().to_string(), message);
+        }
+
+        // A failing operation undoes the ones before it.
+        let mut value = from_str(target)?;
+        let patch =
+            from_str(r#"[{"op": "remove", "path": "/c"}, {"op": "remove", "path": "/c"}]"#)?;
+        assert!(value.apply_patch(&patch).is_err());
+        assert_eq!(value, from_str(target)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_patch() -> anyhow::Result<()> {
+        // The examples of RFC 7386, appendix A.
+        for (target, patch, result) in [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (
+                r#"{"a":{"b":"c"}}"#,
+                r#"{"a":{"b":"d","c":null}}"#,
+                r#"{"a":{"b":"d"}}"#,
+            ),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, "null", "null"),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (
+                r#"{}"#,
+                r#"{"a":{"bb":{"ccc":null}}}"#,
+                r#"{"a":{"bb":{}}}"#,
+            ),
+        ] {
+            let mut value = from_str(target)?;
+            value.merge_patch(&from_str(patch)?);
+            assert_eq!(value.to_string(), result, "{target} + {patch}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+use crate::backing::ObjectBacking;
+use crate::value::JsonValue;
+use anyhow::{Result, bail};
+
+/// One step of a path for [`JsonValue::deep_get`] and
+/// [`JsonValue::deep_set`]: the ready-split form of a JSON Pointer token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+impl<'a> From<&'a str> for Segment<'a> {
+    fn from(key: &'a str) -> Self {
+        Segment::Key(key)
+    }
+}
+
+impl From<usize> for Segment<'_> {
+    fn from(index: usize) -> Self {
+        Segment::Index(index)
+    }
+}
+
+/// Splits an RFC 6901 pointer into unescaped reference tokens. Returns
+/// `None` for a non-empty pointer that doesn't start with `/`.
+pub(crate) fn tokens(ptr: &str) -> Option<Vec<String>> {
+    if ptr.is_empty() {
+        return Some(Vec::new());
+    }
+    let rest = ptr.strip_prefix('/')?;
+    Some(
+        rest.split('/')
+            .map(|t| t.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Appends `token` to the pointer `path`, escaping it.
+pub(crate) fn push_token(path: &mut String, token: &str) {
+    path.push('/');
+    path.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
+
+/// The pointer for a path of segments.
+pub(crate) fn join(path: &[Segment]) -> String {
+    let mut ptr = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) => push_token(&mut ptr, key),
+            Segment::Index(i) => push_token(&mut ptr, &i.to_string()),
+        }
+    }
+    ptr
+}
+
+/// Parses an array index token: decimal digits without leading zeros.
+pub(crate) fn index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || !token.bytes().all(|b| b.is_ascii_digit())
+        || (token.len() > 1 && token.starts_with('0'))
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+impl<B: ObjectBacking> JsonValue<B> {
+    /// Looks up a value by JSON Pointer (RFC 6901), e.g. `"/a/0/b"`. The
+    /// empty pointer refers to `self`.
+    pub fn pointer(&self, ptr: &str) -> Option<&JsonValue<B>> {
+        tokens(ptr)?
+            .iter()
+            .try_fold(self, |value, token| match value {
+                JsonValue::Object(_) => value.get(token),
+                JsonValue::Array(elements) => elements.get(index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Like [`pointer`](Self::pointer), with the path already split. A key
+    /// only matches in an object and an index only in an array.
+    pub fn deep_get(&self, path: &[Segment]) -> Option<&JsonValue<B>> {
+        path.iter()
+            .try_fold(self, |value, segment| match (value, segment) {
+                (JsonValue::Object(_), Segment::Key(key)) => value.get(key),
+                (JsonValue::Array(elements), Segment::Index(i)) => elements.get(*i),
+                _ => None,
+            })
+    }
+
+    /// Whether `ptr` refers to a value.
+    pub fn has_pointer(&self, ptr: &str) -> bool {
+        self.pointer(ptr).is_some()
+    }
+
+    /// The value at `ptr`, or `default` if there is none.
+    pub fn pointer_or<'v>(&'v self, ptr: &str, default: &'v JsonValue<B>) -> &'v JsonValue<B> {
+        self.pointer(ptr).unwrap_or(default)
+    }
+
+    // Typed fetches: `None` if `ptr` is missing or refers to another type.
+
+    pub fn pointer_str(&self, ptr: &str) -> Option<&str> {
+        self.pointer(ptr)?.as_str()
+    }
+
+    pub fn pointer_bool(&self, ptr: &str) -> Option<bool> {
+        self.pointer(ptr)?.as_bool()
+    }
+
+    /// See [`Number::as_i64`](crate::Number::as_i64).
+    pub fn pointer_i64(&self, ptr: &str) -> Option<i64> {
+        self.pointer(ptr)?.as_i64()
+    }
+
+    /// See [`Number::as_u64`](crate::Number::as_u64).
+    pub fn pointer_u64(&self, ptr: &str) -> Option<u64> {
+        self.pointer(ptr)?.as_u64()
+    }
+
+    pub fn pointer_f64(&self, ptr: &str) -> Option<f64> {
+        self.pointer(ptr)?.as_f64()
+    }
+}
+
+impl JsonValue {
+    pub fn pointer_mut(&mut self, ptr: &str) -> Option<&mut JsonValue> {
+        tokens(ptr)?
+            .iter()
+            .try_fold(self, |value, token| match value {
+                JsonValue::Object(_) => value.get_mut(token),
+                JsonValue::Array(elements) => elements.get_mut(index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Stores `value` at `path`, creating what is missing on the way: a
+    /// `null` or absent value becomes an object before a key and an array
+    /// before an index, and an array too short for an index is padded with
+    /// `null`s. New keys are appended. Fails, changing nothing, if the path
+    /// runs into a value of another type.
+    pub fn deep_set(&mut self, path: &[Segment], value: JsonValue) -> Result<()> {
+        // Check first, so a failure doesn't leave half-created containers.
+        let mut node = Some(&*self);
+        for (depth, segment) in path.iter().enumerate() {
+            let Some(existing) = node else { break };
+            node = match (existing, segment) {
+                (JsonValue::Null, _) => None,
+                (JsonValue::Object(_), Segment::Key(key)) => existing.get(key),
+                (JsonValue::Array(elements), Segment::Index(i)) => elements.get(*i),
+                _ => {
+                    let expected = match segment {
+                        Segment::Key(_) => "an object",
+                        Segment::Index(_) => "an array",
+                    };
+                    bail!(
+                        "Cannot set a path through {:?}: expected {expected}, found {}",
+                        join(&path[..depth]),
+                        existing.type_name()
+                    );
+                }
+            };
+        }
+
+        let mut node = self;
+        for segment in path {
+            node = match segment {
+                Segment::Key(key) => node.entry(key)?.or_default(),
+                Segment::Index(i) => {
+                    if *node == JsonValue::Null {
+                        *node = JsonValue::empty_array();
+                    }
+                    let JsonValue::Array(elements) = node else {
+                        unreachable!("checked above");
+                    };
+                    if elements.len() <= *i {
+                        elements.resize(i + 1, JsonValue::Null);
+                    }
+                    &mut elements[*i]
+                }
+            };
+        }
+        *node = value;
+        Ok(())
+    }
+
+    /// The value at `ptr`, or `null` if there is none.
+    pub fn pointer_or_default(&self, ptr: &str) -> &JsonValue {
+        static NULL: JsonValue = JsonValue::Null;
+        self.pointer_or(ptr, &NULL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_deep_set() -> anyhow::Result<()> {
+        use Segment::{Index, Key};
+        let mut value = JsonValue::Null;
+        value.deep_set(&[Key("a"), Index(2), Key("b")], JsonValue::from(1i64))?;
+        assert_eq!(value.to_string(), r#"{"a":[null,null,{"b":1}]}"#);
+        value.deep_set(&["a".into(), 0.into()], JsonValue::from("x"))?;
+        value.deep_set(&[Key("c")], JsonValue::Bool(true))?;
+        assert_eq!(value.to_string(), r#"{"a":["x",null,{"b":1}],"c":true}"#);
+        assert_eq!(
+            value.deep_get(&[Key("a"), Index(2), Key("b")]),
+            value.pointer("/a/2/b")
+        );
+        assert_eq!(value.deep_get(&[Key("a"), Key("0")]), None);
+        assert_eq!(value.deep_get(&[]), Some(&value));
+
+        let before = value.clone();
+        let err = value
+            .deep_set(&[Key("a"), Index(0), Key("y"), Key("z")], JsonValue::Null)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"Cannot set a path through "/a/0": expected an object, found string"#
+        );
+        assert!(
+            value
+                .deep_set(&[Key("c"), Index(0)], JsonValue::Null)
+                .is_err()
+        );
+        assert!(value.deep_set(&[Index(0)], JsonValue::Null).is_err());
+        assert_eq!(value, before);
+        value.deep_set(&[], JsonValue::Null)?;
+        assert_eq!(value, JsonValue::Null);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pointer() -> anyhow::Result<()> {
+        let value = from_str(r#"{"a": [10, {"b/c": 1, "d~": null}], "": 2}"#)?;
+        assert_eq!(value.pointer("").unwrap(), &value);
+        assert_eq!(value.pointer("/a/0").unwrap().to_string(), "10");
+        assert_eq!(value.pointer("/a/1/b~1c").unwrap().to_string(), "1");
+        assert_eq!(value.pointer("/").unwrap().to_string(), "2");
+        assert!(value.has_pointer("/a/1/d~0"));
+        assert!(!value.has_pointer("/a/01"));
+        assert!(!value.has_pointer("/a/2"));
+        assert!(!value.has_pointer("a"));
+        assert!(!value.has_pointer("/a/0/x"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed_pointers() -> anyhow::Result<()> {
+        let config = from_str(
+            r#"{"server": {"host": "localhost", "port": 8080, "tls": false, "ratio": 0.5}}"#,
+        )?;
+        assert_eq!(config.pointer_str("/server/host"), Some("localhost"));
+        assert_eq!(config.pointer_i64("/server/port"), Some(8080));
+        assert_eq!(config.pointer_u64("/server/port"), Some(8080));
+        assert_eq!(config.pointer_bool("/server/tls"), Some(false));
+        assert_eq!(config.pointer_f64("/server/ratio"), Some(0.5));
+        // Missing paths.
+        assert_eq!(config.pointer_str("/server/user"), None);
+        assert_eq!(config.pointer_i64("/client/port"), None);
+        assert_eq!(config.pointer_bool("server/tls"), None);
+        // Wrong types.
+        assert_eq!(config.pointer_str("/server/port"), None);
+        assert_eq!(config.pointer_i64("/server/host"), None);
+        assert_eq!(config.pointer_i64("/server/ratio"), None);
+        assert_eq!(config.pointer_bool("/server"), None);
+
+        let fallback = from_str("443")?;
+        assert_eq!(
+            config.pointer_or("/server/port", &fallback).to_string(),
+            "8080"
+        );
+        assert_eq!(config.pointer_or("/server/tls_port", &fallback), &fallback);
+        assert_eq!(config.pointer_or_default("/x"), &crate::JsonValue::Null);
+        Ok(())
+    }
+}
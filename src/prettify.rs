@@ -0,0 +1,278 @@
+use crate::error::{Error, LexError, ParseError, Position};
+use crate::expect::Expect;
+use crate::ser::IndentStyle;
+use crate::tokenizer::{Token, Tokenizer};
+use anyhow::Result;
+use std::io::{BufReader, BufWriter, Bytes, Read, Write};
+
+/// Bytes of the input with the position of the next one.
+struct Input<R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    peeked: Option<u8>,
+    position: Position,
+}
+
+impl<R: Read> Input<R> {
+    fn peek(&mut self) -> Result<Option<u8>> {
+        if self.peeked.is_none() {
+            self.peeked = self.bytes.next().transpose()?;
+        }
+        Ok(self.peeked)
+    }
+
+    fn next(&mut self) -> Result<Option<u8>> {
+        let b = self.peek()?;
+        self.peeked = None;
+        if let Some(b) = b {
+            self.position.offset += 1;
+            if b == b'\n' {
+                self.position.line += 1;
+                self.position.column = 1;
+            } else if b & 0xC0 != 0x80 {
+                // Not a UTF-8 continuation byte, so a new character.
+                self.position.column += 1;
+            }
+        }
+        Ok(b)
+    }
+}
+
+/// Reformats the document read from `r` as pretty JSON written to `w`, in
+/// one pass and without building a tree, so memory use is bounded by the
+/// nesting depth and the longest number literal, not by the input size.
+///
+/// The layout matches [`JsonValue::to_string_pretty`](crate::JsonValue::to_string_pretty)
+/// with `indent`: one member or element per line, `": "` after keys, and
+/// empty containers as `{}` and `[]`. Strings are copied as written,
+/// escapes included, and numbers and literals too.
+///
+/// The structure and the scalars are checked as they are copied, but the
+/// insides of strings are not: their escapes aren't decoded and their bytes
+/// aren't checked to be UTF-8. Errors are an [`Error`], positioned in the
+/// input, or an `io::Error`; on error, `w` holds part of the output. Both
+/// ends are buffered internally.
+pub fn prettify_stream<R: Read, W: Write>(r: R, w: W, indent: IndentStyle) -> Result<()> {
+    let mut input = Input {
+        bytes: BufReader::new(r).bytes(),
+        peeked: None,
+        position: Position {
+            offset: 0,
+            line: 1,
+            column: 1,
+        },
+    };
+    let mut out = BufWriter::new(w);
+    let unit = match indent {
+        IndentStyle::Spaces(n) => " ".repeat(n),
+        IndentStyle::Tab => "\t".to_string(),
+    };
+    let newline = |out: &mut BufWriter<W>, depth: usize| -> std::io::Result<()> {
+        out.write_all(b"\n")?;
+        for _ in 0..depth {
+            out.write_all(unit.as_bytes())?;
+        }
+        Ok(())
+    };
+    let error = |position, message: String| Error::Parse(ParseError::new(message, position));
+
+    // The open containers, as their opening bytes.
+    let mut stack = Vec::new();
+    let mut expect = Expect::Value;
+    let mut literal = Vec::new();
+    loop {
+        let position = input.position;
+        let Some(b) = input.next()? else { break };
+        if matches!(b, b' ' | b'\t' | b'\n' | b'\r') {
+            continue;
+        }
+        // The first member or element of a container goes on a new line.
+        if matches!(expect, Expect::KeyOrEnd | Expect::ValueOrEnd) && !matches!(b, b'}' | b']') {
+            newline(&mut out, stack.len())?;
+        }
+        expect = match (expect, b) {
+            (Expect::Value | Expect::ValueOrEnd, b'{' | b'[') => {
+                out.write_all(&[b])?;
+                stack.push(b);
+                if b == b'{' {
+                    Expect::KeyOrEnd
+                } else {
+                    Expect::ValueOrEnd
+                }
+            }
+            (Expect::KeyOrEnd | Expect::SeparatorOrEnd, b'}') if stack.last() == Some(&b'{') => {
+                stack.pop();
+                if expect == Expect::SeparatorOrEnd {
+                    newline(&mut out, stack.len())?;
+                }
+                out.write_all(b"}")?;
+                after_value(&stack)
+            }
+            (Expect::ValueOrEnd | Expect::SeparatorOrEnd, b']') if stack.last() == Some(&b'[') => {
+                stack.pop();
+                if expect == Expect::SeparatorOrEnd {
+                    newline(&mut out, stack.len())?;
+                }
+                out.write_all(b"]")?;
+                after_value(&stack)
+            }
+            (Expect::SeparatorOrEnd, b',') => {
+                out.write_all(b",")?;
+                newline(&mut out, stack.len())?;
+                if stack.last() == Some(&b'{') {
+                    Expect::Key
+                } else {
+                    Expect::Value
+                }
+            }
+            (Expect::Colon, b':') => {
+                out.write_all(b": ")?;
+                Expect::Value
+            }
+            (Expect::Key | Expect::KeyOrEnd | Expect::Value | Expect::ValueOrEnd, b'"') => {
+                copy_string(&mut input, &mut out, position)?;
+                if matches!(expect, Expect::Key | Expect::KeyOrEnd) {
+                    Expect::Colon
+                } else {
+                    after_value(&stack)
+                }
+            }
+            (Expect::Value | Expect::ValueOrEnd, _) if !b"]},:".contains(&b) => {
+                literal.clear();
+                literal.push(b);
+                while let Some(b) = input.peek()?
+                    && !matches!(b, b' ' | b'\t' | b'\n' | b'\r' | b',' | b':' | b'"')
+                    && !matches!(b, b'[' | b']' | b'{' | b'}')
+                {
+                    literal.push(b);
+                    input.next()?;
+                }
+                check_literal(&literal, position)?;
+                out.write_all(&literal)?;
+                after_value(&stack)
+            }
+            (expect, b) => {
+                let expected = expect.describe(stack.last() == Some(&b'{'));
+                let found = char::from(b);
+                let message = format!("Expecting {expected}. Got {found:?}");
+                return Err(error(position, message).into());
+            }
+        };
+    }
+    if expect != Expect::Nothing {
+        let expected = expect.describe(stack.last() == Some(&b'{'));
+        let message = format!("Expecting {expected}. Got end of input");
+        return Err(error(input.position, message).into());
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn after_value(stack: &[u8]) -> Expect {
+    if stack.is_empty() {
+        Expect::Nothing
+    } else {
+        Expect::SeparatorOrEnd
+    }
+}
+
+/// Copies a string whose opening quote, at `start`, has been read, up to
+/// and including its closing quote.
+fn copy_string<R: Read, W: Write>(
+    input: &mut Input<R>,
+    out: &mut W,
+    start: Position,
+) -> Result<()> {
+    out.write_all(b"\"")?;
+    let mut escaped = false;
+    loop {
+        let Some(b) = input.next()? else {
+            return Err(Error::Lex(LexError::new("Unterminated string", start)).into());
+        };
+        out.write_all(&[b])?;
+        match b {
+            b'"' if !escaped => return Ok(()),
+            b'\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+}
+
+/// Checks that `literal` is exactly one number, `true`, `false` or `null`.
+fn check_literal(literal: &[u8], start: Position) -> Result<()> {
+    let invalid = || {
+        let text = String::from_utf8_lossy(literal);
+        Error::Lex(LexError::new(format!("Invalid literal {text:?}"), start))
+    };
+    let text = std::str::from_utf8(literal).map_err(|_| invalid())?;
+    let mut tokenizer = Tokenizer::borrowed(text);
+    let tokens: Vec<_> = std::iter::from_fn(|| tokenizer.next_spanned())
+        .map(|t| t.map(|(token, _)| token).map_err(|_| invalid()))
+        .collect::<std::result::Result<_, _>>()?;
+    match tokens[..] {
+        [Token::Number | Token::Boolean | Token::Null] => Ok(()),
+        _ => Err(invalid().into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParserOptions, from_str_with};
+
+    fn prettify(input: &str, indent: IndentStyle) -> Result<String> {
+        let mut out = Vec::new();
+        prettify_stream(input.as_bytes(), &mut out, indent)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_prettify_stream() -> Result<()> {
+        let options = ParserOptions {
+            keep_number_text: true,
+            ..Default::default()
+        };
+        for input in [
+            r#"{"a": [1, -2.5e3, {"b": null}], "c": {}, "d": [], "e": [[], {}]}"#,
+            r#"["[{", "}]\"", "a\\", true, false]"#,
+            "\"é ,:\"",
+            " 0 ",
+            r#"{"nested": {"deeper": {"deepest": [[["x"]]]}}}"#,
+        ] {
+            let expected = from_str_with(input, &options)?.to_string_pretty();
+            assert_eq!(
+                prettify(input, IndentStyle::default())?,
+                expected,
+                "{input}"
+            );
+        }
+        assert_eq!(
+            prettify("{ \"a\" :[1,2] }", IndentStyle::Tab)?,
+            "{\n\t\"a\": [\n\t\t1,\n\t\t2\n\t]\n}"
+        );
+        assert_eq!(
+            prettify("[\"\\u00e9\"]", IndentStyle::Spaces(0))?,
+            "[\n\"\\u00e9\"\n]"
+        );
+
+        for (input, message) in [
+            (
+                "[1, 2",
+                "Expecting `,` or `]`. Got end of input at line 1, column 6",
+            ),
+            ("{\"a\" 1}", "Expecting `:`. Got '1' at line 1, column 6"),
+            ("[1,]", "Expecting a value. Got ']' at line 1, column 4"),
+            ("[1}", "Expecting `,` or `]`. Got '}' at line 1, column 3"),
+            ("\n  [tru]", "Invalid literal \"tru\" at line 2, column 4"),
+            ("[1x]", "Invalid literal \"1x\" at line 1, column 2"),
+            ("[\"a]", "Unterminated string at line 1, column 2"),
+            ("1 2", "Expecting end of input"),
+            ("", "Expecting a value. Got end of input"),
+            ("{\"a\": 1]", "Expecting `,` or `}`. Got ']'"),
+            ("{,}", "Expecting an object key or `}`. Got ','"),
+        ] {
+            let err = prettify(input, IndentStyle::default()).unwrap_err();
+            assert!(err.to_string().contains(message), "{input}: {err}");
+        }
+        Ok(())
+    }
+}
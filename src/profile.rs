@@ -0,0 +1,114 @@
+use crate::pointer::push_token;
+use crate::value::JsonValue;
+
+/// What [`JsonValue::profile`] found at one leaf.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeafKind {
+    Null,
+    Bool,
+    /// `integral` if the number is whole, however it was written (`2.0`
+    /// counts, as for [`SchemaType::Integer`](crate::SchemaType::Integer)).
+    Number {
+        integral: bool,
+    },
+    /// `length` is in characters, not bytes.
+    String {
+        length: usize,
+    },
+    /// Empty containers have no children, so they are leaves too.
+    EmptyArray,
+    EmptyObject,
+}
+
+/// One leaf of a value: a scalar or an empty container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeafInfo {
+    /// JSON Pointer to the leaf; empty if the root is itself a leaf.
+    pub path: String,
+    pub kind: LeafKind,
+}
+
+impl JsonValue {
+    /// Every leaf with its path and kind, in document order, for data
+    /// profiling. Unlike [`infer_schema`](crate::infer_schema), nothing is
+    /// merged: each array element is reported at its own index. Uses an
+    /// explicit stack, so deep trees cannot overflow the call stack.
+    pub fn profile(&self) -> Vec<LeafInfo> {
+        let mut leaves = Vec::new();
+        let mut stack = vec![(self, String::new())];
+        while let Some((node, path)) = stack.pop() {
+            let kind = match node {
+                JsonValue::Null => LeafKind::Null,
+                JsonValue::Bool(_) => LeafKind::Bool,
+                JsonValue::Number(n) => LeafKind::Number {
+                    integral: n.as_f64().fract() == 0.0,
+                },
+                JsonValue::String(s) => LeafKind::String {
+                    length: s.chars().count(),
+                },
+                JsonValue::Array(elements) if elements.is_empty() => LeafKind::EmptyArray,
+                JsonValue::Object(members) if members.is_empty() => LeafKind::EmptyObject,
+                JsonValue::Array(elements) => {
+                    for (i, element) in elements.iter().enumerate().rev() {
+                        let mut child = path.clone();
+                        push_token(&mut child, &i.to_string());
+                        stack.push((element, child));
+                    }
+                    continue;
+                }
+                JsonValue::Object(members) => {
+                    for (key, member) in members.iter().rev() {
+                        let mut child = path.clone();
+                        push_token(&mut child, key);
+                        stack.push((member, child));
+                    }
+                    continue;
+                }
+            };
+            leaves.push(LeafInfo { path, kind });
+        }
+        leaves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_profile() -> anyhow::Result<()> {
+        let value = from_str(
+            r#"{"a": [1, 2.5, 3.0], "é/x": "héllo", "b": {"c": null, "d": [], "e": {}}, "f": true}"#,
+        )?;
+        let leaves: Vec<_> = value
+            .profile()
+            .into_iter()
+            .map(|leaf| (leaf.path, leaf.kind))
+            .collect();
+        assert_eq!(
+            leaves,
+            [
+                ("/a/0".to_string(), LeafKind::Number { integral: true }),
+                ("/a/1".to_string(), LeafKind::Number { integral: false }),
+                ("/a/2".to_string(), LeafKind::Number { integral: true }),
+                ("/é~1x".to_string(), LeafKind::String { length: 5 }),
+                ("/b/c".to_string(), LeafKind::Null),
+                ("/b/d".to_string(), LeafKind::EmptyArray),
+                ("/b/e".to_string(), LeafKind::EmptyObject),
+                ("/f".to_string(), LeafKind::Bool),
+            ]
+        );
+        for leaf in value.profile() {
+            assert!(value.pointer(&leaf.path).is_some());
+        }
+        assert_eq!(
+            from_str("7")?.profile(),
+            [LeafInfo {
+                path: String::new(),
+                kind: LeafKind::Number { integral: true },
+            }]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use crate::value::JsonValue;
+
+/// What [`JsonValue::prune`] removes. The default removes nothing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneOptions {
+    pub nulls: bool,
+    pub empty_objects: bool,
+    pub empty_arrays: bool,
+}
+
+impl PruneOptions {
+    fn removes(&self, value: &JsonValue) -> bool {
+        match value {
+            JsonValue::Null => self.nulls,
+            JsonValue::Object(members) => self.empty_objects && members.is_empty(),
+            JsonValue::Array(elements) => self.empty_arrays && elements.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+/// A container being rebuilt: its children not yet pruned, and the ones
+/// kept so far.
+enum Frame {
+    Array(std::vec::IntoIter<JsonValue>, Vec<JsonValue>),
+    /// Also holds the key of the member being pruned.
+    Object(
+        std::vec::IntoIter<(String, JsonValue)>,
+        Vec<(String, JsonValue)>,
+        String,
+    ),
+}
+
+impl JsonValue {
+    /// Removes the object members and array elements that `options` selects,
+    /// e.g. to tidy an API response before passing it on.
+    ///
+    /// Works bottom-up: a container emptied by pruning is itself removed if
+    /// empty containers of its kind are. The root is never removed, only
+    /// emptied. Uses an explicit stack, so deep trees cannot overflow the
+    /// call stack.
+    pub fn prune(&mut self, options: PruneOptions) {
+        let mut stack = Vec::new();
+        let mut current = std::mem::take(self);
+        loop {
+            // Open containers down to the first unpruned leaf.
+            match current {
+                JsonValue::Array(elements) if !elements.is_empty() => {
+                    let kept = Vec::with_capacity(elements.len());
+                    let mut rest = elements.into_iter();
+                    current = rest.next().unwrap();
+                    stack.push(Frame::Array(rest, kept));
+                    continue;
+                }
+                JsonValue::Object(members) if !members.is_empty() => {
+                    let kept = Vec::with_capacity(members.len());
+                    let mut rest = members.into_iter();
+                    let (key, value) = rest.next().unwrap();
+                    current = value;
+                    stack.push(Frame::Object(rest, kept, key));
+                    continue;
+                }
+                _ => {}
+            }
+            // `current` is pruned: hand it to its parent, closing every
+            // container that has no children left to prune.
+            loop {
+                let Some(frame) = stack.last_mut() else {
+                    *self = current;
+                    return;
+                };
+                let keep = !options.removes(&current);
+                match frame {
+                    Frame::Array(rest, kept) => {
+                        if keep {
+                            kept.push(current);
+                        }
+                        if let Some(next) = rest.next() {
+                            current = next;
+                            break;
+                        }
+                        current = JsonValue::Array(std::mem::take(kept));
+                    }
+                    Frame::Object(rest, kept, key) => {
+                        if keep {
+                            kept.push((std::mem::take(key), current));
+                        }
+                        if let Some((next_key, next)) = rest.next() {
+                            *key = next_key;
+                            current = next;
+                            break;
+                        }
+                        current = JsonValue::Object(std::mem::take(kept));
+                    }
+                }
+                stack.pop();
+            }
+        }
+    }
+
+    /// Keeps only the object members for which `f` returns `true`, like
+    /// `Vec::retain`. Does nothing to non-objects.
+    pub fn retain_keys<F: FnMut(&str, &JsonValue) -> bool>(&mut self, mut f: F) {
+        if let JsonValue::Object(members) = self {
+            members.retain(|(k, v)| f(k, v));
+        }
+    }
+
+    /// Keeps only the array elements for which `f` returns `true`. Does
+    /// nothing to non-arrays.
+    pub fn retain<F: FnMut(&JsonValue) -> bool>(&mut self, f: F) {
+        if let JsonValue::Array(elements) = self {
+            elements.retain(f);
+        }
+    }
+
+    /// [`retain_keys`](Self::retain_keys) on every object in the tree, e.g.
+    /// to drop private fields everywhere. Containers are filtered before
+    /// their children, so `f` sees members whose own contents haven't been
+    /// filtered yet, and removed members are never visited.
+    pub fn retain_keys_recursive<F: FnMut(&str, &JsonValue) -> bool>(&mut self, mut f: F) {
+        self.map_values(|node| node.retain_keys(&mut f));
+    }
+
+    /// [`retain`](Self::retain) on every array in the tree, in the same
+    /// order as [`retain_keys_recursive`](Self::retain_keys_recursive).
+    pub fn retain_recursive<F: FnMut(&JsonValue) -> bool>(&mut self, mut f: F) {
+        self.map_values(|node| node.retain(&mut f));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_prune() -> anyhow::Result<()> {
+        let input =
+            r#"{"a": null, "b": {"c": {"d": null}, "e": [null, []]}, "f": [1, null, {}], "g": 0}"#;
+        let pruned = |options| -> anyhow::Result<String> {
+            let mut value = from_str(input)?;
+            value.prune(options);
+            Ok(value.to_string())
+        };
+        assert_eq!(pruned(PruneOptions::default())?, input.replace(' ', ""));
+        let nulls = PruneOptions {
+            nulls: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            pruned(nulls)?,
+            r#"{"b":{"c":{},"e":[[]]},"f":[1,{}],"g":0}"#
+        );
+        let everything = PruneOptions {
+            nulls: true,
+            empty_objects: true,
+            empty_arrays: true,
+        };
+        assert_eq!(pruned(everything)?, r#"{"f":[1],"g":0}"#);
+
+        let mut value = from_str(r#"{"a": {"b": null}}"#)?;
+        value.prune(everything);
+        assert_eq!(value, JsonValue::empty_object());
+        Ok(())
+    }
+
+    #[test]
+    fn test_retain() -> anyhow::Result<()> {
+        let input = r#"{"_id": 1, "name": "a", "items": [{"_rev": 2, "x": [1, "s", {"_y": 0}]}], "_meta": {"k": 1}}"#;
+        let public = |k: &str, _: &JsonValue| !k.starts_with('_');
+        let mut value = from_str(input)?;
+        value.retain_keys(public);
+        assert_eq!(
+            value.to_string(),
+            r#"{"name":"a","items":[{"_rev":2,"x":[1,"s",{"_y":0}]}]}"#
+        );
+        let mut value = from_str(input)?;
+        value.retain_keys_recursive(public);
+        assert_eq!(
+            value.to_string(),
+            r#"{"name":"a","items":[{"x":[1,"s",{}]}]}"#
+        );
+        value.retain_recursive(|v| !matches!(v, JsonValue::String(_)));
+        assert_eq!(value.to_string(), r#"{"name":"a","items":[{"x":[1,{}]}]}"#);
+        let mut scalar = JsonValue::from(1i64);
+        scalar.retain(|_| false);
+        scalar.retain_keys(|_, _| false);
+        assert_eq!(scalar, JsonValue::from(1i64));
+        Ok(())
+    }
+}
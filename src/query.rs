@@ -0,0 +1,80 @@
+use crate::value::JsonValue;
+use anyhow::{Result, bail};
+use std::fmt::Write;
+
+impl JsonValue {
+    /// Encodes a flat object as a URL query string, `key=value&key2=value2`,
+    /// in member order, for form-encoded endpoints.
+    ///
+    /// Keys and string values are percent-encoded byte by byte, leaving only
+    /// RFC 3986's unreserved characters (`A-Z a-z 0-9 - . _ ~`) as they are.
+    /// Numbers and booleans are written as in JSON, and `null` as an empty
+    /// value. Fails for a non-object or for a member that is an array or an
+    /// object.
+    pub fn to_query_string(&self) -> Result<String> {
+        let JsonValue::Object(members) = self else {
+            bail!(
+                "Expected object for a query string, found {}",
+                self.type_name()
+            );
+        };
+        let mut out = String::new();
+        for (i, (key, value)) in members.iter().enumerate() {
+            if i > 0 {
+                out.push('&');
+            }
+            percent_encode(&mut out, key);
+            out.push('=');
+            match value {
+                JsonValue::Null => {}
+                JsonValue::String(s) => percent_encode(&mut out, s),
+                JsonValue::Bool(_) | JsonValue::Number(_) => write!(out, "{value}")?,
+                JsonValue::Array(_) | JsonValue::Object(_) => {
+                    bail!(
+                        "Query string member {key:?} is an {}, not a scalar",
+                        value.type_name()
+                    )
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn percent_encode(out: &mut String, s: &str) {
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::from_str;
+
+    #[test]
+    fn test_to_query_string() -> anyhow::Result<()> {
+        let value = from_str(
+            r#"{"q": "a b&c=d/é", "page": 2, "ratio": 0.5, "exact": true, "cursor": null, "k~-._": "~"}"#,
+        )?;
+        assert_eq!(
+            value.to_query_string()?,
+            "q=a%20b%26c%3Dd%2F%C3%A9&page=2&ratio=0.5&exact=true&cursor=&k~-._=~"
+        );
+        assert_eq!(from_str("{}")?.to_query_string()?, "");
+
+        let err = from_str(r#"{"a": 1, "tags": ["x"]}"#)?
+            .to_query_string()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"Query string member "tags" is an array, not a scalar"#
+        );
+        assert!(from_str(r#"{"a": {}}"#)?.to_query_string().is_err());
+        assert!(from_str("[1]")?.to_query_string().is_err());
+        Ok(())
+    }
+}
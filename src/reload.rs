@@ -0,0 +1,64 @@
+use crate::diff::Change;
+use crate::error::Error;
+use crate::options::ParserOptions;
+use crate::value::JsonValue;
+
+/// The last good parse of a configuration that is reloaded while a program
+/// runs.
+///
+/// Each [`update`](Self::update) with the file's new contents reports what
+/// changed. Input that fails to parse is rejected and the previous value
+/// stays in effect, so a half-written file never takes down a daemon.
+#[derive(Debug, Clone)]
+pub struct ReloadableConfig {
+    value: JsonValue,
+    options: ParserOptions,
+}
+
+impl ReloadableConfig {
+    pub fn new(input: &str) -> Result<Self, Error> {
+        Self::with_options(input, ParserOptions::default())
+    }
+
+    pub fn with_options(input: &str, options: ParserOptions) -> Result<Self, Error> {
+        let value = crate::from_str_with(input, &options)?;
+        Ok(ReloadableConfig { value, options })
+    }
+
+    pub fn value(&self) -> &JsonValue {
+        &self.value
+    }
+
+    /// Parses `input` and makes it the current value, returning how it
+    /// differs from the previous one (see [`JsonValue::diff`]). On error the
+    /// previous value is kept.
+    pub fn update(&mut self, input: &str) -> Result<Vec<Change>, Error> {
+        let value = crate::from_str_with(input, &self.options)?;
+        let changes = self.value.diff(&value);
+        self.value = value;
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update() -> anyhow::Result<()> {
+        let mut config = ReloadableConfig::new(r#"{"port": 80, "debug": false}"#)?;
+        let changes = config.update(r#"{"port": 8080, "debug": false}"#)?;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path(), "/port");
+        assert_eq!(config.value().pointer_i64("/port"), Some(8080));
+
+        assert!(config.update(r#"{"port": 9090,"#).is_err());
+        assert_eq!(config.value().pointer_i64("/port"), Some(8080));
+        assert!(
+            config
+                .update(r#"{"port": 8080, "debug": false}"#)?
+                .is_empty()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use crate::error::{Error, Position};
+use crate::number::Number;
+use crate::parser::decode_string;
+use crate::tokenizer::{Span, Token, Token::*, Tokenizer};
+use std::collections::HashSet;
+
+/// Which advisory checks [`validate_and_report`] runs.
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    /// Numbers that cannot be stored without losing precision.
+    pub lossy_numbers: bool,
+    /// Objects that repeat a key.
+    pub duplicate_keys: bool,
+    /// Warn the first time containers nest this deep.
+    pub nesting_depth: Option<usize>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            lossy_numbers: true,
+            duplicate_keys: true,
+            nesting_depth: Some(100),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    LossyNumber,
+    DuplicateKey,
+    DeepNesting,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+    pub position: Position,
+}
+
+/// Outcome of [`validate_and_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// The first error, or `None` if the document is valid.
+    pub error: Option<Error>,
+    /// Advisory findings, in source order. Only the part of the document
+    /// before a lex error is checked.
+    pub warnings: Vec<Warning>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Validates `input` and collects advisory warnings in one call, for linters.
+pub fn validate_and_report(input: &str, options: &ReportOptions) -> ValidationReport {
+    ValidationReport {
+        error: crate::validate(input).err(),
+        warnings: warnings(input, options),
+    }
+}
+
+fn warnings(input: &str, options: &ReportOptions) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    let mut warn = |kind, span: Span, message: String| {
+        warnings.push(Warning {
+            kind,
+            message,
+            position: Position::locate(input, span.start),
+        })
+    };
+
+    // One entry per open container: the keys seen so far if it's an object.
+    let mut open: Vec<Option<HashSet<String>>> = Vec::new();
+    let mut warned_depth = false;
+    let mut previous: Option<(Token, Span)> = None;
+    let mut tokenizer = Tokenizer::borrowed(input);
+    while let Some(Ok((token, span))) = tokenizer.next_spanned() {
+        let text = &input[span.start..span.end];
+        match token {
+            BeginObject | BeginArray => {
+                open.push((token == BeginObject).then(HashSet::new));
+                if options.nesting_depth.is_some_and(|d| open.len() >= d) && !warned_depth {
+                    warned_depth = true;
+                    warn(
+                        WarningKind::DeepNesting,
+                        span,
+                        format!("Nesting depth reaches {}", open.len()),
+                    );
+                }
+            }
+            EndObject | EndArray => {
+                open.pop();
+            }
+            NameSeparator if options.duplicate_keys => {
+                let (Some(Some(keys)), Some((Stringy, key_span))) = (open.last_mut(), previous)
+                else {
+                    continue;
+                };
+                let literal = &input[key_span.start..key_span.end];
+                let Ok(key) = decode_string(literal, None) else {
+                    continue;
+                };
+                if keys.contains(&key) {
+                    warn(
+                        WarningKind::DuplicateKey,
+                        key_span,
+                        format!("Duplicate key {key:?}"),
+                    );
+                } else {
+                    keys.insert(key);
+                }
+            }
+            Number if options.lossy_numbers && is_lossy(text) => warn(
+                WarningKind::LossyNumber,
+                span,
+                format!("{text} cannot be represented exactly"),
+            ),
+            _ => {}
+        }
+        previous = Some((token, span));
+    }
+    warnings
+}
+
+/// Whether storing the literal loses information: an integer too large for
+/// 64 bits, a float that overflows, or more significant digits than an
+/// `f64` holds (17).
+fn is_lossy(text: &str) -> bool {
+    match Number::from_literal(text) {
+        Some(Number::Float(f) | Number::Text(f, _)) if !f.is_finite() => true,
+        Some(Number::Float(_)) => {
+            if !text.contains(['.', 'e', 'E']) && text != "-0" {
+                return true;
+            }
+            let mantissa = text.split(['e', 'E']).next().unwrap();
+            let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+            digits.trim_matches('0').len() > 17
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let input = "{\"a\": 1, \"b\": 123456789012345678901, \"a\": [[0.1]],\n \"c\": 1.00000000000000000001, \"d\": 1e400}";
+        let options = ReportOptions {
+            nesting_depth: Some(3),
+            ..Default::default()
+        };
+        let report = validate_and_report(input, &options);
+        assert!(report.is_valid());
+        let kinds: Vec<_> = report.warnings.iter().map(|w| w.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                WarningKind::LossyNumber,
+                WarningKind::DuplicateKey,
+                WarningKind::DeepNesting,
+                WarningKind::LossyNumber,
+                WarningKind::LossyNumber,
+            ]
+        );
+        assert_eq!(report.warnings[3].position.line, 2);
+
+        let quiet = ReportOptions {
+            lossy_numbers: false,
+            duplicate_keys: false,
+            nesting_depth: None,
+        };
+        assert!(validate_and_report(input, &quiet).warnings.is_empty());
+    }
+
+    #[test]
+    fn test_report_error() {
+        let report = validate_and_report("[1,\n 2 3]", &ReportOptions::default());
+        assert!(!report.is_valid());
+        assert!(
+            validate_and_report("{1: 2}", &ReportOptions::default())
+                .error
+                .is_some()
+        );
+        let position = report.error.unwrap().position();
+        assert_eq!((position.offset, position.line, position.column), (7, 2, 4));
+
+        // Leading zeros are outside the grammar, not merely advisory.
+        let report = validate_and_report(r#"{"a": 01, "b": 02}"#, &ReportOptions::default());
+        let err = report.error.unwrap();
+        assert_eq!(err.position().offset, 6);
+        assert_eq!(
+            err.to_string(),
+            r#"lex error: Leading zeros are not allowed in numbers at line 1, column 7, while parsing object value for key "a""#
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use crate::error::Error;
+use crate::options::ParserOptions;
+use crate::parser::{Parser, Scratch};
+use crate::tokenizer::Tokenizer;
+use crate::value::JsonValue;
+
+/// A parser that keeps its working memory between documents, for servers
+/// parsing many small bodies.
+///
+/// [`from_str_with`](crate::from_str_with) collects the children of every
+/// container on stacks that are thrown away after each document; this
+/// keeps them, so after the first few documents parsing allocates only
+/// the returned value itself.
+#[derive(Debug, Default)]
+pub struct JsonParser {
+    options: ParserOptions,
+    scratch: Scratch,
+}
+
+impl JsonParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: ParserOptions) -> Self {
+        JsonParser {
+            options,
+            scratch: Scratch::default(),
+        }
+    }
+
+    pub fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    /// Parses a complete JSON document, like
+    /// [`from_str_with`](crate::from_str_with) with this parser's options.
+    pub fn parse(&mut self, input: &str) -> Result<JsonValue, Error> {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.clear();
+        let mut tokenizer = Tokenizer::borrowed(input).configured(&self.options);
+        let tokens = std::iter::from_fn(|| tokenizer.next_spanned());
+        let mut parser = Parser::new(input, tokens, &self.options).with_scratch(scratch);
+        let result = parser.parse();
+        self.scratch = parser.into_scratch();
+        result
+    }
+
+    /// Frees the memory kept from earlier documents, e.g. after an unusually
+    /// large one.
+    pub fn reset(&mut self) {
+        self.scratch = Scratch::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reuse() -> anyhow::Result<()> {
+        let mut parser = JsonParser::new();
+        for input in [
+            r#"{"a": [1, 2, {"b": [3]}], "c": {}}"#,
+            "[1, [2, [3, [4]]], 5]",
+            r#"{"a": 1, "a": 2}"#,
+        ] {
+            assert_eq!(parser.parse(input)?, crate::from_str(input)?);
+        }
+        // A failed parse leaves nothing behind for the next one.
+        assert!(parser.parse("[1, 2, [3, 4").is_err());
+        assert_eq!(parser.parse("[5]")?.to_string(), "[5]");
+        parser.reset();
+        assert_eq!(parser.parse("[]")?, JsonValue::empty_array());
+
+        let options = ParserOptions {
+            allow_leading_plus: true,
+            ..Default::default()
+        };
+        let mut parser = JsonParser::with_options(options);
+        assert_eq!(parser.parse("[+1]")?.to_string(), "[1]");
+        Ok(())
+    }
+}
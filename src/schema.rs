@@ -0,0 +1,282 @@
+use crate::pointer::push_token;
+use crate::value::JsonValue;
+use std::fmt;
+
+/// A JSON type a [`Schema`] can require.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaType {
+    Null,
+    Boolean,
+    Number,
+    /// A number without a fractional part, however it was written (`2.0`
+    /// counts).
+    Integer,
+    String,
+    Array,
+    Object,
+}
+
+impl SchemaType {
+    fn matches(self, value: &JsonValue) -> bool {
+        match (self, value) {
+            (SchemaType::Null, JsonValue::Null)
+            | (SchemaType::Boolean, JsonValue::Bool(_))
+            | (SchemaType::Number, JsonValue::Number(_))
+            | (SchemaType::String, JsonValue::String(_))
+            | (SchemaType::Array, JsonValue::Array(_))
+            | (SchemaType::Object, JsonValue::Object(_)) => true,
+            (SchemaType::Integer, JsonValue::Number(n)) => n.as_f64().fract() == 0.0,
+            _ => false,
+        }
+    }
+
+    /// Matches [`JsonValue::type_name`], plus `"integer"`.
+    fn name(self) -> &'static str {
+        match self {
+            SchemaType::Null => "null",
+            SchemaType::Boolean => "bool",
+            SchemaType::Number => "number",
+            SchemaType::Integer => "integer",
+            SchemaType::String => "string",
+            SchemaType::Array => "array",
+            SchemaType::Object => "object",
+        }
+    }
+}
+
+/// A small, programmatically built subset of JSON Schema, for checking
+/// configuration files.
+///
+/// A schema can constrain a value's type, list the values it may take (an
+/// enum of scalars), describe an object's members and which are required,
+/// and describe every element of an array. Anything not constrained is
+/// accepted, including object members the schema doesn't mention. There is
+/// no support for references, combinators or numeric and length bounds.
+///
+/// ```
+/// use json_parser::{Schema, SchemaType, from_str};
+///
+/// let schema = Schema::of(SchemaType::Object)
+///     .required("name", Schema::of(SchemaType::String))
+///     .property("level", Schema::one_of_values(["debug", "info"]))
+///     .property("ports", Schema::of(SchemaType::Array).items(Schema::of(SchemaType::Integer)));
+/// let config = from_str(r#"{"level": "trace", "ports": [80, "443"]}"#).unwrap();
+/// let errors = schema.validate(&config).unwrap_err();
+/// let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+/// assert_eq!(paths, ["/name", "/level", "/ports/1"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    types: Vec<SchemaType>,
+    allowed: Option<Vec<JsonValue>>,
+    required: Vec<String>,
+    properties: Vec<(String, Schema)>,
+    items: Option<Box<Schema>>,
+}
+
+/// One way in which a value doesn't match a [`Schema`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    /// JSON Pointer to the offending value; empty for the root.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl Schema {
+    /// Accepts any value.
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    /// Accepts values of type `ty`.
+    pub fn of(ty: SchemaType) -> Self {
+        Schema {
+            types: vec![ty],
+            ..Default::default()
+        }
+    }
+
+    /// Accepts values of any of `types`.
+    pub fn of_any(types: impl IntoIterator<Item = SchemaType>) -> Self {
+        Schema {
+            types: types.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Accepts only the given values.
+    pub fn one_of_values<V: Into<JsonValue>>(values: impl IntoIterator<Item = V>) -> Self {
+        Schema {
+            allowed: Some(values.into_iter().map(Into::into).collect()),
+            ..Default::default()
+        }
+    }
+
+    /// Object members named `key`, if present, must match `schema`.
+    pub fn property(mut self, key: &str, schema: Schema) -> Self {
+        self.properties.push((key.to_string(), schema));
+        self
+    }
+
+    /// Like [`property`](Self::property), and the member must be present.
+    pub fn required(mut self, key: &str, schema: Schema) -> Self {
+        self.required.push(key.to_string());
+        self.property(key, schema)
+    }
+
+    /// Every array element must match `schema`.
+    pub fn items(mut self, schema: Schema) -> Self {
+        self.items = Some(Box::new(schema));
+        self
+    }
+
+    /// Checks `value`, reporting every violation rather than just the first.
+    pub fn validate(&self, value: &JsonValue) -> Result<(), Vec<SchemaError>> {
+        let mut errors = Vec::new();
+        self.check(value, &mut String::new(), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn check(&self, value: &JsonValue, path: &mut String, errors: &mut Vec<SchemaError>) {
+        let mut error = |message: String| {
+            errors.push(SchemaError {
+                path: path.clone(),
+                message,
+            })
+        };
+        if !self.types.is_empty() && !self.types.iter().any(|ty| ty.matches(value)) {
+            let expected: Vec<_> = self.types.iter().map(|ty| ty.name()).collect();
+            error(format!(
+                "expected {}, found {}",
+                expected.join(" or "),
+                value.type_name()
+            ));
+            // Nothing else is meaningful for a value of the wrong type.
+            return;
+        }
+        if let Some(allowed) = &self.allowed
+            && !allowed.contains(value)
+        {
+            let allowed: Vec<_> = allowed.iter().map(ToString::to_string).collect();
+            error(format!(
+                "expected one of {}, found {value}",
+                allowed.join(", ")
+            ));
+        }
+
+        let base = path.len();
+        match value {
+            JsonValue::Object(_) => {
+                for key in &self.required {
+                    if !value.contains_key(key) {
+                        push_token(path, key);
+                        errors.push(SchemaError {
+                            path: path.clone(),
+                            message: "required member is missing".to_string(),
+                        });
+                        path.truncate(base);
+                    }
+                }
+                for (key, schema) in &self.properties {
+                    if let Some(member) = value.get(key) {
+                        push_token(path, key);
+                        schema.check(member, path, errors);
+                        path.truncate(base);
+                    }
+                }
+            }
+            JsonValue::Array(elements) => {
+                if let Some(schema) = &self.items {
+                    for (i, element) in elements.iter().enumerate() {
+                        push_token(path, &i.to_string());
+                        schema.check(element, path, errors);
+                        path.truncate(base);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    fn server() -> Schema {
+        Schema::of(SchemaType::Object)
+            .required("host", Schema::of(SchemaType::String))
+            .required("port", Schema::of(SchemaType::Integer))
+            .property("tls", Schema::of(SchemaType::Boolean))
+            .property("mode", Schema::one_of_values(["dev", "prod"]))
+            .property(
+                "tags",
+                Schema::of(SchemaType::Array).items(Schema::of(SchemaType::String)),
+            )
+            .property(
+                "timeout",
+                Schema::of_any([SchemaType::Number, SchemaType::Null]),
+            )
+    }
+
+    #[test]
+    fn test_valid() -> anyhow::Result<()> {
+        let value = from_str(
+            r#"{"host": "a", "port": 80.0, "mode": "dev", "tags": [], "timeout": null, "x": 1}"#,
+        )?;
+        assert_eq!(server().validate(&value), Ok(()));
+        assert_eq!(Schema::any().validate(&value), Ok(()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_violations() -> anyhow::Result<()> {
+        let schema = Schema::of(SchemaType::Object)
+            .property("servers", Schema::of(SchemaType::Array).items(server()));
+        let value = from_str(
+            r#"{"servers": [
+                {"host": "a", "port": 1.5, "tls": "yes"},
+                {"port": 1, "mode": "test", "tags": ["x", 2]},
+                3
+            ]}"#,
+        )?;
+        let errors: Vec<_> = schema
+            .validate(&value)
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "/servers/0/port: expected integer, found number",
+                "/servers/0/tls: expected bool, found string",
+                "/servers/1/host: required member is missing",
+                r#"/servers/1/mode: expected one of "dev", "prod", found "test""#,
+                "/servers/1/tags/1: expected string, found number",
+                "/servers/2: expected object, found number",
+            ]
+        );
+        let err = Schema::of(SchemaType::Array).validate(&JsonValue::Null);
+        assert_eq!(err.unwrap_err()[0].path, "");
+        Ok(())
+    }
+}
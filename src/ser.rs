@@ -0,0 +1,515 @@
+use crate::backing::ObjectBacking;
+use crate::error::{DepthLimitExceeded, SerializeError};
+use crate::value::JsonValue;
+use std::fmt::{self, Write};
+use std::io;
+
+/// Layout used when serializing a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SerializeStyle {
+    /// No insignificant whitespace at all.
+    #[default]
+    Compact,
+    /// One member or element per line, indented per level as set by
+    /// [`SerializeOptions::indent`].
+    Pretty,
+    /// On one line, with a single space after each `:` and `,`:
+    /// `{"a": 1, "b": [1, 2]}`.
+    Normalized,
+}
+
+/// Indentation added per nesting level in pretty output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    Spaces(usize),
+    Tab,
+}
+
+impl Default for IndentStyle {
+    fn default() -> Self {
+        IndentStyle::Spaces(2)
+    }
+}
+
+/// Line terminator used in pretty output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+/// Controls how a `JsonValue` is turned back into text.
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    pub style: SerializeStyle,
+    /// Emit object members sorted by key (byte order) at every level,
+    /// instead of in their stored order.
+    pub sort_keys: bool,
+    /// Pretty output only.
+    pub indent: IndentStyle,
+    /// Pretty output, and the trailing newline in any style.
+    pub line_ending: LineEnding,
+    /// Deepest allowed nesting, as for
+    /// [`ParserOptions::depth_budget`](crate::ParserOptions::depth_budget).
+    /// Deeper values are refused up front instead of recursing.
+    pub depth_budget: Option<usize>,
+    /// Write `/` as `\/`, so that output embedded in an HTML `<script>`
+    /// element cannot close it with `</script>`.
+    pub escape_forward_slashes: bool,
+    /// Write every non-ASCII character as `\uXXXX` (a surrogate pair outside
+    /// the Basic Multilingual Plane), for ASCII-only transports.
+    pub escape_non_ascii: bool,
+    /// End the output with a line ending, as Unix tools expect of text
+    /// files. Off by default, so `to_string` stays a bare value.
+    pub trailing_newline: bool,
+}
+
+impl SerializeOptions {
+    pub fn pretty() -> Self {
+        SerializeOptions {
+            style: SerializeStyle::Pretty,
+            ..Default::default()
+        }
+    }
+
+    fn check_depth<B: ObjectBacking>(
+        &self,
+        value: &JsonValue<B>,
+    ) -> Result<(), DepthLimitExceeded> {
+        match self.depth_budget {
+            Some(limit) if value.depth() > limit => Err(DepthLimitExceeded { limit }),
+            _ => Ok(()),
+        }
+    }
+}
+
+struct Serializer<'o, W: Write> {
+    out: W,
+    options: &'o SerializeOptions,
+    depth: usize,
+}
+
+impl<W: Write> Serializer<'_, W> {
+    fn write_value<B: ObjectBacking>(&mut self, value: &JsonValue<B>) -> fmt::Result {
+        match value {
+            JsonValue::Null => self.out.write_str("null"),
+            JsonValue::Bool(b) => write!(self.out, "{b}"),
+            JsonValue::Number(n) => write!(self.out, "{n}"),
+            JsonValue::String(s) => self.write_escaped(s),
+            JsonValue::Array(elements) => {
+                if elements.is_empty() {
+                    return self.out.write_str("[]");
+                }
+                self.out.write_char('[')?;
+                self.depth += 1;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.write_comma()?;
+                    }
+                    self.newline()?;
+                    self.write_value(element)?;
+                }
+                self.depth -= 1;
+                self.newline()?;
+                self.out.write_char(']')
+            }
+            JsonValue::Object(members) => {
+                if B::len(members) == 0 {
+                    return self.out.write_str("{}");
+                }
+                let mut members: Vec<_> = B::iter(members).collect();
+                if self.options.sort_keys {
+                    members.sort_by_key(|(key, _)| *key);
+                }
+                self.out.write_char('{')?;
+                self.depth += 1;
+                for (i, (key, value)) in members.into_iter().enumerate() {
+                    if i > 0 {
+                        self.write_comma()?;
+                    }
+                    self.newline()?;
+                    self.write_escaped(key)?;
+                    self.out.write_char(':')?;
+                    if self.options.style != SerializeStyle::Compact {
+                        self.out.write_char(' ')?;
+                    }
+                    self.write_value(value)?;
+                }
+                self.depth -= 1;
+                self.newline()?;
+                self.out.write_char('}')
+            }
+        }
+    }
+
+    fn write_escaped(&mut self, s: &str) -> fmt::Result {
+        let out = &mut self.out;
+        out.write_char('"')?;
+        for c in s.chars() {
+            match c {
+                '"' => out.write_str("\\\"")?,
+                '\\' => out.write_str("\\\\")?,
+                '\n' => out.write_str("\\n")?,
+                '\r' => out.write_str("\\r")?,
+                '\t' => out.write_str("\\t")?,
+                '\u{8}' => out.write_str("\\b")?,
+                '\u{c}' => out.write_str("\\f")?,
+                '/' if self.options.escape_forward_slashes => out.write_str("\\/")?,
+                c if c < ' ' => write!(out, "\\u{:04x}", c as u32)?,
+                c if !c.is_ascii() && self.options.escape_non_ascii => {
+                    for unit in c.encode_utf16(&mut [0; 2]) {
+                        write!(out, "\\u{unit:04x}")?;
+                    }
+                }
+                c => out.write_char(c)?,
+            }
+        }
+        out.write_char('"')
+    }
+
+    fn write_comma(&mut self) -> fmt::Result {
+        self.out.write_char(',')?;
+        if self.options.style == SerializeStyle::Normalized {
+            self.out.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    fn write_line_ending(&mut self) -> fmt::Result {
+        self.out.write_str(match self.options.line_ending {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        })
+    }
+
+    fn newline(&mut self) -> fmt::Result {
+        if self.options.style == SerializeStyle::Pretty {
+            self.write_line_ending()?;
+            for _ in 0..self.depth {
+                match self.options.indent {
+                    IndentStyle::Spaces(n) => {
+                        for _ in 0..n {
+                            self.out.write_char(' ')?;
+                        }
+                    }
+                    IndentStyle::Tab => self.out.write_char('\t')?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Objects are written in the backing's own order: arbitrary for
+/// [`Hashed`](crate::Hashed), unless `sort_keys` is set.
+impl<B: ObjectBacking> JsonValue<B> {
+    /// Serializes into any `fmt::Write` sink according to `options`. Fails
+    /// with a bare `fmt::Error` if the depth budget is exceeded or the value
+    /// holds a NaN or infinite number.
+    pub fn fmt_with<W: Write>(&self, out: W, options: &SerializeOptions) -> fmt::Result {
+        options.check_depth(self).map_err(|_| fmt::Error)?;
+        self.fmt_unchecked(out, options)
+    }
+
+    /// [`fmt_with`](Self::fmt_with) once the depth budget has been checked.
+    fn fmt_unchecked<W: Write>(&self, out: W, options: &SerializeOptions) -> fmt::Result {
+        let mut serializer = Serializer {
+            out,
+            options,
+            depth: 0,
+        };
+        serializer.write_value(self)?;
+        if options.trailing_newline {
+            serializer.write_line_ending()?;
+        }
+        Ok(())
+    }
+
+    /// Serializes into an `io::Write` sink according to `options`. Output
+    /// is written piecemeal, so wrap unbuffered sinks in a `BufWriter`. A
+    /// [`SerializeError`] is reported as `InvalidInput`; a NaN or infinite
+    /// number is only found on reaching it, so part of the output may have
+    /// been written by then.
+    pub fn write_to<W: io::Write>(&self, out: W, options: &SerializeOptions) -> io::Result<()> {
+        let invalid = |e: SerializeError| io::Error::new(io::ErrorKind::InvalidInput, e);
+        options.check_depth(self).map_err(|e| invalid(e.into()))?;
+        /// Keeps the sink's error, which `fmt::Error` cannot carry.
+        struct Adapter<W> {
+            out: W,
+            error: Option<io::Error>,
+        }
+        impl<W: io::Write> Write for Adapter<W> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.out.write_all(s.as_bytes()).map_err(|e| {
+                    self.error = Some(e);
+                    fmt::Error
+                })
+            }
+        }
+        let mut adapter = Adapter { out, error: None };
+        self.fmt_unchecked(&mut adapter, options).map_err(|_| {
+            adapter
+                .error
+                .take()
+                .unwrap_or_else(|| invalid(SerializeError::NonFiniteNumber))
+        })
+    }
+
+    /// Pretty-prints with the default layout. For other indents or line
+    /// endings, use [`write_to`](Self::write_to) with your own options.
+    pub fn to_writer_pretty<W: io::Write>(&self, out: W) -> io::Result<()> {
+        self.write_to(out, &SerializeOptions::pretty())
+    }
+
+    /// Serializes to a `String` according to `options`. Fails if
+    /// `options.depth_budget` is exceeded, which is checked before anything
+    /// is written, or if the value holds a NaN or infinite number.
+    pub fn to_string_with(&self, options: &SerializeOptions) -> Result<String, SerializeError> {
+        options.check_depth(self)?;
+        let mut out = String::new();
+        // Writing to a String cannot fail, so only a number can.
+        self.fmt_unchecked(&mut out, options)
+            .map_err(|_| SerializeError::NonFiniteNumber)?;
+        Ok(out)
+    }
+
+    /// # Panics
+    ///
+    /// If the value holds a NaN or infinite number, as `to_string` does;
+    /// [`to_string_with`](Self::to_string_with) reports it instead.
+    pub fn to_string_pretty(&self) -> String {
+        let mut out = String::new();
+        self.fmt_unchecked(&mut out, &SerializeOptions::pretty())
+            .expect("a NaN or infinite number has no JSON text");
+        out
+    }
+}
+
+/// Compact serialization; `to_string()` yields minimal JSON text. Fails,
+/// so `to_string()` panics, on a NaN or infinite number.
+impl<B: ObjectBacking> fmt::Display for JsonValue<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, &SerializeOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+    use crate::number::Number;
+
+    #[test]
+    fn test_compact_and_pretty() -> anyhow::Result<()> {
+        let value = from_str(r#"{"a": [1, "x\n"], "b": []}"#)?;
+        assert_eq!(value.to_string(), r#"{"a":[1,"x\n"],"b":[]}"#);
+        assert_eq!(
+            value.to_string_pretty(),
+            "{\n  \"a\": [\n    1,\n    \"x\\n\"\n  ],\n  \"b\": []\n}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout_options() -> anyhow::Result<()> {
+        let value = from_str(r#"{"a": [1]}"#)?;
+        let mut out = Vec::new();
+        value.to_writer_pretty(&mut out)?;
+        assert_eq!(out, b"{\n  \"a\": [\n    1\n  ]\n}");
+
+        let options = SerializeOptions {
+            indent: IndentStyle::Tab,
+            line_ending: LineEnding::CrLf,
+            ..SerializeOptions::pretty()
+        };
+        let mut out = Vec::new();
+        value.write_to(&mut out, &options)?;
+        assert_eq!(out, b"{\r\n\t\"a\": [\r\n\t\t1\r\n\t]\r\n}");
+
+        let options = SerializeOptions {
+            indent: IndentStyle::Spaces(4),
+            ..SerializeOptions::pretty()
+        };
+        assert_eq!(
+            value.to_string_with(&options)?,
+            "{\n    \"a\": [\n        1\n    ]\n}"
+        );
+        // Compact output ignores the layout options.
+        let options = SerializeOptions {
+            line_ending: LineEnding::CrLf,
+            ..Default::default()
+        };
+        assert_eq!(value.to_string_with(&options)?, r#"{"a":[1]}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalized() -> anyhow::Result<()> {
+        let options = SerializeOptions {
+            style: SerializeStyle::Normalized,
+            ..Default::default()
+        };
+        for input in [
+            r#"{"a":1,"b":[1,2]}"#,
+            "{\n  \"a\" :\t1 ,\r\n\"b\": [ 1,\n2 ] }",
+        ] {
+            assert_eq!(
+                from_str(input)?.to_string_with(&options)?,
+                r#"{"a": 1, "b": [1, 2]}"#
+            );
+        }
+        let value = from_str(r#"[{}, [], " a ,  b:c ", {"k  ": [{"x": null}]}]"#)?;
+        assert_eq!(
+            value.to_string_with(&options)?,
+            r#"[{}, [], " a ,  b:c ", {"k  ": [{"x": null}]}]"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_trailing_newline() -> anyhow::Result<()> {
+        let value = from_str(r#"{"a": [1, 2]}"#)?;
+        for style in [
+            SerializeStyle::Compact,
+            SerializeStyle::Pretty,
+            SerializeStyle::Normalized,
+        ] {
+            let mut options = SerializeOptions {
+                style,
+                ..Default::default()
+            };
+            let bare = value.to_string_with(&options)?;
+            assert!(!bare.ends_with('\n'), "{style:?}");
+            options.trailing_newline = true;
+            let text = value.to_string_with(&options)?;
+            assert_eq!(text, format!("{bare}\n"));
+            assert_eq!(from_str(&text)?, value);
+            let mut written = Vec::new();
+            value.write_to(&mut written, &options)?;
+            assert_eq!(written, text.as_bytes());
+            options.line_ending = LineEnding::CrLf;
+            assert_eq!(
+                value.to_string_with(&options)?,
+                bare.replace('\n', "\r\n") + "\r\n"
+            );
+        }
+        assert!(!value.to_string().ends_with('\n'));
+        Ok(())
+    }
+
+    #[test]
+    fn test_depth_budget() {
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        let parse_options = crate::ParserOptions {
+            depth_budget: Some(3),
+            ..Default::default()
+        };
+        assert!(crate::from_str_with(&nested(3), &parse_options).is_ok());
+        let err = crate::from_str_with(&nested(4), &parse_options).unwrap_err();
+        assert_eq!(err.position().offset, 3);
+        assert!(crate::from_str_with(r#"[{"a": [{}]}]"#, &parse_options).is_err());
+
+        let options = SerializeOptions {
+            depth_budget: Some(3),
+            ..Default::default()
+        };
+        let value = from_str(&nested(3)).unwrap();
+        assert_eq!(value.to_string_with(&options).unwrap(), nested(3));
+        let value = from_str(&nested(4)).unwrap();
+        assert_eq!(
+            value.to_string_with(&options),
+            Err(SerializeError::DepthLimitExceeded(DepthLimitExceeded {
+                limit: 3
+            }))
+        );
+        let err = value.write_to(Vec::new(), &options).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_non_finite_numbers() {
+        for f in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let value: JsonValue = JsonValue::Array(vec![JsonValue::Number(Number::Float(f))]);
+            let options = SerializeOptions::default();
+            assert_eq!(
+                value.to_string_with(&options),
+                Err(SerializeError::NonFiniteNumber)
+            );
+            let err = value.write_to(Vec::new(), &options).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert!(value.fmt_with(String::new(), &options).is_err());
+        }
+        // Literals out of `f64` range are kept as written.
+        let value = from_str("[1e400, -1e400]").unwrap();
+        assert_eq!(value.to_string(), "[1e400,-1e400]");
+    }
+
+    #[test]
+    fn test_escape_options() -> anyhow::Result<()> {
+        let value = from_str(r#"{"html": "</script>", "text": "é😀/\u0001"}"#)?;
+        assert_eq!(
+            value.to_string(),
+            r#"{"html":"</script>","text":"é😀/\u0001"}"#
+        );
+        let options = SerializeOptions {
+            escape_forward_slashes: true,
+            ..Default::default()
+        };
+        let text = value.to_string_with(&options)?;
+        assert_eq!(text, r#"{"html":"<\/script>","text":"é😀\/\u0001"}"#);
+        assert_eq!(from_str(&text)?, value);
+
+        let options = SerializeOptions {
+            escape_non_ascii: true,
+            ..SerializeOptions::pretty()
+        };
+        let text = value.to_string_with(&options)?;
+        assert!(text.is_ascii());
+        assert!(text.contains(r#""\u00e9\ud83d\ude00/\u0001""#));
+        assert_eq!(from_str(&text)?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn test_number_forms() -> anyhow::Result<()> {
+        let value = from_str("[-0.0, 0.0, 2.0, 2, -0]")?;
+        assert_eq!(value.to_string(), "[-0.0,0.0,2.0,2,-0]");
+        // The default options already keep the distinction.
+        let value = from_str(r#"{"a":1,"b":1.0}"#)?;
+        assert_eq!(
+            value.get("a").and_then(JsonValue::as_number),
+            Some(&Number::Int(1))
+        );
+        assert_eq!(
+            value.get("b").and_then(JsonValue::as_number),
+            Some(&Number::Float(1.0))
+        );
+        assert_eq!(value.to_string(), r#"{"a":1,"b":1.0}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_keys() -> anyhow::Result<()> {
+        let one = from_str(r#"{"b": 1, "a": {"d": [{"z": 0, "y": 1}], "c": 3}}"#)?;
+        let other = from_str(r#"{"a": {"c": 3, "d": [{"y": 1, "z": 0}]}, "b": 1}"#)?;
+        let compact = SerializeOptions {
+            sort_keys: true,
+            ..Default::default()
+        };
+        let pretty = SerializeOptions {
+            sort_keys: true,
+            ..SerializeOptions::pretty()
+        };
+        let expected = r#"{"a":{"c":3,"d":[{"y":1,"z":0}]},"b":1}"#;
+        assert_eq!(one.to_string_with(&compact)?, expected);
+        assert_eq!(other.to_string_with(&compact)?, expected);
+        assert_eq!(one.to_string_with(&pretty)?, other.to_string_with(&pretty)?);
+        // The stored order is untouched.
+        assert_eq!(
+            one.to_string(),
+            r#"{"b":1,"a":{"d":[{"z":0,"y":1}],"c":3}}"#
+        );
+        Ok(())
+    }
+}
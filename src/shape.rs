@@ -0,0 +1,134 @@
+use crate::value::JsonValue;
+use std::fmt;
+
+/// The shape compared by [`JsonValue::assert_homogeneous`]: for an object,
+/// its set of keys, ignoring order and repeats; for anything else, its type.
+/// Only the top level counts, so `[1]` and `["a"]` are both arrays.
+#[derive(Debug, PartialEq, Eq)]
+enum Shape<'a> {
+    Object(Vec<&'a str>),
+    Other(&'static str),
+}
+
+impl<'a> Shape<'a> {
+    fn of(value: &'a JsonValue) -> Self {
+        match value {
+            JsonValue::Object(members) => {
+                let mut keys: Vec<_> = members.iter().map(|(k, _)| k.as_str()).collect();
+                keys.sort_unstable();
+                keys.dedup();
+                Shape::Object(keys)
+            }
+            other => Shape::Other(other.type_name()),
+        }
+    }
+}
+
+impl fmt::Display for Shape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shape::Object(keys) => write!(f, "object with keys {keys:?}"),
+            Shape::Other(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Why a value is not a homogeneous array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HomogeneityError {
+    NotAnArray {
+        found: &'static str,
+    },
+    /// Element `index` is the first whose shape differs from element 0's.
+    Mismatch {
+        index: usize,
+        expected: String,
+        found: String,
+    },
+}
+
+impl fmt::Display for HomogeneityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HomogeneityError::NotAnArray { found } => write!(f, "expected array, found {found}"),
+            HomogeneityError::Mismatch {
+                index,
+                expected,
+                found,
+            } => write!(f, "element {index} is {found}, expected {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for HomogeneityError {}
+
+impl JsonValue {
+    /// Whether this is an array whose elements all have the same shape; see
+    /// [`assert_homogeneous`](Self::assert_homogeneous).
+    pub fn is_homogeneous_array(&self) -> bool {
+        self.assert_homogeneous().is_ok()
+    }
+
+    /// Checks that this is an array whose elements all have the same shape
+    /// as the first, e.g. the records of a dump. Objects have the same shape
+    /// if they have the same set of keys, in any order; other values if they
+    /// have the same type. Nested values aren't compared. An empty array
+    /// passes.
+    pub fn assert_homogeneous(&self) -> Result<(), HomogeneityError> {
+        let JsonValue::Array(elements) = self else {
+            return Err(HomogeneityError::NotAnArray {
+                found: self.type_name(),
+            });
+        };
+        let Some((first, rest)) = elements.split_first() else {
+            return Ok(());
+        };
+        let expected = Shape::of(first);
+        for (i, element) in rest.iter().enumerate() {
+            let found = Shape::of(element);
+            if found != expected {
+                return Err(HomogeneityError::Mismatch {
+                    index: i + 1,
+                    expected: expected.to_string(),
+                    found: found.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_homogeneous() -> anyhow::Result<()> {
+        for input in [
+            "[]",
+            "[1, 2.5, -3]",
+            r#"[{"a": 1, "b": [1]}, {"b": "x", "a": null}, {"a": 1, "b": 2, "a": 3}]"#,
+            r#"[[1], ["a", {}], []]"#,
+        ] {
+            assert!(from_str(input)?.is_homogeneous_array(), "{input}");
+        }
+
+        let mixed = from_str(r#"[{"a": 1, "b": 2}, {"b": 2, "a": 1}, {"a": 1}, 4]"#)?;
+        let err = mixed.assert_homogeneous().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"element 2 is object with keys ["a"], expected object with keys ["a", "b"]"#
+        );
+        let err = from_str(r#"["a", "b", null]"#)?.assert_homogeneous();
+        assert!(matches!(
+            err,
+            Err(HomogeneityError::Mismatch { index: 2, .. })
+        ));
+        assert_eq!(
+            from_str("{}")?.assert_homogeneous(),
+            Err(HomogeneityError::NotAnArray { found: "object" })
+        );
+        Ok(())
+    }
+}
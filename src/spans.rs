@@ -0,0 +1,68 @@
+use crate::pointer;
+use crate::tokenizer::Span;
+
+/// Source locations for a parsed value, mirroring its structure: the node
+/// for an array holds one child per element, the node for an object one
+/// entry per member, in the same order as the `JsonValue`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanTree {
+    /// Byte range of the whole value, from its first to its last token.
+    pub span: Span,
+    pub kind: SpanKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpanKind {
+    Scalar,
+    Array(Vec<SpanTree>),
+    /// Each member's decoded key, the span of the key literal, and the value.
+    Object(Vec<(String, Span, SpanTree)>),
+}
+
+impl SpanTree {
+    /// Finds the node for the value `ptr` (a JSON Pointer) refers to.
+    pub fn pointer(&self, ptr: &str) -> Option<&SpanTree> {
+        pointer::tokens(ptr)?
+            .iter()
+            .try_fold(self, |node, token| match &node.kind {
+                SpanKind::Object(members) => members
+                    .iter()
+                    .find(|(k, _, _)| k == token)
+                    .map(|(_, _, v)| v),
+                SpanKind::Array(elements) => elements.get(pointer::index(token)?),
+                SpanKind::Scalar => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_with_spans;
+
+    #[test]
+    fn test_nested_span() -> anyhow::Result<()> {
+        let input = r#"{"a": [1, {"b": "xy"}], "c": null}"#;
+        let (value, spans) = parse_with_spans(input)?;
+        let node = spans.pointer("/a/1/b").unwrap();
+        assert_eq!(&input[node.span.start..node.span.end], r#""xy""#);
+        assert_eq!(value.pointer("/a/1/b").unwrap().to_string(), r#""xy""#);
+
+        let node = spans.pointer("/a/1").unwrap();
+        assert_eq!(&input[node.span.start..node.span.end], r#"{"b": "xy"}"#);
+        let SpanKind::Object(members) = &node.kind else {
+            panic!("expected an object node");
+        };
+        assert_eq!(members[0].1, Span { start: 11, end: 14 });
+
+        assert_eq!(
+            spans.span,
+            Span {
+                start: 0,
+                end: input.len()
+            }
+        );
+        assert!(spans.pointer("/d").is_none());
+        Ok(())
+    }
+}
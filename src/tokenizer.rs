@@ -0,0 +1,947 @@
+use crate::error::{LexError, Position};
+use crate::options::ParserOptions;
+use Token::*;
+use nom::IResult;
+use nom::character::complete::digit1;
+use nom::combinator::opt;
+use nom::sequence::tuple;
+use std::borrow::Cow;
+
+fn consume_f64(input: &str) -> IResult<&str, ()> {
+    let (input, _) = tuple((
+        opt(nom::character::complete::char('-')), // Optional negative sign
+        digit1,                                   // Integer part
+        opt(tuple((
+            nom::character::complete::char('.'),
+            digit1, // Fractional part
+        ))),
+        opt(tuple((
+            nom::character::complete::one_of("eE"),
+            opt(nom::character::complete::one_of("+-")), // Optional exponent sign
+            digit1,                                      // Exponent digits
+        ))),
+    ))(input)?;
+
+    Ok((input, ())) // Just consume, discard value
+}
+
+/// Length of the number at the start of `input` under the JSON grammar,
+/// with each digit run also allowed to contain single `_`s between digits.
+/// Like `consume_f64`, an optional fraction or exponent that doesn't
+/// complete is left unconsumed.
+fn separated_number_len(input: &[u8]) -> Option<usize> {
+    let digits = |at: usize| {
+        let mut end = at;
+        while input.get(end).is_some_and(u8::is_ascii_digit) {
+            end += 1;
+            if input.get(end) == Some(&b'_') && input.get(end + 1).is_some_and(u8::is_ascii_digit) {
+                end += 1;
+            }
+        }
+        (end > at).then_some(end)
+    };
+    let mut end = usize::from(input.first() == Some(&b'-'));
+    end = digits(end)?;
+    if input.get(end) == Some(&b'.')
+        && let Some(fraction) = digits(end + 1)
+    {
+        end = fraction;
+    }
+    if matches!(input.get(end), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(input.get(end + 1), Some(b'+' | b'-')));
+        if let Some(exponent) = digits(end + 1 + sign) {
+            end = exponent;
+        }
+    }
+    Some(end)
+}
+
+const SEPARATOR_PLACEMENT: &str = "Digit separators must be between two digits";
+
+/// Kinds of lexical tokens. A token's text is found through its [`Span`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Token {
+    Number,
+    Boolean,
+    Null,
+    Stringy,
+    /// A string in object-key position. Only produced when
+    /// [`Tokenizer::distinguish_keys`] is on; otherwise keys are `Stringy`.
+    Key,
+    BeginObject,
+    EndObject,
+    BeginArray,
+    EndArray,
+    ValueSeparator,
+    NameSeparator,
+}
+
+/// Byte range of a token in the tokenizer's input.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A token and its span, or the reason the next token is malformed.
+pub type Spanned = Result<(Token, Span), LexError>;
+
+/// A comment skipped under
+/// [`ParserOptions::allow_comments`](crate::ParserOptions::allow_comments).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentSpan {
+    /// From the opening `//` or `/*` to the end of the line (not including
+    /// the line break) or past the closing `*/`.
+    pub span: Span,
+    /// The source text of `span`, delimiters included.
+    pub text: String,
+}
+
+/// Splits JSON text into tokens. The input is either owned or borrowed from
+/// the caller, so parsing a `&str` needs no up-front copy.
+pub struct Tokenizer<'a> {
+    input: Cow<'a, str>,
+    position: usize,
+    /// Whether the whole input is ASCII, so every byte is a char and the
+    /// scanner never needs to decode UTF-8.
+    ascii: bool,
+    fast_numbers: bool,
+    leading_plus: bool,
+    leading_zeros: bool,
+    digit_separators: bool,
+    max_number_digits: Option<usize>,
+    comments: bool,
+    /// Comments skipped so far, when recording them.
+    recorded: Option<Vec<CommentSpan>>,
+    /// Open containers, `true` for objects, when distinguishing keys.
+    containers: Option<Vec<bool>>,
+    previous: Option<Token>,
+}
+
+impl Tokenizer<'static> {
+    pub fn new(input: String) -> Self {
+        Tokenizer::from_cow(Cow::Owned(input))
+    }
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn borrowed(input: &'a str) -> Self {
+        Tokenizer::from_cow(Cow::Borrowed(input))
+    }
+
+    fn from_cow(input: Cow<'a, str>) -> Self {
+        Tokenizer {
+            ascii: input.is_ascii(),
+            input,
+            position: 0,
+            fast_numbers: false,
+            leading_plus: false,
+            leading_zeros: false,
+            digit_separators: false,
+            max_number_digits: None,
+            comments: false,
+            recorded: None,
+            containers: None,
+            previous: None,
+        }
+    }
+
+    /// See [`ParserOptions::fast_numbers`](crate::ParserOptions::fast_numbers).
+    pub fn fast_numbers(mut self, fast: bool) -> Self {
+        self.fast_numbers = fast;
+        self
+    }
+
+    /// See [`ParserOptions::allow_leading_plus`].
+    pub fn allow_leading_plus(mut self, allow: bool) -> Self {
+        self.leading_plus = allow;
+        self
+    }
+
+    /// See [`ParserOptions::allow_leading_zeros`].
+    pub fn allow_leading_zeros(mut self, allow: bool) -> Self {
+        self.leading_zeros = allow;
+        self
+    }
+
+    /// See [`ParserOptions::allow_digit_separators`].
+    pub fn allow_digit_separators(mut self, allow: bool) -> Self {
+        self.digit_separators = allow;
+        self
+    }
+
+    /// See [`ParserOptions::max_number_digits`].
+    pub fn max_number_digits(mut self, limit: Option<usize>) -> Self {
+        self.max_number_digits = limit;
+        self
+    }
+
+    /// See [`ParserOptions::allow_comments`].
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.comments = allow;
+        self
+    }
+
+    /// Keep every comment skipped, for [`take_comments`](Self::take_comments).
+    pub fn recording_comments(mut self) -> Self {
+        self.recorded = Some(Vec::new());
+        self
+    }
+
+    /// The comments skipped since recording started or since the last call,
+    /// in source order.
+    pub fn take_comments(&mut self) -> Vec<CommentSpan> {
+        self.recorded
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Applies the lexical settings in `options`.
+    pub(crate) fn configured(self, options: &ParserOptions) -> Self {
+        self.fast_numbers(options.fast_numbers)
+            .allow_leading_plus(options.allow_leading_plus)
+            .allow_leading_zeros(options.allow_leading_zeros)
+            .allow_digit_separators(options.allow_digit_separators)
+            .max_number_digits(options.max_number_digits)
+            .allow_comments(options.allow_comments)
+    }
+
+    /// Emit [`Token::Key`] instead of [`Token::Stringy`] for object keys.
+    ///
+    /// Whether a string is a key is grammar, not lexing: the tokenizer has
+    /// to track which containers are open, a small slice of the parser's
+    /// state. It trusts the input's structure, so on malformed input the
+    /// classification is a best guess.
+    pub fn distinguish_keys(mut self, distinguish: bool) -> Self {
+        self.containers = distinguish.then(Vec::new);
+        self
+    }
+
+    /// Starts over on `input`, keeping the settings. The text is copied
+    /// into the tokenizer's own buffer, whose allocation is reused if it
+    /// owns one already (if made by [`new`](Tokenizer::new)), as are those
+    /// for recording comments and distinguishing keys. Comments not yet
+    /// taken are dropped.
+    pub fn reset(&mut self, input: &str) {
+        match &mut self.input {
+            Cow::Owned(buffer) => {
+                buffer.clear();
+                buffer.push_str(input);
+            }
+            Cow::Borrowed(_) => self.input = Cow::Owned(input.to_string()),
+        }
+        self.restart();
+    }
+
+    /// Like [`reset`](Self::reset), borrowing `input` instead of copying it.
+    pub fn reset_borrowed(&mut self, input: &'a str) {
+        self.input = Cow::Borrowed(input);
+        self.restart();
+    }
+
+    fn restart(&mut self) {
+        self.ascii = self.input.is_ascii();
+        self.position = 0;
+        self.previous = None;
+        if let Some(recorded) = &mut self.recorded {
+            recorded.clear();
+        }
+        if let Some(containers) = &mut self.containers {
+            containers.clear();
+        }
+    }
+
+    /// Returns the next token, or `None` at the end of input or at the
+    /// first malformed token.
+    pub fn next_token(&mut self) -> Option<Token> {
+        self.next_spanned()
+            .and_then(Result::ok)
+            .map(|(token, _)| token)
+    }
+
+    /// Like `next_token`, but also returns where the token sits in the input
+    /// so the parser can slice out its text, and reports malformed tokens.
+    pub fn next_spanned(&mut self) -> Option<Spanned> {
+        let next = self.scan();
+        let (Some(containers), Some(Ok((token, _)))) = (&mut self.containers, &next) else {
+            return next;
+        };
+        let token = *token;
+        match token {
+            BeginObject | BeginArray => containers.push(token == BeginObject),
+            EndObject | EndArray => {
+                containers.pop();
+            }
+            _ => {}
+        }
+        let in_object = containers.last() == Some(&true);
+        let after = self.previous.replace(token);
+        if token == Stringy && in_object && matches!(after, Some(BeginObject | ValueSeparator)) {
+            self.previous = Some(Key);
+            return next.map(|t| t.map(|(_, span)| (Key, span)));
+        }
+        next
+    }
+
+    fn scan(&mut self) -> Option<Spanned> {
+        while let Some(c) = self.peek_char() {
+            let start = self.position;
+            let token = match c {
+                ' ' | '\n' | '\r' | '\t' => {
+                    self.position += 1;
+                    continue;
+                }
+                '{' => {
+                    self.position += 1;
+                    BeginObject
+                }
+                '}' => {
+                    self.position += 1;
+                    EndObject
+                }
+                '[' => {
+                    self.position += 1;
+                    BeginArray
+                }
+                ']' => {
+                    self.position += 1;
+                    EndArray
+                }
+                ':' => {
+                    self.position += 1;
+                    NameSeparator
+                }
+                ',' => {
+                    self.position += 1;
+                    ValueSeparator
+                }
+                'n' => match self.consume_keyword("null") {
+                    Ok(()) => Null,
+                    Err(e) => return Some(Err(e)),
+                },
+                't' | 'f' => {
+                    let keyword = if c == 't' { "true" } else { "false" };
+                    match self.consume_keyword(keyword) {
+                        Ok(()) => Boolean,
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+                '"' => {
+                    self.position += 1;
+                    if let Err(e) = self.skip_string_body(start) {
+                        return Some(Err(e));
+                    }
+                    Stringy
+                }
+                '/' if self.comments => match self.skip_comment(start) {
+                    Ok(()) => continue,
+                    Err(e) => return Some(Err(e)),
+                },
+                '+' if !self.leading_plus => {
+                    return Some(Err(self.error(start, "Numbers cannot start with `+`")));
+                }
+                '+' => {
+                    // Only a digit may follow, so `+-1` stays invalid.
+                    let next = self.input.as_bytes().get(start + 1);
+                    if !next.is_some_and(u8::is_ascii_digit) {
+                        return Some(Err(self.error(start, "Invalid number")));
+                    }
+                    self.position += 1;
+                    match self.scan_number(start) {
+                        Ok(()) => Number,
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+                c if c.is_ascii_digit() || c == '-' => match self.scan_number(start) {
+                    Ok(()) => Number,
+                    Err(e) => return Some(Err(e)),
+                },
+                _ => {
+                    // Step over it, so a caller that carries on after the
+                    // error still makes progress.
+                    self.position += c.len_utf8();
+                    return Some(Err(
+                        self.error(start, &format!("Unexpected character {c:?}"))
+                    ));
+                }
+            };
+            let end = self.position;
+            return Some(Ok((token, Span { start, end })));
+        }
+        None
+    }
+
+    /// Scans a number literal from the current position; `start` is where
+    /// the token began, for errors.
+    fn scan_number(&mut self, start: usize) -> Result<(), LexError> {
+        let separators = self.digit_separators;
+        let in_number = |b: &u8| {
+            matches!(b, b'-' | b'+' | b'0'..=b'9' | b'.' | b'e' | b'E')
+                || (separators && *b == b'_')
+        };
+        if let Some(limit) = self.max_number_digits {
+            let digits = self.input.as_bytes()[self.position..]
+                .iter()
+                .take_while(|b| in_number(b))
+                .filter(|b| b.is_ascii_digit())
+                .take(limit + 1)
+                .count();
+            if digits > limit {
+                return Err(self.error(start, &format!("Number has more than {limit} digits")));
+            }
+        }
+        let rest = &self.input.as_bytes()[self.position..];
+        // Past the sign, a `0` can only be the whole integer part.
+        let digits = &rest[usize::from(rest.first() == Some(&b'-'))..];
+        if !self.leading_zeros
+            && digits.first() == Some(&b'0')
+            && digits
+                .get(usize::from(separators && digits.get(1) == Some(&b'_')) + 1)
+                .is_some_and(u8::is_ascii_digit)
+        {
+            return Err(self.error(start, "Leading zeros are not allowed in numbers"));
+        }
+        if self.fast_numbers {
+            let len = rest.iter().take_while(|b| in_number(b)).count();
+            // The run isn't otherwise checked, but separators must still
+            // sit between digits.
+            let run = &rest[..len];
+            if let Some(i) = (0..len).find(|&i| {
+                run[i] == b'_'
+                    && !(i > 0
+                        && run[i - 1].is_ascii_digit()
+                        && run.get(i + 1).is_some_and(u8::is_ascii_digit))
+            }) {
+                return Err(self.error(self.position + i, SEPARATOR_PLACEMENT));
+            }
+            self.position += len;
+        } else if separators {
+            let Some(len) = separated_number_len(rest) else {
+                return Err(self.error(start, "Invalid number"));
+            };
+            self.position += len;
+        } else {
+            let Ok((rest, ())) = consume_f64(&self.input[self.position..]) else {
+                return Err(self.error(start, "Invalid number"));
+            };
+            self.position = self.input.len() - rest.len();
+        }
+        // Whatever ends a number must be able to end a value, so `1.2.3` and
+        // `12abc` are one bad literal rather than a number and then garbage.
+        match self.peek_char() {
+            Some('_') if separators => Err(self.error(self.position, SEPARATOR_PLACEMENT)),
+            Some(c) if c.is_alphanumeric() || matches!(c, '.' | '+' | '-' | '_') => {
+                Err(self.error(self.position, &format!("Unexpected {c:?} after number")))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Moves past the comment starting at `start`, recording it if asked.
+    fn skip_comment(&mut self, start: usize) -> Result<(), LexError> {
+        let rest = &self.input[start..];
+        let len = if rest.starts_with("//") {
+            rest.find(['\r', '\n']).unwrap_or(rest.len())
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            match body.find("*/") {
+                Some(i) => i + 4,
+                None => {
+                    self.position = self.input.len();
+                    return Err(self.error(start, "Unterminated comment"));
+                }
+            }
+        } else {
+            self.position += 1;
+            return Err(self.error(start, "Expecting `//` or `/*`"));
+        };
+        let end = start + len;
+        if let Some(recorded) = &mut self.recorded {
+            recorded.push(CommentSpan {
+                span: Span { start, end },
+                text: self.input[start..end].to_string(),
+            });
+        }
+        self.position = end;
+        Ok(())
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        if self.ascii {
+            self.input.as_bytes().get(self.position).map(|&b| b as char)
+        } else {
+            self.input[self.position..].chars().next()
+        }
+    }
+
+    /// Moves past the closing quote of a string whose opening quote, at
+    /// `start`, has been consumed, stepping over escaped characters so that
+    /// `\"` doesn't end it. Fails if the input ends first, or if the string
+    /// holds a raw control character (below U+0020), which JSON only allows
+    /// escaped; the error is at that character, but scanning still resumes
+    /// after the string. Escapes are only checked later, when the string is
+    /// decoded.
+    fn skip_string_body(&mut self, start: usize) -> Result<(), LexError> {
+        let mut control = None;
+        let terminated = if self.ascii {
+            self.skip_ascii_string_body(&mut control)
+        } else {
+            self.skip_utf8_string_body(&mut control)
+        };
+        if let Some(at) = control {
+            let c = self.input.as_bytes()[at];
+            return Err(self.error(
+                at,
+                &format!("Unescaped control character U+{c:04X} in string"),
+            ));
+        }
+        if !terminated {
+            return Err(self.error(start, "Unterminated string"));
+        }
+        Ok(())
+    }
+
+    /// [`Self::skip_string_body`] for all-ASCII input, searching bytes.
+    /// `false` if the input ends first; `control` gets the offset of the
+    /// first raw control character.
+    fn skip_ascii_string_body(&mut self, control: &mut Option<usize>) -> bool {
+        let bytes = self.input.as_bytes();
+        while let Some(i) = bytes[self.position..]
+            .iter()
+            .position(|&b| b == b'"' || b == b'\\' || b < b' ')
+        {
+            let at = self.position + i;
+            match bytes[at] {
+                b'"' => {
+                    self.position = at + 1;
+                    return true;
+                }
+                b'\\' => self.position = (at + 2).min(bytes.len()),
+                _ => {
+                    control.get_or_insert(at);
+                    self.position = at + 1;
+                }
+            }
+        }
+        self.position = bytes.len();
+        false
+    }
+
+    /// Like [`Self::skip_ascii_string_body`], decoding UTF-8.
+    fn skip_utf8_string_body(&mut self, control: &mut Option<usize>) -> bool {
+        let mut chars = self.input[self.position..].chars();
+        while let Some(c) = chars.next() {
+            let at = self.position;
+            self.position += c.len_utf8();
+            match c {
+                '"' => return true,
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        self.position += escaped.len_utf8();
+                    }
+                }
+                c if c < ' ' => {
+                    control.get_or_insert(at);
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn consume_keyword(&mut self, keyword: &str) -> Result<(), LexError> {
+        if !self.input[self.position..].starts_with(keyword) {
+            return Err(self.error(self.position, &format!("Expecting keyword `{keyword}`")));
+        }
+        self.position += keyword.len();
+        Ok(())
+    }
+
+    fn error(&self, offset: usize, message: &str) -> LexError {
+        LexError::new(message, Position::locate(&self.input, offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenizer() {
+        let input = r#"{"key": "value"}"#;
+        let mut tokenizer = Tokenizer::new(input.to_string());
+        assert_eq!(tokenizer.next_token(), Some(BeginObject));
+        assert_eq!(tokenizer.next_token(), Some(Stringy));
+        assert_eq!(tokenizer.next_token(), Some(NameSeparator));
+        assert_eq!(tokenizer.next_token(), Some(Stringy));
+        assert_eq!(tokenizer.next_token(), Some(EndObject));
+        assert_eq!(tokenizer.next_token(), None);
+    }
+
+    #[test]
+    fn test_spans_after_number() {
+        let input = "[12, 3]";
+        let mut tokenizer = Tokenizer::borrowed(input);
+        tokenizer.next_spanned();
+        let (token, span) = tokenizer.next_spanned().unwrap().unwrap();
+        assert_eq!(token, Number);
+        assert_eq!(&input[span.start..span.end], "12");
+        tokenizer.next_spanned();
+        let (_, span) = tokenizer.next_spanned().unwrap().unwrap();
+        assert_eq!(&input[span.start..span.end], "3");
+    }
+
+    #[test]
+    fn test_keyword_lengths() {
+        let input = "[false, true, null]";
+        let mut tokenizer = Tokenizer::borrowed(input);
+        let lexemes: Vec<_> = std::iter::from_fn(|| tokenizer.next_spanned())
+            .map(|t| {
+                let (_, span) = t.unwrap();
+                &input[span.start..span.end]
+            })
+            .collect();
+        assert_eq!(lexemes, ["[", "false", ",", "true", ",", "null", "]"]);
+    }
+
+    #[test]
+    fn test_keyword_like_strings() -> anyhow::Result<()> {
+        // Strings are scanned by their quotes, never by their first letter,
+        // so keys and values that look like literals stay strings.
+        let input = r#"{"nil":null, "null":true, "false":0, "true":"false", "tru":"nul", "ñull":[false,"f"]}"#;
+        let mut tokenizer = Tokenizer::borrowed(input);
+        let lexemes: Vec<_> = std::iter::from_fn(|| tokenizer.next_spanned())
+            .map(|t| t.map(|(token, span)| (token, &input[span.start..span.end])))
+            .collect::<Result<_, _>>()?;
+        let strings: Vec<_> = lexemes
+            .iter()
+            .filter(|(t, _)| *t == Stringy)
+            .map(|(_, text)| *text)
+            .collect();
+        assert_eq!(
+            strings,
+            [
+                r#""nil""#,
+                r#""null""#,
+                r#""false""#,
+                r#""true""#,
+                r#""false""#,
+                r#""tru""#,
+                r#""nul""#,
+                r#""ñull""#,
+                r#""f""#
+            ]
+        );
+        let literals: Vec<_> = lexemes
+            .iter()
+            .filter(|(t, _)| matches!(t, Null | Boolean))
+            .map(|(_, text)| *text)
+            .collect();
+        assert_eq!(literals, ["null", "true", "false"]);
+
+        let value = crate::from_str(input)?;
+        assert_eq!(value.to_string(), input.replace(", ", ","));
+        Ok(())
+    }
+
+    #[test]
+    fn test_distinguish_keys() {
+        let input = r#"{"a": "b", "c": ["d", {"e": "f"}], "g": {}}"#;
+        let mut tokenizer = Tokenizer::borrowed(input).distinguish_keys(true);
+        let strings: Vec<_> = std::iter::from_fn(|| tokenizer.next_token())
+            .filter(|t| matches!(t, Stringy | Key))
+            .collect();
+        assert_eq!(strings, [Key, Stringy, Key, Stringy, Key, Stringy, Key]);
+
+        let mut tokenizer = Tokenizer::borrowed(input);
+        assert!(std::iter::from_fn(|| tokenizer.next_token()).all(|t| t != Key));
+    }
+
+    #[test]
+    fn test_reset() {
+        let spans = |tokenizer: &mut Tokenizer| {
+            std::iter::from_fn(|| tokenizer.next_spanned())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+        let mut tokenizer = Tokenizer::new(r#"{"é": [1, 2]} // x"#.to_string())
+            .distinguish_keys(true)
+            .allow_comments(true)
+            .recording_comments();
+        // Leave an object open and a comment recorded.
+        tokenizer.reset("[1] /* c */ {");
+        assert_eq!(spans(&mut tokenizer).len(), 4);
+        let buffer = tokenizer.input.as_ptr();
+        tokenizer.reset(r#"{"a": "b"} // end"#);
+        assert_eq!(tokenizer.input.as_ptr(), buffer);
+        let first = spans(&mut tokenizer);
+        assert_eq!(first[1], (Key, Span { start: 1, end: 4 }));
+        assert_eq!(first.last(), Some(&(EndObject, Span { start: 9, end: 10 })));
+        assert_eq!(tokenizer.take_comments().len(), 1);
+
+        let input = String::from(r#"["a", 1]"#);
+        tokenizer.reset_borrowed(&input);
+        let second = spans(&mut tokenizer);
+        assert_eq!(second[1], (Stringy, Span { start: 1, end: 4 }));
+        assert_eq!(second.len(), 5);
+        assert!(tokenizer.take_comments().is_empty());
+
+        // A failed document leaves nothing behind either.
+        tokenizer.reset("[nul");
+        assert!(std::iter::from_fn(|| tokenizer.next_spanned()).any(|t| t.is_err()));
+        tokenizer.reset("null");
+        assert_eq!(spans(&mut tokenizer), [(Null, Span { start: 0, end: 4 })]);
+    }
+
+    #[test]
+    fn test_lex_errors() {
+        let mut tokenizer = Tokenizer::new("[nul]".to_string());
+        tokenizer.next_spanned();
+        let err = tokenizer.next_spanned().unwrap().unwrap_err();
+        assert_eq!(err.position.offset, 1);
+
+        let mut tokenizer = Tokenizer::new("-x".to_string());
+        assert!(tokenizer.next_spanned().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_control_characters() {
+        for input in ["\0", "[1,\0 2]", "{\"a\":\u{1}1}", "[\u{7f}]", "\u{85}"] {
+            assert!(crate::tokenize(input).is_err(), "{input:?}");
+            assert!(crate::from_str(input).is_err(), "{input:?}");
+            assert!(crate::validate(input).is_err(), "{input:?}");
+        }
+        let err = crate::tokenize("[1,\0 2]").unwrap_err();
+        assert_eq!(err.message, "Unexpected character '\\0'");
+        assert_eq!(err.position.offset, 3);
+        // Strings may only hold them escaped.
+        for (input, offset) in [
+            ("\"a\0b\"", 2),
+            ("[\"x\", \"\u{1f}\"]", 7),
+            ("\"é\u{1}\"", 3),
+        ] {
+            let err = crate::tokenize(input).unwrap_err();
+            assert_eq!(err.position.offset, offset, "{input:?}");
+            assert!(crate::from_str(input).is_err(), "{input:?}");
+            assert!(crate::validate(input).is_err(), "{input:?}");
+        }
+        assert_eq!(
+            crate::tokenize("\"a\0b\"").unwrap_err().message,
+            "Unescaped control character U+0000 in string"
+        );
+        assert!(!crate::is_valid("\"tab\there\""));
+        assert!(crate::is_valid(r#""tab\there\u0000""#));
+        // Scanning resumes after the string.
+        let mut tokenizer = Tokenizer::borrowed("[\"a\nb\", 1]");
+        let results: Vec<_> = std::iter::from_fn(|| tokenizer.next_spanned()).collect();
+        assert!(results[1].is_err());
+        assert_eq!(results[3].as_ref().unwrap().0, Number);
+        assert!(crate::from_str("{\r\n\t\"a\": [1,\r\n2]\r\n}\r\n").is_ok());
+    }
+
+    #[test]
+    fn test_unexpected_characters() {
+        for (input, offset) in [("@", 0), ("[1, #]", 4), (r#"{"a": )}"#, 6), ("[é]", 1)] {
+            let err = crate::tokenize(input).unwrap_err();
+            assert_eq!(err.position.offset, offset, "{input}");
+            assert!(crate::from_str(input).is_err(), "{input}");
+        }
+        assert_eq!(
+            crate::tokenize("[@]").unwrap_err().to_string(),
+            "Unexpected character '@' at line 1, column 2"
+        );
+        // Each error consumes the character, so scanning on terminates.
+        let mut tokenizer = Tokenizer::borrowed("@#) 1");
+        let results: Vec<_> = std::iter::from_fn(|| tokenizer.next_spanned()).collect();
+        assert_eq!(results.len(), 4);
+        assert!(results[..3].iter().all(Result::is_err));
+        assert_eq!(results[3].as_ref().unwrap().0, Number);
+        // Stray closers tokenize fine; the parser rejects them.
+        for input in ["}", "]", "[1]]", "{}}"] {
+            assert!(crate::tokenize(input).is_ok(), "{input}");
+            assert!(crate::from_str(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_max_number_digits() {
+        let options = crate::ParserOptions {
+            max_number_digits: Some(5),
+            ..Default::default()
+        };
+        for input in ["12345", "-1.234", "1e-234", "[1,22,333]"] {
+            assert!(crate::from_str_with(input, &options).is_ok(), "{input}");
+        }
+        for input in ["123456", "[0, -1.23456]", "12e3456"] {
+            let err = crate::validate_with(input, &options).unwrap_err();
+            assert!(
+                err.to_string().contains("Number has more than 5 digits"),
+                "{input}"
+            );
+        }
+        let huge = format!("1{}", "0".repeat(100_000));
+        assert!(crate::from_str_with(&huge, &options).is_err());
+        assert!(crate::from_str(&huge).is_ok());
+    }
+
+    #[test]
+    fn test_comments() {
+        let input = "// head\r\n{\"a\": /* one */ 1, /* two\n lines */\n\"b\": 2 // tail\n}//";
+        let mut tokenizer = Tokenizer::borrowed(input)
+            .allow_comments(true)
+            .recording_comments();
+        let tokens: Vec<_> = std::iter::from_fn(|| tokenizer.next_token()).collect();
+        assert_eq!(tokens.len(), 9);
+        let comments = tokenizer.take_comments();
+        let texts: Vec<_> = comments.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            ["// head", "/* one */", "/* two\n lines */", "// tail", "//"]
+        );
+        for comment in &comments {
+            assert_eq!(&input[comment.span.start..comment.span.end], comment.text);
+        }
+        assert!(tokenizer.take_comments().is_empty());
+
+        for (input, message) in [
+            ("[1 /* open", "Unterminated comment"),
+            ("[1 / 2]", "Expecting `//` or `/*`"),
+        ] {
+            let mut tokenizer = Tokenizer::borrowed(input).allow_comments(true);
+            let err = std::iter::from_fn(|| tokenizer.next_spanned())
+                .find_map(Result::err)
+                .unwrap();
+            assert_eq!(err.message, message);
+        }
+        assert!(crate::tokenize("[1 // x\n]").is_err());
+    }
+
+    #[test]
+    fn test_number_continuations() {
+        for (input, offset) in [("12abc", 2), ("1.2.3", 3), ("1e1e1", 3), ("[0x1]", 2)] {
+            let mut tokenizer = Tokenizer::borrowed(input);
+            let err = std::iter::from_fn(|| tokenizer.next_spanned())
+                .find_map(Result::err)
+                .unwrap();
+            assert_eq!(err.position.offset, offset, "{input}");
+            assert!(crate::validate(input).is_err());
+            // The fast scanner takes `1.2.3` whole, but it still can't
+            // become a number.
+            let options = crate::ParserOptions {
+                fast_numbers: true,
+                ..Default::default()
+            };
+            assert!(crate::from_str_with(input, &options).is_err());
+        }
+        assert!(crate::validate("[1.5,2e3]").is_ok());
+        let err = crate::from_str("1.2.3").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lex error: Unexpected '.' after number at line 1, column 4"
+        );
+    }
+
+    #[test]
+    fn test_leading_zeros() {
+        for input in ["01", "-01", "[00]", "012.5", "-007e1"] {
+            let err = crate::tokenize(input).unwrap_err();
+            assert_eq!(err.message, "Leading zeros are not allowed in numbers");
+            assert!(crate::from_str(input).is_err(), "{input}");
+            let fast = crate::ParserOptions {
+                fast_numbers: true,
+                ..Default::default()
+            };
+            assert!(crate::validate_with(input, &fast).is_err(), "{input}");
+        }
+        for input in ["0", "-0", "0.01", "0e5", "[0, 10, -0.0]"] {
+            assert!(crate::from_str(input).is_ok(), "{input}");
+        }
+        let lenient = crate::ParserOptions {
+            allow_leading_zeros: true,
+            ..Default::default()
+        };
+        let value = crate::from_str_with("[01, -007.5]", &lenient).unwrap();
+        assert_eq!(value.to_string(), "[1,-7.5]");
+        let separated = crate::ParserOptions {
+            allow_digit_separators: true,
+            ..Default::default()
+        };
+        assert!(crate::from_str_with("0_1", &separated).is_err());
+        assert!(crate::from_str_with("1_0", &separated).is_ok());
+    }
+
+    #[test]
+    fn test_ascii_fast_path() {
+        let input = r#"{"a": [1, -2.5e3, "x y", true, null], "b": "unterminated}"#;
+        let all = |ascii| {
+            let mut tokenizer = Tokenizer::borrowed(input);
+            assert!(tokenizer.ascii);
+            tokenizer.ascii = ascii;
+            std::iter::from_fn(|| tokenizer.next_spanned()).collect::<Vec<_>>()
+        };
+        assert_eq!(all(true), all(false));
+
+        let input = r#"["é", "ü"]"#;
+        let mut tokenizer = Tokenizer::borrowed(input);
+        assert!(!tokenizer.ascii);
+        tokenizer.next_spanned();
+        let (_, span) = tokenizer.next_spanned().unwrap().unwrap();
+        assert_eq!(&input[span.start..span.end], r#""é""#);
+    }
+
+    #[test]
+    fn test_escaped_strings() {
+        for (string, on_ascii_path) in [
+            (r#""a\"b""#, true),
+            (r#""\\""#, true),
+            (r#""\\\"""#, true),
+            (r#""\/""#, true),
+            (r#""\u0041\u20ac""#, true),
+            (r#""\ud83d\ude00""#, true),
+            (r#""\"\\\/\b\f\n\r\t""#, true),
+            (r#""é\"😀\\""#, false),
+        ] {
+            let input = format!(r#"[{string}, {{"k\"": 1}}]"#);
+            let mut tokenizer = Tokenizer::borrowed(&input);
+            assert_eq!(tokenizer.ascii, on_ascii_path);
+            let tokens: Vec<_> = std::iter::from_fn(|| tokenizer.next_spanned())
+                .map(|t| {
+                    let (token, span) = t.unwrap();
+                    (token, &input[span.start..span.end])
+                })
+                .collect();
+            assert_eq!(
+                tokens,
+                [
+                    (BeginArray, "["),
+                    (Stringy, string),
+                    (ValueSeparator, ","),
+                    (BeginObject, "{"),
+                    (Stringy, r#""k\"""#),
+                    (NameSeparator, ":"),
+                    (Number, "1"),
+                    (EndObject, "}"),
+                    (EndArray, "]"),
+                ],
+                "{input}"
+            );
+        }
+        for input in [r#""\""#, r#""a\"#, r#""é\""#] {
+            let mut tokenizer = Tokenizer::borrowed(input);
+            let err = tokenizer.next_spanned().unwrap().unwrap_err();
+            assert_eq!(err.message, "Unterminated string");
+        }
+        let value = crate::from_str(r#"["a\"b", "\\", {"\"": "\\\""}]"#).unwrap();
+        assert_eq!(value.to_string(), r#"["a\"b","\\",{"\"":"\\\""}]"#);
+    }
+
+    #[test]
+    fn test_unterminated_string() {
+        let mut tokenizer = Tokenizer::new("{\"a\": \"unterminated}".to_string());
+        let tokens: Vec<_> = std::iter::from_fn(|| tokenizer.next_spanned()).collect();
+        assert_eq!(tokens.len(), 4);
+        let err = tokens[3].clone().unwrap_err();
+        assert_eq!(err.message, "Unterminated string");
+        assert_eq!(err.position.offset, 6);
+    }
+}
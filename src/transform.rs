@@ -0,0 +1,253 @@
+use crate::error::{Error, LexError, ParseError, Position};
+use crate::expect::Expect;
+use crate::number::Number;
+use crate::parser::decode_string;
+use crate::pointer::push_token;
+use crate::tokenizer::{Span, Token::*, Tokenizer};
+use crate::value::JsonValue;
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// An open container. `base` is the length of the path to it.
+enum Frame {
+    Object { base: usize },
+    Array { base: usize, index: usize },
+}
+
+/// Copies the document `input` to `out`, letting `f` rewrite scalar values
+/// on the way, without building a tree.
+///
+/// `f` is called once for every string, number, boolean and `null`, in
+/// document order, with the value's JSON Pointer (e.g. `/users/0/email`)
+/// and its decoded value. Returning `Some` replaces the value's source text
+/// with the compact serialization of the returned value, which may be of
+/// any type; returning `None` keeps it. All other text, whitespace
+/// included, is copied unchanged.
+///
+/// Memory use is bounded by the nesting depth and the longest path, not by
+/// the document size. The document is checked as it is copied; on error,
+/// `out` holds the output up to the last replacement. Errors are an
+/// [`Error`] or an `io::Error` from `out`. Output is written piecemeal, so
+/// wrap unbuffered sinks in a `BufWriter`.
+pub fn transform<W, F>(input: &str, mut out: W, mut f: F) -> Result<()>
+where
+    W: Write,
+    F: FnMut(&str, &JsonValue) -> Option<JsonValue>,
+{
+    let error = |offset, message: String| {
+        Error::Parse(ParseError::new(message, Position::locate(input, offset)))
+    };
+
+    let mut tokenizer = Tokenizer::borrowed(input);
+    let mut stack = Vec::new();
+    let mut path = String::new();
+    let mut expect = Expect::Value;
+    // Input before this offset has been written out.
+    let mut copied = 0;
+    while let Some((token, span)) = tokenizer.next_spanned().transpose()? {
+        let in_object = matches!(stack.last(), Some(Frame::Object { .. }));
+        let in_array = matches!(stack.last(), Some(Frame::Array { .. }));
+        expect = match (expect, token) {
+            (Expect::KeyOrEnd | Expect::Key, Stringy) => {
+                let key = decode(input, span)?;
+                if let Some(Frame::Object { base }) = stack.last() {
+                    path.truncate(*base);
+                }
+                push_token(&mut path, &key);
+                Expect::Colon
+            }
+            (Expect::Colon, NameSeparator) => Expect::Value,
+            (Expect::SeparatorOrEnd, ValueSeparator) => match stack.last_mut() {
+                Some(Frame::Array { base, index }) => {
+                    *index += 1;
+                    path.truncate(*base);
+                    write!(path, "/{index}").unwrap();
+                    Expect::Value
+                }
+                _ => Expect::Key,
+            },
+            (Expect::KeyOrEnd | Expect::SeparatorOrEnd, EndObject) if in_object => {
+                close(&mut stack, &mut path)
+            }
+            (Expect::ValueOrEnd | Expect::SeparatorOrEnd, EndArray) if in_array => {
+                close(&mut stack, &mut path)
+            }
+            (Expect::Value | Expect::ValueOrEnd, BeginObject) => {
+                stack.push(Frame::Object { base: path.len() });
+                Expect::KeyOrEnd
+            }
+            (Expect::Value | Expect::ValueOrEnd, BeginArray) => {
+                stack.push(Frame::Array {
+                    base: path.len(),
+                    index: 0,
+                });
+                path.push_str("/0");
+                Expect::ValueOrEnd
+            }
+            (Expect::Value | Expect::ValueOrEnd, Number | Stringy | Boolean | Null) => {
+                let text = &input[span.start..span.end];
+                let value = match token {
+                    Number => JsonValue::Number(number(input, span)?),
+                    Stringy => JsonValue::String(decode(input, span)?),
+                    Boolean => JsonValue::Bool(text == "true"),
+                    _ => JsonValue::Null,
+                };
+                if let Some(replacement) = f(&path, &value) {
+                    out.write_all(&input.as_bytes()[copied..span.start])?;
+                    write!(out, "{replacement}")?;
+                    copied = span.end;
+                }
+                after_value(&stack)
+            }
+            (expect, t) => {
+                let expected = expect.describe(in_object);
+                return Err(error(span.start, format!("Expecting {expected}. Got {t:?}")).into());
+            }
+        };
+    }
+    if expect != Expect::Nothing {
+        let in_object = matches!(stack.last(), Some(Frame::Object { .. }));
+        let message = format!("Expecting {}. Got end of input", expect.describe(in_object));
+        return Err(error(input.len(), message).into());
+    }
+    out.write_all(&input.as_bytes()[copied..])?;
+    Ok(())
+}
+
+fn close(stack: &mut Vec<Frame>, path: &mut String) -> Expect {
+    if let Some(Frame::Object { base } | Frame::Array { base, .. }) = stack.pop() {
+        path.truncate(base);
+    }
+    after_value(stack)
+}
+
+fn after_value(stack: &[Frame]) -> Expect {
+    if stack.is_empty() {
+        Expect::Nothing
+    } else {
+        Expect::SeparatorOrEnd
+    }
+}
+
+/// The tokenizer only passes valid literals, but a disagreement with
+/// `from_literal` is reported like any malformed token rather than panicking.
+fn number(input: &str, span: Span) -> std::result::Result<Number, Error> {
+    let text = &input[span.start..span.end];
+    Number::from_literal(text).ok_or_else(|| {
+        let message = format!("Invalid number {text:?}");
+        Error::Lex(LexError::new(message, Position::locate(input, span.start)))
+    })
+}
+
+fn decode(input: &str, span: Span) -> std::result::Result<String, Error> {
+    decode_string(&input[span.start..span.end], None).map_err(|e| {
+        let message = match e {
+            crate::parser::DecodeError::Malformed(message) => message,
+            crate::parser::DecodeError::TooLong => unreachable!("no limit was set"),
+        };
+        Error::Lex(LexError::new(message, Position::locate(input, span.start)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run<F>(input: &str, f: F) -> Result<String>
+    where
+        F: FnMut(&str, &JsonValue) -> Option<JsonValue>,
+    {
+        let mut out = Vec::new();
+        transform(input, &mut out, f)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_redact() -> Result<()> {
+        let input = r#"{
+  "users": [
+    {"name": "alice", "password": "hunter2", "age": 30},
+    {"name": "bob", "password": "pass", "tags": [], "nested": {"password": 1}}
+  ],
+  "pass/word": "keep"
+}"#;
+        let mut seen = Vec::new();
+        let output = run(input, |path, value| {
+            seen.push(path.to_string());
+            path.ends_with("/password")
+                .then(|| JsonValue::String("***".to_string()))
+                .filter(|_| value.as_str().is_some())
+        })?;
+        assert_eq!(
+            output,
+            input
+                .replace(r#""hunter2""#, r#""***""#)
+                .replace(r#""pass""#, r#""***""#)
+        );
+        assert_eq!(
+            seen,
+            [
+                "/users/0/name",
+                "/users/0/password",
+                "/users/0/age",
+                "/users/1/name",
+                "/users/1/password",
+                "/users/1/nested/password",
+                "/pass~1word",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_types() -> Result<()> {
+        let output = run(r#"[1, "2", [3.5], null]"#, |path, value| match value {
+            JsonValue::Number(n) => Some(JsonValue::Number((n.as_f64() * 2.0).into())),
+            JsonValue::Null => Some(crate::from_str(&format!(r#"{{"at": "{path}"}}"#)).unwrap()),
+            _ => None,
+        })?;
+        assert_eq!(output, r#"[2.0, "2", [7.0], {"at":"/3"}]"#);
+        assert_eq!(run("  true ", |_, _| None)?, "  true ");
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed() {
+        for input in [
+            "",
+            "[1,]",
+            "[1 2]",
+            r#"{"a" 1}"#,
+            r#"{"a": 1"#,
+            r#"{1: 2}"#,
+            "[1]]",
+            "[}",
+            "1 2",
+            r#"["\x"]"#,
+        ] {
+            assert!(run(input, |_, _| None).is_err(), "{input}");
+        }
+        for (input, message) in [
+            (
+                "[1 2]",
+                "Expecting `,` or `]`. Got Number at line 1, column 4",
+            ),
+            (r#"{"a": 1]"#, "Expecting `,` or `}`. Got EndArray"),
+            (r#"{"a" 1}"#, "Expecting `:`. Got Number"),
+            ("{1: 2}", "Expecting an object key or `}`. Got Number"),
+            (r#"{"a": 1,}"#, "Expecting an object key. Got EndObject"),
+            ("[1,]", "Expecting a value. Got EndArray"),
+            ("[", "Expecting a value or `]`. Got end of input"),
+            ("1 2", "Expecting end of input. Got Number"),
+        ] {
+            let err = run(input, |_, _| None).unwrap_err().to_string();
+            assert!(err.contains(message), "{input}: {err}");
+        }
+
+        // A literal `from_literal` can't read is an error, not a panic.
+        let err = number("[1.e400]", Span { start: 1, end: 7 }).unwrap_err();
+        assert!(matches!(err, Error::Lex(_)));
+        assert_eq!(err.position().offset, 1);
+    }
+}
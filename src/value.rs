@@ -0,0 +1,785 @@
+use crate::backing::{ObjectBacking, Ordered};
+use crate::number::Number;
+use crate::pointer::Segment;
+use std::ops::ControlFlow;
+
+/// The JSON type of a value, as named by [`JsonValue::type_name`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+/// A parsed JSON document.
+///
+/// Objects are stored as `B` says; by default, [`Ordered`], they keep their
+/// members in source order.
+///
+/// Equality is structural, with object members compared in order (for
+/// [`Ordered`]) or by key.
+#[derive(Default)]
+pub enum JsonValue<B: ObjectBacking = Ordered> {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<JsonValue<B>>),
+    Object(B::Map<JsonValue<B>>),
+}
+
+impl<B: ObjectBacking> JsonValue<B> {
+    /// Looks up `key` in an object. `None` for missing keys and non-objects.
+    pub fn get(&self, key: &str) -> Option<&JsonValue<B>> {
+        match self {
+            JsonValue::Object(members) => B::lookup(members, key),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            JsonValue::Null => ValueKind::Null,
+            JsonValue::Bool(_) => ValueKind::Bool,
+            JsonValue::Number(_) => ValueKind::Number,
+            JsonValue::String(_) => ValueKind::String,
+            JsonValue::Array(_) => ValueKind::Array,
+            JsonValue::Object(_) => ValueKind::Object,
+        }
+    }
+
+    /// Name of the value's JSON type, as used in the crate's error messages:
+    /// `"null"`, `"bool"`, `"number"`, `"string"`, `"array"` or `"object"`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "bool",
+            JsonValue::Number(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            JsonValue::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// See [`Number::as_i64`].
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number()?.as_i64()
+    }
+
+    /// See [`Number::as_u64`].
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number()?.as_u64()
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(Number::as_f64)
+    }
+
+    /// A number's literal exactly as written in the source, e.g. `1.10` or
+    /// `1E+2`. Only available for numbers parsed under
+    /// [`ParserOptions::keep_number_text`](crate::ParserOptions::keep_number_text);
+    /// `None` for other numbers and non-numbers.
+    pub fn as_number_raw(&self) -> Option<&str> {
+        self.as_number()?.source_text()
+    }
+
+    /// Whether this is an object with a member named `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// How deeply containers nest: 0 for scalars, 1 for a container holding
+    /// only scalars (or nothing), and so on.
+    pub fn depth(&self) -> usize {
+        let mut deepest = 0;
+        let mut stack = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let children: Box<dyn Iterator<Item = &JsonValue<B>>> = match node {
+                JsonValue::Array(elements) => Box::new(elements.iter()),
+                JsonValue::Object(members) => Box::new(B::iter(members).map(|(_, v)| v)),
+                _ => continue,
+            };
+            deepest = deepest.max(depth + 1);
+            stack.extend(children.map(|child| (child, depth + 1)));
+        }
+        deepest
+    }
+}
+
+impl JsonValue {
+    /// `{}`
+    pub fn empty_object() -> Self {
+        JsonValue::Object(Vec::new())
+    }
+
+    /// `[]`
+    pub fn empty_array() -> Self {
+        JsonValue::Array(Vec::new())
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        match self {
+            JsonValue::Object(members) => {
+                members.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+            }
+            _ => None,
+        }
+    }
+
+    /// An object's members sorted by key, in byte order, leaving the stored
+    /// order alone. `None` for non-objects. The sort is stable, so repeated
+    /// keys stay in source order.
+    pub fn as_object_entries_sorted(&self) -> Option<Vec<(&str, &JsonValue)>> {
+        let JsonValue::Object(members) = self else {
+            return None;
+        };
+        let mut entries: Vec<_> = members.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        entries.sort_by_key(|&(k, _)| k);
+        Some(entries)
+    }
+
+    /// Consumes an object, yielding its members in order so they can be
+    /// moved out without cloning. Yields nothing for non-objects.
+    pub fn into_object_entries(self) -> std::vec::IntoIter<(String, JsonValue)> {
+        match self {
+            JsonValue::Object(members) => members.into_iter(),
+            _ => Vec::new().into_iter(),
+        }
+    }
+
+    /// Consumes an array, yielding its elements in order. Yields nothing
+    /// for non-arrays.
+    pub fn into_array(self) -> std::vec::IntoIter<JsonValue> {
+        match self {
+            JsonValue::Array(elements) => elements.into_iter(),
+            _ => Vec::new().into_iter(),
+        }
+    }
+
+    /// Estimates the heap memory owned by the value, in bytes, e.g. to
+    /// decide whether a parsed document is worth caching.
+    ///
+    /// Counts the allocated capacity of every string, key and container:
+    /// an array holds `size_of::<JsonValue>()` bytes per slot, and an
+    /// object `size_of::<(String, JsonValue)>()` per member slot, plus
+    /// what the elements and members own themselves. The value itself is
+    /// not counted, as it may live inline in something else; add
+    /// `size_of::<JsonValue>()` for a boxed root. Allocator bookkeeping
+    /// and rounding are not counted either, so the real figure is somewhat
+    /// higher.
+    pub fn memory_footprint(&self) -> usize {
+        let mut total = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            total += match node {
+                JsonValue::Null | JsonValue::Bool(_) => 0,
+                JsonValue::Number(n) => n.source_text().map_or(0, str::len),
+                JsonValue::String(s) => s.capacity(),
+                JsonValue::Array(elements) => {
+                    stack.extend(elements);
+                    elements.capacity() * size_of::<JsonValue>()
+                }
+                JsonValue::Object(members) => {
+                    stack.extend(members.iter().map(|(_, v)| v));
+                    members.capacity() * size_of::<(String, JsonValue)>()
+                        + members.iter().map(|(k, _)| k.capacity()).sum::<usize>()
+                }
+            };
+        }
+        total
+    }
+
+    /// Every string value in the tree that contains `needle`, in document
+    /// order. Matching is on the decoded text, so `"\u0041"` in the source
+    /// matches a search for `A`. Object keys are not searched.
+    pub fn find_strings_containing(&self, needle: &str) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                JsonValue::String(s) if s.contains(needle) => found.push(s.clone()),
+                JsonValue::Array(elements) => stack.extend(elements.iter().rev()),
+                JsonValue::Object(members) => stack.extend(members.iter().rev().map(|(_, v)| v)),
+                _ => {}
+            }
+        }
+        found
+    }
+
+    /// Every value of an object member named `key`, at any depth, in
+    /// document order: JSONPath's `$..key`. Array elements are searched
+    /// too, and so is each match, so a member can be found inside another.
+    pub fn find_all(&self, key: &str) -> Vec<&JsonValue> {
+        let mut found = Vec::new();
+        // Each node with whether it is the value of a matching member.
+        let mut stack = vec![(self, false)];
+        while let Some((node, matched)) = stack.pop() {
+            if matched {
+                found.push(node);
+            }
+            match node {
+                JsonValue::Array(elements) => {
+                    stack.extend(elements.iter().rev().map(|e| (e, false)))
+                }
+                JsonValue::Object(members) => {
+                    stack.extend(members.iter().rev().map(|(k, v)| (v, k == key)))
+                }
+                _ => {}
+            }
+        }
+        found
+    }
+
+    /// Calls `f` on every node of the tree, including `self`, in pre-order:
+    /// a container is visited before its children, so if `f` replaces a
+    /// node, the replacement's children are what get visited next. Object
+    /// keys are not passed to `f`. Uses an explicit stack, so deep trees
+    /// cannot overflow the call stack.
+    pub fn map_values<F: FnMut(&mut JsonValue)>(&mut self, mut f: F) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            f(node);
+            match node {
+                JsonValue::Array(elements) => stack.extend(elements.iter_mut().rev()),
+                JsonValue::Object(members) => {
+                    stack.extend(members.iter_mut().rev().map(|(_, v)| v))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Turns every float in the tree that is a whole number in `i64` range
+    /// into an integer, so `3.0` serializes as `3`, e.g. before
+    /// canonicalizing data from a source that writes only floats. Other
+    /// floats, such as `3.5` or `1e30`, are unchanged, as are numbers kept
+    /// as text by [`ParserOptions::keep_number_text`]. So is `-0.0`, whose
+    /// sign an integer would lose.
+    ///
+    /// [`ParserOptions::keep_number_text`]: crate::ParserOptions::keep_number_text
+    pub fn retype_numbers(&mut self) {
+        self.map_values(|node| {
+            if let JsonValue::Number(n @ Number::Float(_)) = node
+                && n.as_f64().to_bits() != (-0.0f64).to_bits()
+                && let Some(i) = n.as_i64()
+            {
+                *n = Number::Int(i);
+            }
+        });
+    }
+
+    /// Calls `f` on every node with its path from `self`, in pre-order and
+    /// document order, stopping as soon as `f` breaks; the break value is
+    /// returned. Uses an explicit stack, so deep trees cannot overflow the
+    /// call stack.
+    ///
+    /// ```
+    /// use json_parser::{JsonValue, Segment, from_str};
+    /// use std::ops::ControlFlow;
+    ///
+    /// let value = from_str(r#"{"a": [1, {"id": 7}], "id": 8}"#).unwrap();
+    /// let first = value.walk(|path, node| match path.last() {
+    ///     Some(Segment::Key("id")) => ControlFlow::Break((path.len(), node)),
+    ///     _ => ControlFlow::Continue(()),
+    /// });
+    /// assert_eq!(first, ControlFlow::Break((3, &JsonValue::from(7i64))));
+    /// ```
+    pub fn walk<'a, B, F>(&'a self, mut f: F) -> ControlFlow<B>
+    where
+        F: FnMut(&[Segment<'a>], &'a JsonValue) -> ControlFlow<B>,
+    {
+        let mut path = Vec::new();
+        // Each node with the length of its parent's path and its own
+        // segment, `None` for the root.
+        let mut stack = vec![(self, 0, None)];
+        while let Some((node, depth, segment)) = stack.pop() {
+            path.truncate(depth);
+            path.extend(segment);
+            f(&path, node)?;
+            let depth = path.len();
+            match node {
+                JsonValue::Array(elements) => stack.extend(
+                    elements
+                        .iter()
+                        .enumerate()
+                        .rev()
+                        .map(|(i, e)| (e, depth, Some(Segment::Index(i)))),
+                ),
+                JsonValue::Object(members) => stack.extend(
+                    members
+                        .iter()
+                        .rev()
+                        .map(|(k, v)| (v, depth, Some(Segment::Key(k.as_str())))),
+                ),
+                _ => {}
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Checks every object key in the tree against `pred`, e.g. that keys
+    /// are usable as column names. On failure, returns the pointer of each
+    /// member whose key fails, in document order.
+    pub fn validate_keys<F: Fn(&str) -> bool>(&self, pred: F) -> Result<(), Vec<String>> {
+        let mut failures = Vec::new();
+        let _ = self.walk(|path, _| {
+            if let Some(Segment::Key(key)) = path.last()
+                && !pred(key)
+            {
+                failures.push(crate::pointer::join(path));
+            }
+            ControlFlow::<()>::Continue(())
+        });
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(b: bool) -> Self {
+        JsonValue::Bool(b)
+    }
+}
+
+impl From<Number> for JsonValue {
+    fn from(n: Number) -> Self {
+        JsonValue::Number(n)
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(i: i64) -> Self {
+        JsonValue::Number(i.into())
+    }
+}
+
+impl From<i32> for JsonValue {
+    fn from(i: i32) -> Self {
+        JsonValue::Number(i.into())
+    }
+}
+
+impl From<u64> for JsonValue {
+    fn from(u: u64) -> Self {
+        JsonValue::Number(u.into())
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(f: f64) -> Self {
+        JsonValue::Number(f.into())
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(s: &str) -> Self {
+        JsonValue::String(s.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(s: String) -> Self {
+        JsonValue::String(s)
+    }
+}
+
+/// Comparisons against plain Rust values, mostly for tests and guards. A
+/// value of another JSON type is never equal; numbers compare numerically.
+///
+/// ```
+/// # use json_parser::from_str;
+/// let value = from_str(r#"{"name": "alice", "count": 3, "ok": true}"#).unwrap();
+/// assert!(value.get("name").unwrap() == "alice");
+/// assert_eq!(value.pointer("/count").unwrap(), &3);
+/// assert_eq!(value.pointer("/count").unwrap(), &3.0);
+/// assert!(*value.get("ok").unwrap() == true);
+/// assert!(*value.get("count").unwrap() != "3");
+/// ```
+impl PartialEq<str> for JsonValue {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == Some(other)
+    }
+}
+
+impl PartialEq<&str> for JsonValue {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == Some(*other)
+    }
+}
+
+impl PartialEq<String> for JsonValue {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == Some(other)
+    }
+}
+
+impl PartialEq<bool> for JsonValue {
+    fn eq(&self, other: &bool) -> bool {
+        self.as_bool() == Some(*other)
+    }
+}
+
+impl PartialEq<i64> for JsonValue {
+    fn eq(&self, other: &i64) -> bool {
+        self.as_i64() == Some(*other)
+    }
+}
+
+impl PartialEq<f64> for JsonValue {
+    fn eq(&self, other: &f64) -> bool {
+        self.as_f64() == Some(*other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owned_iteration() -> anyhow::Result<()> {
+        let value = crate::from_str(r#"{"a": [1, "x"], "b": null}"#)?;
+        let mut entries = value.into_object_entries();
+        let (key, array) = entries.next().unwrap();
+        assert_eq!(key, "a");
+        let elements: Vec<_> = array.into_array().collect();
+        assert_eq!(elements, [JsonValue::from(1i64), JsonValue::from("x")]);
+        assert_eq!(entries.next(), Some(("b".to_string(), JsonValue::Null)));
+        assert_eq!(entries.next(), None);
+
+        assert_eq!(JsonValue::from("s").into_array().len(), 0);
+        assert_eq!(JsonValue::empty_array().into_object_entries().len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_footprint() -> anyhow::Result<()> {
+        let value = crate::from_str(r#"{"ab": [1, "xyz", []], "c": {}}"#)?;
+        let (slot, member) = (size_of::<JsonValue>(), size_of::<(String, JsonValue)>());
+        // Two members with keys of 2 and 1 bytes, three array slots, and
+        // `"xyz"`, assuming the parser allocates exactly.
+        assert_eq!(value.memory_footprint(), 2 * member + 3 + 3 * slot + 3);
+        assert_eq!(JsonValue::from(true).memory_footprint(), 0);
+        assert_eq!(JsonValue::empty_object().memory_footprint(), 0);
+
+        let mut value = JsonValue::empty_array();
+        let JsonValue::Array(elements) = &mut value else {
+            unreachable!()
+        };
+        elements.reserve_exact(10);
+        assert_eq!(value.memory_footprint(), 10 * slot);
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_constructors() {
+        assert_eq!(<JsonValue>::default(), JsonValue::Null);
+        assert_eq!(JsonValue::empty_object().to_string(), "{}");
+        assert_eq!(JsonValue::empty_array().to_string(), "[]");
+    }
+
+    #[test]
+    fn test_scalar_accessors() -> anyhow::Result<()> {
+        let value = crate::from_str(r#"["a", true, 1]"#)?;
+        assert_eq!(value.pointer("/0").and_then(JsonValue::as_str), Some("a"));
+        assert_eq!(value.pointer("/1").and_then(JsonValue::as_bool), Some(true));
+        assert_eq!(value.pointer("/2").and_then(JsonValue::as_str), None);
+        assert_eq!(value.pointer("/0").and_then(JsonValue::as_bool), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_as_number_raw() -> anyhow::Result<()> {
+        let options = crate::ParserOptions {
+            keep_number_text: true,
+            ..Default::default()
+        };
+        let value = crate::from_str_with(r#"[1.10, 1E+2, "1.10"]"#, &options)?;
+        assert_eq!(
+            value.pointer("/0").and_then(JsonValue::as_number_raw),
+            Some("1.10")
+        );
+        assert_eq!(
+            value.pointer("/1").and_then(JsonValue::as_number_raw),
+            Some("1E+2")
+        );
+        assert_eq!(value.pointer("/2").and_then(JsonValue::as_number_raw), None);
+        let value = crate::from_str("[1.10]")?;
+        assert_eq!(value.pointer("/0").and_then(JsonValue::as_number_raw), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_name() -> anyhow::Result<()> {
+        let value = crate::from_str(r#"[null, true, 1.5, "s", [], {}]"#)?;
+        let JsonValue::Array(elements) = value else {
+            unreachable!()
+        };
+        let names: Vec<_> = elements.iter().map(JsonValue::type_name).collect();
+        assert_eq!(
+            names,
+            ["null", "bool", "number", "string", "array", "object"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_entries_sorted() -> anyhow::Result<()> {
+        let input = r#"{"b": 1, "Z": 2, "a": 3, "é": 4, "aa": 5}"#;
+        let value = crate::from_str(input)?;
+        let keys: Vec<_> = value
+            .as_object_entries_sorted()
+            .unwrap()
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys, ["Z", "a", "aa", "b", "é"]);
+        assert_eq!(value.to_string(), input.replace(' ', ""));
+        assert_eq!(crate::from_str("[1]")?.as_object_entries_sorted(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_contains_key() -> anyhow::Result<()> {
+        let value = crate::from_str(r#"{"a": null, "b": {"c": 1}}"#)?;
+        assert!(value.contains_key("a"));
+        assert!(!value.contains_key("c"));
+        assert!(!JsonValue::empty_array().contains_key("a"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_strings_containing() -> anyhow::Result<()> {
+        let value = crate::from_str(
+            r#"{"\u0041": "no", "a": ["\u0041bc", "xyz", {"b": "cAb"}], "c": "A\n", "d": 1}"#,
+        )?;
+        assert_eq!(value.find_strings_containing("A"), ["Abc", "cAb", "A\n"]);
+        assert_eq!(value.find_strings_containing("A\n"), ["A\n"]);
+        assert!(value.find_strings_containing("\\u0041").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_all() -> anyhow::Result<()> {
+        let value = crate::from_str(
+            r#"{"user_id": 1, "events": [{"user_id": 2, "meta": {"user_id": {"user_id": 3}}}, [{"user_id": null}]], "other": {"id": 4}}"#,
+        )?;
+        let found: Vec<_> = value
+            .find_all("user_id")
+            .into_iter()
+            .map(JsonValue::to_string)
+            .collect();
+        assert_eq!(found, ["1", "2", r#"{"user_id":3}"#, "3", "null"]);
+        assert!(value.find_all("missing").is_empty());
+        assert!(
+            crate::from_str(r#"["user_id"]"#)?
+                .find_all("user_id")
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_keys() -> anyhow::Result<()> {
+        let identifier = |key: &str| {
+            let mut chars = key.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+        let value = crate::from_str(
+            r#"{"id": 1, "first name": "a", "rows": [{"_ok": 0, "2x": {"a/b": null}}], "": []}"#,
+        )?;
+        assert_eq!(
+            value.validate_keys(identifier),
+            Err(vec![
+                "/first name".to_string(),
+                "/rows/0/2x".to_string(),
+                "/rows/0/2x/a~1b".to_string(),
+                "/".to_string(),
+            ])
+        );
+        let value = crate::from_str(r#"{"id": 1, "tags": ["not a key"], "nested": {"x_1": 2}}"#)?;
+        assert_eq!(value.validate_keys(identifier), Ok(()));
+        assert_eq!(crate::from_str("[]")?.validate_keys(|_| false), Ok(()));
+        Ok(())
+    }
+
+    /// Nests `[{"k": ...}]` `depth` times around `leaf`.
+    fn nested(depth: usize, leaf: JsonValue) -> JsonValue {
+        (0..depth).fold(leaf, |inner, _| {
+            JsonValue::Array(vec![JsonValue::Object(vec![("k".to_string(), inner)])])
+        })
+    }
+
+    /// Drops `value` one level at a time; the derived drop would recurse.
+    fn dismantle(value: JsonValue) {
+        let mut stack = vec![value];
+        while let Some(mut node) = stack.pop() {
+            match &mut node {
+                JsonValue::Array(elements) => stack.append(elements),
+                JsonValue::Object(members) => stack.extend(members.drain(..).map(|(_, v)| v)),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_deep_equality() {
+        let a = nested(100_000, JsonValue::Bool(true));
+        let b = nested(100_000, JsonValue::Bool(true));
+        let c = nested(100_000, JsonValue::Bool(false));
+        assert!(a == b);
+        assert!(a != c);
+        let shallow = nested(99_999, JsonValue::Bool(true));
+        assert!(a != shallow);
+        for value in [a, b, c, shallow] {
+            dismantle(value);
+        }
+    }
+
+    #[test]
+    fn test_deep_clone() {
+        let value = nested(100_000, JsonValue::Bool(true));
+        let copy = value.clone();
+        assert!(copy == value);
+        dismantle(value);
+        dismantle(copy);
+    }
+
+    #[test]
+    fn test_equality() -> anyhow::Result<()> {
+        let value = crate::from_str(r#"{"a": [1, "x", null], "b": {"c": false}}"#)?;
+        assert_eq!(value, value.clone());
+        for other in [
+            r#"{"b": {"c": false}, "a": [1, "x", null]}"#,
+            r#"{"a": [1, "x"], "b": {"c": false}}"#,
+            r#"{"a": [1, "x", null], "b": {"d": false}}"#,
+            r#"{"a": [1.0, "x", null], "b": {"c": false}}"#,
+        ] {
+            assert_ne!(value, crate::from_str(other)?, "{other}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_walk() -> anyhow::Result<()> {
+        let value = crate::from_str(r#"{"a": [1, {"b": null}], "c": "x"}"#)?;
+        let mut visited = Vec::new();
+        let flow = value.walk(|path, node| {
+            let ptr = crate::pointer::join(path);
+            assert_eq!(value.deep_get(path), Some(node));
+            visited.push(format!("{ptr}={}", node.type_name()));
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+        assert_eq!(
+            visited,
+            [
+                "=object",
+                "/a=array",
+                "/a/0=number",
+                "/a/1=object",
+                "/a/1/b=null",
+                "/c=string"
+            ]
+        );
+
+        let mut seen = 0;
+        let flow = value.walk(|_, node| {
+            seen += 1;
+            match node {
+                JsonValue::Null => ControlFlow::Break("found"),
+                _ => ControlFlow::Continue(()),
+            }
+        });
+        assert_eq!(flow, ControlFlow::Break("found"));
+        assert_eq!(seen, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_retype_numbers() -> anyhow::Result<()> {
+        let mut value =
+            crate::from_str(r#"{"a": 3.0, "b": [3.5, 1e30, -2e0, {"c": -0.0}], "d": 7}"#)?;
+        value.retype_numbers();
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":3,"b":[3.5,1e30,-2,{"c":-0.0}],"d":7}"#
+        );
+        assert_eq!(
+            value.get("a").and_then(JsonValue::as_number),
+            Some(&Number::Int(3))
+        );
+        assert!(
+            value
+                .pointer("/b/0")
+                .and_then(JsonValue::as_number)
+                .unwrap()
+                .is_float()
+        );
+        assert!(
+            value
+                .pointer("/b/1")
+                .and_then(JsonValue::as_number)
+                .unwrap()
+                .is_float()
+        );
+
+        let options = crate::ParserOptions {
+            keep_number_text: true,
+            ..Default::default()
+        };
+        let mut value = crate::from_str_with("[3.0]", &options)?;
+        value.retype_numbers();
+        assert_eq!(value.to_string(), "[3.0]");
+        // Negative zero keeps its sign, whether written as `-0` or `-0.0`.
+        let mut value = crate::from_str("[-0, -0.0, 0.0]")?;
+        value.retype_numbers();
+        assert_eq!(value.to_string(), "[-0,-0.0,0]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_values() -> anyhow::Result<()> {
+        let mut value = crate::from_str(r#"{"Name": "ALICE", "tags": ["Admin", 1, {"x": "Y"}]}"#)?;
+        value.map_values(|v| {
+            if let JsonValue::String(s) = v {
+                *s = s.to_lowercase();
+            }
+        });
+        assert_eq!(
+            value.to_string(),
+            r#"{"Name":"alice","tags":["admin",1,{"x":"y"}]}"#
+        );
+        Ok(())
+    }
+}